@@ -1,4 +1,5 @@
-pub trait Actuator {
+/// Actuators are shared with the encephalon, which may be running on another thread
+pub trait Actuator: Send + Sync {
     fn set_control_value(&self, value: f32);
     fn get_name(&self) -> String;
     fn get_control_value(&self) -> f32;
@@ -6,26 +7,26 @@ pub trait Actuator {
 
 pub mod custom_actuator {
     use crate::actuator::Actuator;
-    use std::cell::RefCell;
+    use std::sync::Mutex;
 
     /// Literally just takes and stores a value
     pub struct BasicActuator {
         name: String,
-        measure: RefCell<f32>,
+        measure: Mutex<f32>,
     }
 
     impl BasicActuator {
         pub fn new(name: String) -> BasicActuator {
             BasicActuator {
                 name,
-                measure: RefCell::new(0.),
+                measure: Mutex::new(0.),
             }
         }
     }
 
     impl Actuator for BasicActuator {
         fn set_control_value(&self, value: f32) {
-            *self.measure.borrow_mut() = value;
+            *self.measure.lock().unwrap() = value;
         }
 
         fn get_name(&self) -> String {
@@ -33,7 +34,7 @@ pub mod custom_actuator {
         }
 
         fn get_control_value(&self) -> f32 {
-            *self.measure.borrow()
+            *self.measure.lock().unwrap()
         }
     }
 }
@@ -1,30 +1,130 @@
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use aesir::actuator::custom_actuator::BasicActuator;
 use aesir::actuator::Actuator;
 use aesir::ecp_geometry::{EcpBox, EcpGeometry};
-use aesir::encephalon::Encephalon;
-use aesir::neuron::{basic_weight_modifier, SynapseType};
+use aesir::encephalon::{Encephalon, EncephalonBuilder};
+use aesir::neuron::{basic_weight_modifier, Impulse, InternalCharge, SynapseType};
 use aesir::reflex::Reflex;
 use aesir::sensor::custom_sensors::ConstantSensor;
 use aesir::sensor::Sensor;
 
-fn main() {
+const CYCLES: u32 = 3000;
+const SEED: u64 = 0;
+
+// Charge micro-benchmark parameters. Each iteration is what a single neuron does to
+// its charge in one cycle: take in impulses, evaluate, then reset
+const CHARGE_ITERATIONS: u32 = 200_000;
+const CHARGE_BINS: u8 = 10;
+const IMPULSES_PER_ITERATION: u32 = 20;
+
+/// The former `InternalCharge` layout, with bins in `HashMap`s that are rebuilt on
+/// every reset. Only kept as a baseline for the charge micro-benchmark
+struct HashMapCharge {
+    current: HashMap<u8, f32>,
+    current_weights: HashMap<u8, f32>,
+    next: HashMap<u8, f32>,
+    next_weights: HashMap<u8, f32>,
+    bins: u8,
+}
+
+impl HashMapCharge {
+    fn new(bins: u8) -> HashMapCharge {
+        HashMapCharge {
+            current: (0..bins).map(|i| (i, 0.0)).collect(),
+            current_weights: (0..bins).map(|i| (i, 0.0)).collect(),
+            next: (0..bins).map(|i| (i, 0.0)).collect(),
+            next_weights: (0..bins).map(|i| (i, 0.0)).collect(),
+            bins,
+        }
+    }
+
+    fn incr_next_charge(&mut self, weight: f32, measure: f32) {
+        let bin = ((measure * self.bins as f32).floor() as u8).min(self.bins - 1);
+
+        *self.next.get_mut(&bin).unwrap() += weight * measure;
+        *self.next_weights.get_mut(&bin).unwrap() += weight;
+    }
+
+    fn evaluate_and_reset(&mut self) -> f32 {
+        let mut total_weighted_charge = 0.0;
+        let mut weights = 0.0;
+
+        for i in 0..self.bins {
+            let weight = *self.current_weights.get(&i).unwrap();
+            if weight >= 0.0 {
+                weights += weight;
+                total_weighted_charge += self.current.get(&i).unwrap().max(0.0);
+            }
+        }
+
+        self.current = (0..self.bins).map(|i| (i, 0.0)).collect();
+        self.current_weights = (0..self.bins).map(|i| (i, 0.0)).collect();
+        std::mem::swap(&mut self.current, &mut self.next);
+        std::mem::swap(&mut self.current_weights, &mut self.next_weights);
+
+        if weights == 0.0 {
+            0.0
+        } else {
+            total_weighted_charge / weights
+        }
+    }
+}
+
+/// Measure of the `i`th impulse of an iteration, spread over every bin
+fn impulse_measure(i: u32) -> f32 {
+    (i as f32 + 0.5) / IMPULSES_PER_ITERATION as f32
+}
+
+/// Times `CHARGE_ITERATIONS` iterations of `InternalCharge` against the `HashMap`
+/// baseline and returns the elapsed times in seconds
+fn charge_micro_benchmark() -> (f32, f32) {
+    let start = SystemTime::now();
+
+    let mut internal_charge = InternalCharge::new(CHARGE_BINS);
+    for cycle_number in 0..CHARGE_ITERATIONS as u64 {
+        for i in 0..IMPULSES_PER_ITERATION {
+            internal_charge.incr_next_charge(cycle_number, Impulse::new(3., impulse_measure(i)));
+        }
+
+        black_box(internal_charge.get_weights(cycle_number));
+        black_box(internal_charge.get_charge_weighted_average(cycle_number));
+        internal_charge.reset_charge(cycle_number);
+    }
+
+    let dense_time = start.elapsed().unwrap().as_secs_f32();
+    let start = SystemTime::now();
+
+    let mut hash_map_charge = HashMapCharge::new(CHARGE_BINS);
+    for _ in 0..CHARGE_ITERATIONS {
+        for i in 0..IMPULSES_PER_ITERATION {
+            hash_map_charge.incr_next_charge(3., impulse_measure(i));
+        }
+
+        black_box(hash_map_charge.evaluate_and_reset());
+    }
+
+    (dense_time, start.elapsed().unwrap().as_secs_f32())
+}
+
+fn build_encephalon() -> Encephalon {
     let sensor_names = ["1", "2", "3", "4"];
 
-    let mut sensors: Vec<Rc<dyn Sensor>> = Vec::new();
+    let mut sensors: Vec<Arc<dyn Sensor>> = Vec::new();
 
     for name in &sensor_names {
-        sensors.push(Rc::new(ConstantSensor::new((*name).into(), 0.5)));
+        sensors.push(Arc::new(ConstantSensor::new((*name).into(), 0.5)));
     }
 
     let actuator_names = ["act1", "act2", "act3"];
 
-    let mut actuators: Vec<Rc<dyn Actuator>> = Vec::new();
+    let mut actuators: Vec<Arc<dyn Actuator>> = Vec::new();
 
     for name in &actuator_names {
-        actuators.push(Rc::new(BasicActuator::new((*name).into())));
+        actuators.push(Arc::new(BasicActuator::new((*name).into())));
     }
 
     let reflexes = vec![
@@ -34,22 +134,28 @@ fn main() {
         Reflex::new("3".into(), "act3".into(), SynapseType::Inhibitory, 20.),
     ];
 
-    let ecp_g = Box::new(EcpBox::new(10_u32.pow(3) as u32, 3, 4, 215));
-
-    let mut ecp = Encephalon::new(
-        ecp_g,
-        sensors,
-        actuators,
-        reflexes,
-        10,
-        basic_weight_modifier,
-        2.,
-        10.,
-        (2., 5.),
-    );
+    let ecp_g = Box::new(EcpBox::new(10_u32.pow(3), 3, 4, 215));
+
+    EncephalonBuilder::new(ecp_g)
+        .sensors(sensors)
+        .actuators(actuators)
+        .reflexes(reflexes)
+        .charge_bins(10)
+        .weight_modifier(basic_weight_modifier)
+        .synaptic_type_ratio(2.)
+        .fire_threshold(10.)
+        .synapse_weight_ranges((2., 5.))
+        .seed(SEED)
+        .build()
+        .unwrap()
+}
+
+/// Runs `CYCLES` cycles and returns the total elapsed time in seconds
+fn time_cycles(ecp: &mut Encephalon) -> f32 {
+    let start = SystemTime::now();
 
     let mut time_now = SystemTime::now();
-    for i in 0..3000 {
+    for i in 0..CYCLES {
         ecp.run_cycle();
 
         if i % 100 == 0 {
@@ -61,4 +167,55 @@ fn main() {
             time_now = SystemTime::now();
         }
     }
+
+    start.elapsed().unwrap().as_secs_f32()
+}
+
+fn main() {
+    let (dense_time, hash_map_time) = charge_micro_benchmark();
+    let per_iteration = |time: f32| time * 1e9 / CHARGE_ITERATIONS as f32;
+
+    println!(
+        "InternalCharge: {:.1}ns per neuron cycle (HashMap bins: {:.1}ns, {:.2}x faster)",
+        per_iteration(dense_time),
+        per_iteration(hash_map_time),
+        hash_map_time / dense_time
+    );
+
+    let mut ecp = build_encephalon();
+    let sequential_time = time_cycles(&mut ecp);
+
+    println!(
+        "Sequential: {} cycles in {}s ({} cycles/s)",
+        CYCLES,
+        sequential_time,
+        CYCLES as f32 / sequential_time
+    );
+
+    #[cfg(feature = "parallel")]
+    {
+        // Every available core unless the number of threads is passed as an argument
+        let threads = std::env::args()
+            .nth(1)
+            .and_then(|threads| threads.parse().ok())
+            .or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|threads| threads.get())
+                    .ok()
+            })
+            .unwrap_or(1);
+
+        let mut ecp = build_encephalon();
+        ecp.set_threads(threads);
+        let parallel_time = time_cycles(&mut ecp);
+
+        println!(
+            "Parallel ({} threads): {} cycles in {}s ({} cycles/s)",
+            threads,
+            CYCLES,
+            parallel_time,
+            CYCLES as f32 / parallel_time
+        );
+        println!("Speedup: {:.2}x", sequential_time / parallel_time);
+    }
 }
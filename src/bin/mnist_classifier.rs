@@ -9,7 +9,7 @@ use aesir::reflex::Reflex;
 use aesir::sensor::custom_sensors::BasicSensor;
 use aesir::sensor::Sensor;
 use mnist::{Mnist, MnistBuilder};
-use std::rc::Rc;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 const MNIST_SIDE_LENGTH: usize = 28;
@@ -46,19 +46,19 @@ fn main() {
         .map(|num| format!("{}", num))
         .collect::<Vec<String>>();
 
-    let mut actuators: Vec<Rc<dyn Actuator>> = Vec::new();
-    let mut actuator_copy: Vec<Rc<dyn Actuator>> = Vec::new();
+    let mut actuators: Vec<Arc<dyn Actuator>> = Vec::new();
+    let mut actuator_copy: Vec<Arc<dyn Actuator>> = Vec::new();
 
     for name in &actuator_names {
-        let actuator = Rc::new(BasicActuator::new(name.clone()));
+        let actuator = Arc::new(BasicActuator::new(name.clone()));
 
-        actuators.push(Rc::clone(&actuator) as Rc<dyn Actuator>);
-        actuator_copy.push(Rc::clone(&actuator) as Rc<dyn Actuator>);
+        actuators.push(Arc::clone(&actuator) as Arc<dyn Actuator>);
+        actuator_copy.push(Arc::clone(&actuator) as Arc<dyn Actuator>);
     }
 
     // Create img sensors
-    let mut mnist_sensors: Vec<Rc<BasicSensor>> = Vec::new();
-    let mut mnist_sensors_copy: Vec<Rc<dyn Sensor>> = Vec::new();
+    let mut mnist_sensors: Vec<Arc<BasicSensor>> = Vec::new();
+    let mut mnist_sensors_copy: Vec<Arc<dyn Sensor>> = Vec::new();
 
     let mut sens_x = 0;
     let mut sens_y = 0;
@@ -69,10 +69,10 @@ fn main() {
         }
 
         let name = format!("{}:{}", sens_x, sens_y);
-        let sensor = Rc::new(BasicSensor::new(name));
+        let sensor = Arc::new(BasicSensor::new(name));
 
-        mnist_sensors.push(Rc::clone(&sensor));
-        mnist_sensors_copy.push(Rc::clone(&sensor) as Rc<dyn Sensor>);
+        mnist_sensors.push(Arc::clone(&sensor));
+        mnist_sensors_copy.push(Arc::clone(&sensor) as Arc<dyn Sensor>);
 
         if sens_x >= MNIST_SIDE_LENGTH - 1 {
             sens_y += 1;
@@ -83,10 +83,10 @@ fn main() {
     }
 
     // Create reflex sensors
-    let mut reflex_sensors: Vec<Rc<BasicSensor>> = Vec::new();
+    let mut reflex_sensors: Vec<Arc<BasicSensor>> = Vec::new();
 
     for sensor_index in 0..10 {
-        reflex_sensors.push(Rc::new(BasicSensor::new(format!("{}", sensor_index))));
+        reflex_sensors.push(Arc::new(BasicSensor::new(format!("{}", sensor_index))));
     }
 
     // Create ecp geometry
@@ -125,7 +125,7 @@ fn main() {
         }
 
         ecp.add_reflex_sensor(
-            Rc::clone(reflex_sensor) as Rc<dyn Sensor>,
+            Arc::clone(reflex_sensor) as Arc<dyn Sensor>,
             sensor_index as i32,
             reflexes,
             basic_weight_modifier,
@@ -137,10 +137,10 @@ fn main() {
 
         for sensor_index in 0..MNIST_AREA {
             mnist_sensors
-                .get(sensor_index as usize)
+                .get(sensor_index)
                 .unwrap()
                 .set_measure(
-                    *trn_img.get((img_start_index + sensor_index) as usize).unwrap() as f32 / 255.,
+                    *trn_img.get(img_start_index + sensor_index).unwrap() as f32 / 255.,
                 );
         }
     };
@@ -167,8 +167,8 @@ fn main() {
         for _ in 0..50 {
             ecp.run_cycle();
 
-            for i in 0..10 {
-                label_totals[i] += actuators.get(i).unwrap().get_control_value();
+            for (total, actuator) in label_totals.iter_mut().zip(actuators.iter()) {
+                *total += actuator.get_control_value();
             }
         }
 
@@ -176,11 +176,11 @@ fn main() {
         let mut max_value = label_totals[0];
 
         // println!("{}", trn_lbl.get(i).unwrap());
-        for j in 1..10 {
-            // println!("label {}", label_totals[j]);
-            if label_totals[j] > max_value {
+        for (j, &label_total) in label_totals.iter().enumerate().skip(1) {
+            // println!("label {}", label_total);
+            if label_total > max_value {
                 max_index = j;
-                max_value = label_totals[j];
+                max_value = label_total;
             }
         }
 
@@ -209,8 +209,8 @@ fn main() {
         for _ in 0..50 {
             ecp.run_static_cycle();
 
-            for i in 0..10 {
-                label_totals[i] += actuators.get(i).unwrap().get_control_value();
+            for (total, actuator) in label_totals.iter_mut().zip(actuators.iter()) {
+                *total += actuator.get_control_value();
             }
         }
 
@@ -218,11 +218,11 @@ fn main() {
         let mut max_value = label_totals[0];
 
         // println!("{}", trn_lbl.get(i).unwrap());
-        for j in 1..10 {
-            // println!("label {}", label_totals[j]);
-            if label_totals[j] > max_value {
+        for (j, &label_total) in label_totals.iter().enumerate().skip(1) {
+            // println!("label {}", label_total);
+            if label_total > max_value {
                 max_index = j;
-                max_value = label_totals[j];
+                max_value = label_total;
             }
         }
 
@@ -0,0 +1,30 @@
+/// Aggregate activity of an encephalon over one cycle, returned by
+/// `Encephalon::run_cycle` and `Encephalon::run_static_cycle` once enabled with
+/// `Encephalon::set_cycle_stats`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CycleStats {
+    /// Number of the cycle these are the stats of
+    pub cycle: u64,
+    pub sensory_fired: usize,
+    pub plastic_fired: usize,
+    pub actuator_fired: usize,
+    /// Mean and max measure every neuron that fired fired with, 0 if none fired
+    pub mean_measure: f32,
+    pub max_measure: f32,
+    /// Sum of the weights of every synapse at the end of the cycle
+    pub total_weight: f32,
+    /// Number of synapses whose weight was changed by learning or weight regulation,
+    /// and the sum of how much each changed by
+    pub changed_synapses: usize,
+    pub weight_change: f32,
+    /// Total weight of the excitatory and inhibitory impulses sent
+    pub excitatory_impulse: f32,
+    pub inhibitory_impulse: f32,
+}
+
+impl CycleStats {
+    /// Number of neurons that fired, of any kind
+    pub fn fired(&self) -> usize {
+        self.sensory_fired + self.plastic_fired + self.actuator_fired
+    }
+}
@@ -1,8 +1,31 @@
-pub trait EcpGeometry {
+use crate::error::EncephalonError;
+
+#[allow(clippy::ptr_arg)]
+pub trait EcpGeometry: Send + Sync {
+    /// Panics if the parameters can't be laid out, see `try_new`
     fn new(num_plastic: u32, num_actuator: u32, num_sensory: u32, nearby_count: u32) -> Self
     where
         Self: Sized;
 
+    /// Fallible version of `new`. Geometries that can't be misconfigured don't need to
+    /// override this
+    fn try_new(
+        num_plastic: u32,
+        num_actuator: u32,
+        num_sensory: u32,
+        nearby_count: u32,
+    ) -> Result<Self, EncephalonError>
+    where
+        Self: Sized,
+    {
+        Ok(Self::new(
+            num_plastic,
+            num_actuator,
+            num_sensory,
+            nearby_count,
+        ))
+    }
+
     fn get_num_plastic(&self) -> u32;
     fn get_num_sensory(&self) -> u32;
     fn get_num_actuator(&self) -> u32;
@@ -19,6 +42,101 @@ pub trait EcpGeometry {
     /// First index is plastic neurons, second index is
     /// actuator neurons,
     fn get_nearby_rx_neurons(&self, loc: &Vec<i32>) -> (Vec<Vec<i32>>, Vec<Vec<i32>>);
+
+    /// Distance between two neuron locations, which `SynapticDelay::Distance` turns
+    /// into the delay of the synapse between them. Euclidean unless overridden
+    fn distance(&self, source: &[i32], target: &[i32]) -> f32 {
+        source
+            .iter()
+            .zip(target)
+            .map(|(&a, &b)| ((a - b) as f32).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    /// Location for a sensory neuron added after the encephalon is built, the first
+    /// one `taken` returns false for. `None` if there's no room left
+    fn vacant_sensory_loc(&self, taken: &dyn Fn(&[i32]) -> bool) -> Option<Vec<i32>> {
+        let mut loc_option = Some(self.first_sensory_loc());
+        while let Some(loc) = loc_option {
+            if !taken(&loc) {
+                return Some(loc);
+            }
+            loc_option = self.next_sensory_loc(&loc);
+        }
+
+        None
+    }
+
+    /// Location for an actuator neuron added after the encephalon is built, the first
+    /// one `taken` returns false for. `None` if there's no room left
+    fn vacant_actuator_loc(&self, taken: &dyn Fn(&[i32]) -> bool) -> Option<Vec<i32>> {
+        let mut loc_option = Some(self.first_actuator_loc());
+        while let Some(loc) = loc_option {
+            if !taken(&loc) {
+                return Some(loc);
+            }
+            loc_option = self.next_actuator_loc(&loc);
+        }
+
+        None
+    }
+
+    /// Plastic neurons that have the actuator neuron at `actuator_loc` nearby
+    fn get_nearby_plastic_neurons(&self, actuator_loc: &[i32]) -> Vec<Vec<i32>> {
+        let mut plastic_locs = Vec::new();
+
+        let mut loc_option = Some(self.first_plastic_loc());
+        while let Some(loc) = loc_option {
+            let (_, actuator_locs) = self.get_nearby_rx_neurons(&loc);
+            if actuator_locs
+                .iter()
+                .any(|nearby| nearby[..] == *actuator_loc)
+            {
+                plastic_locs.push(loc.clone());
+            }
+            loc_option = self.next_plastic_loc(&loc);
+        }
+
+        plastic_locs
+    }
+}
+
+/// Number of cycles the impulses of the synapses an encephalon grows take to reach
+/// their target
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SynapticDelay {
+    /// Every synapse has the same delay
+    Fixed(u8),
+    /// Synapses take an extra cycle for every `distance_per_cycle` between the neurons
+    /// they connect, up to a delay of `max_delay`
+    Distance {
+        distance_per_cycle: f32,
+        max_delay: u8,
+    },
+}
+
+impl SynapticDelay {
+    /// Delay of a synapse between neurons `distance` apart
+    pub fn delay(&self, distance: f32) -> u8 {
+        match *self {
+            SynapticDelay::Fixed(delay) => delay,
+            SynapticDelay::Distance {
+                distance_per_cycle,
+                max_delay,
+            } => {
+                let delay = 1. + (distance / distance_per_cycle).floor();
+
+                delay.min(max_delay as f32) as u8
+            }
+        }
+    }
+}
+
+impl Default for SynapticDelay {
+    fn default() -> SynapticDelay {
+        SynapticDelay::Fixed(1)
+    }
 }
 
 pub struct EcpBox {
@@ -32,6 +150,17 @@ pub struct EcpBox {
     // plastic_to_actuator_connections: HashMap<Vec<i32>, Vec<Vec<i32>>>
 }
 
+impl EcpBox {
+    /// Lowest coordinate of the cube of nearby neurons around `coord`, which is kept
+    /// inside the cube of plastic neurons
+    fn nearby_origin(&self, coord: i32) -> i32 {
+        let half_nearby = (self.nearby_side_length as f32 / 2.0).floor() as i32;
+        let max_origin = (self.plastic_side_length - self.nearby_side_length) as i32;
+
+        (coord - half_nearby).clamp(0, max_origin)
+    }
+}
+
 impl EcpGeometry for EcpBox {
     fn new(num_plastic: u32, num_actuator: u32, num_sensory: u32, nearby_count: u32) -> Self
     where
@@ -140,6 +269,59 @@ impl EcpGeometry for EcpBox {
         }
     }
 
+    fn try_new(
+        num_plastic: u32,
+        num_actuator: u32,
+        num_sensory: u32,
+        nearby_count: u32,
+    ) -> Result<Self, EncephalonError>
+    where
+        Self: Sized,
+    {
+        let invalid = |parameter: &'static str, reason: &str| {
+            Err(EncephalonError::InvalidGeometry {
+                parameter,
+                reason: reason.into(),
+            })
+        };
+
+        let plastic_side_length = (num_plastic as f32).powf(1. / 3.).floor() as u32;
+
+        if num_plastic == 0 || plastic_side_length.pow(3) != num_plastic {
+            return invalid("num_plastic", "must be a non-zero perfect cube");
+        }
+
+        let nearby_side_length = ((nearby_count + 1) as f32).powf(1. / 3.).floor() as u32;
+
+        if nearby_side_length.pow(3) != nearby_count + 1 {
+            return invalid("nearby_count", "must be one less than a perfect cube");
+        }
+
+        if nearby_side_length > plastic_side_length {
+            return invalid(
+                "nearby_count",
+                "the nearby cube can't be larger than the cube of plastic neurons",
+            );
+        }
+
+        if num_actuator == 0 {
+            return invalid("num_actuator", "must be at least 1");
+        }
+
+        let sensory_side_length = (num_sensory as f32).powf(0.5).floor() as u32;
+
+        if num_sensory == 0 || sensory_side_length.pow(2) != num_sensory {
+            return invalid("num_sensory", "must be a non-zero perfect square");
+        }
+
+        Ok(EcpBox::new(
+            num_plastic,
+            num_actuator,
+            num_sensory,
+            nearby_count,
+        ))
+    }
+
     fn get_num_plastic(&self) -> u32 {
         self.num_plastic
     }
@@ -161,7 +343,7 @@ impl EcpGeometry for EcpBox {
     }
 
     fn next_plastic_loc(&self, loc: &Vec<i32>) -> Option<Vec<i32>> {
-        let x = *loc.get(0).unwrap();
+        let x = *loc.first().unwrap();
         let y = *loc.get(1).unwrap();
         let z = *loc.get(2).unwrap();
 
@@ -171,7 +353,7 @@ impl EcpGeometry for EcpBox {
             return None;
         }
 
-        return if x == plastic_index {
+        if x == plastic_index {
             if y == plastic_index {
                 Some(vec![0, 0, z + 1])
             } else {
@@ -179,7 +361,7 @@ impl EcpGeometry for EcpBox {
             }
         } else {
             Some(vec![x + 1, y, z])
-        };
+        }
     }
 
     fn first_actuator_loc(&self) -> Vec<i32> {
@@ -187,7 +369,7 @@ impl EcpGeometry for EcpBox {
     }
 
     fn next_actuator_loc(&self, loc: &Vec<i32>) -> Option<Vec<i32>> {
-        let x = *loc.get(0).unwrap();
+        let x = *loc.first().unwrap();
         let y = *loc.get(1).unwrap();
         let z = *loc.get(2).unwrap();
 
@@ -197,11 +379,11 @@ impl EcpGeometry for EcpBox {
             return None;
         }
 
-        return if x == plastic_index {
+        if x == plastic_index {
             Some(vec![0, y, z + 1])
         } else {
             Some(vec![x + 1, y, z])
-        };
+        }
     }
 
     fn first_sensory_loc(&self) -> Vec<i32> {
@@ -209,7 +391,7 @@ impl EcpGeometry for EcpBox {
     }
 
     fn next_sensory_loc(&self, loc: &Vec<i32>) -> Option<Vec<i32>> {
-        let x = *loc.get(0).unwrap();
+        let x = *loc.first().unwrap();
         let y = *loc.get(1).unwrap();
         let z = *loc.get(2).unwrap();
 
@@ -219,15 +401,15 @@ impl EcpGeometry for EcpBox {
             return None;
         }
 
-        return if x == sensor_index {
+        if x == sensor_index {
             Some(vec![0, y, z + 1])
         } else {
             Some(vec![x + 1, y, z])
-        };
+        }
     }
 
     fn get_nearby_rx_neurons(&self, loc: &Vec<i32>) -> (Vec<Vec<i32>>, Vec<Vec<i32>>) {
-        let loc_x = *loc.get(0).unwrap();
+        let loc_x = *loc.first().unwrap();
         let loc_y = *loc.get(1).unwrap();
         let loc_z = *loc.get(2).unwrap();
 
@@ -237,11 +419,14 @@ impl EcpGeometry for EcpBox {
             //This is a sensor
             let scaled_plastic = self.plastic_side_length - self.nearby_side_length;
 
+            // Sensors added past the laid out grid are near the edge of the cube
             let x_0 = ((scaled_plastic as f32 / self.sensory_side_length as f32) * loc_x as f32)
-                .floor() as i32;
+                .floor()
+                .min(scaled_plastic as f32) as i32;
             let y_0 = 0;
             let z_0 = ((scaled_plastic as f32 / self.sensory_side_length as f32) * loc_z as f32)
-                .floor() as i32;
+                .floor()
+                .min(scaled_plastic as f32) as i32;
 
             let mut x = 0;
             let mut y = 0;
@@ -296,6 +481,9 @@ impl EcpGeometry for EcpBox {
 
             let mut actuators = Vec::new();
 
+            // Only the `num_actuator` actuators laid out when the encephalon is built are
+            // counted, since the first plastic neurons nearby make way for them. Actuators
+            // added later are connected to through `get_nearby_plastic_neurons` instead
             if loc_y + half_nearby > nearby_index {
                 let mut x = 0;
                 let mut z = 0;
@@ -305,8 +493,8 @@ impl EcpGeometry for EcpBox {
                         break;
                     }
 
-                    if (self.plastic_side_length as i32 * (z_0 + z)) + (x_0 + x) + 1
-                        <= self.num_actuator as i32
+                    if (self.plastic_side_length as i32 * (z_0 + z)) + (x_0 + x)
+                        < self.num_actuator as i32
                     {
                         actuators.push(vec![x_0 + x, self.plastic_side_length as i32, z + z_0]);
 
@@ -332,10 +520,10 @@ impl EcpGeometry for EcpBox {
             let mut plastic_connections = Vec::new();
 
             loop {
-                if count > actuator_count {
-                    if !(x + x_0 == loc_x && y + y_0 == loc_y && z + z_0 == loc_z) {
-                        plastic_connections.push(vec![x + x_0, y + y_0, z + z_0]);
-                    }
+                if count > actuator_count
+                    && !(x + x_0 == loc_x && y + y_0 == loc_y && z + z_0 == loc_z)
+                {
+                    plastic_connections.push(vec![x + x_0, y + y_0, z + z_0]);
                 }
 
                 if x == nearby_index {
@@ -359,6 +547,58 @@ impl EcpGeometry for EcpBox {
             }
         }
     }
+
+    /// Sensors can take any spot on the plane below the plastic neurons once the ones
+    /// laid out for `num_sensory` sensors are taken
+    fn vacant_sensory_loc(&self, taken: &dyn Fn(&[i32]) -> bool) -> Option<Vec<i32>> {
+        let sensory_side_length = self.sensory_side_length as i32;
+        let side_length = sensory_side_length.max(self.plastic_side_length as i32);
+        let laid_out = |x: i32, z: i32| x < sensory_side_length && z < sensory_side_length;
+
+        let plane = (0..side_length).flat_map(|z| (0..side_length).map(move |x| (x, z)));
+        plane
+            .clone()
+            .filter(|&(x, z)| laid_out(x, z))
+            .chain(plane.filter(|&(x, z)| !laid_out(x, z)))
+            .map(|(x, z)| vec![x, -1, z])
+            .find(|loc| !taken(loc))
+    }
+
+    /// Actuators can take any spot on the plane above the plastic neurons, not just the
+    /// ones laid out for `num_actuator` actuators
+    fn vacant_actuator_loc(&self, taken: &dyn Fn(&[i32]) -> bool) -> Option<Vec<i32>> {
+        let side_length = self.plastic_side_length as i32;
+
+        (0..side_length)
+            .flat_map(|z| (0..side_length).map(move |x| vec![x, side_length, z]))
+            .find(|loc| !taken(loc))
+    }
+
+    /// Plastic neurons near the top of the cube have the actuators above their nearby
+    /// cube nearby
+    fn get_nearby_plastic_neurons(&self, actuator_loc: &[i32]) -> Vec<Vec<i32>> {
+        let act_x = *actuator_loc.first().unwrap();
+        let act_z = *actuator_loc.get(2).unwrap();
+
+        let half_nearby = (self.nearby_side_length as f32 / 2.0).floor() as i32;
+        let nearby_index = (self.nearby_side_length - 1) as i32;
+        let in_window = |origin: i32, coord: i32| (origin..=origin + nearby_index).contains(&coord);
+
+        let mut plastic_locs = Vec::new();
+
+        let mut loc_option = Some(self.first_plastic_loc());
+        while let Some(loc) = loc_option {
+            if loc[1] + half_nearby > nearby_index
+                && in_window(self.nearby_origin(loc[0]), act_x)
+                && in_window(self.nearby_origin(loc[2]), act_z)
+            {
+                plastic_locs.push(loc.clone());
+            }
+            loc_option = self.next_plastic_loc(&loc);
+        }
+
+        plastic_locs
+    }
 }
 
 #[cfg(test)]
@@ -1,21 +1,22 @@
 use crate::ecp_geometry::{EcpBox, EcpGeometry};
+use crate::error::EncephalonError;
 
 #[test]
 fn test_get_next_plastic() {
     let ecp_box = EcpBox::new(27, 10, 10, 7);
 
     let nex_loc = ecp_box.next_plastic_loc(&vec![0, 0, 0]).unwrap();
-    assert_eq!(1, *nex_loc.get(0).unwrap());
+    assert_eq!(1, *nex_loc.first().unwrap());
     assert_eq!(0, *nex_loc.get(1).unwrap());
     assert_eq!(0, *nex_loc.get(2).unwrap());
 
     let nex_loc = ecp_box.next_plastic_loc(&vec![2, 0, 0]).unwrap();
-    assert_eq!(0, *nex_loc.get(0).unwrap());
+    assert_eq!(0, *nex_loc.first().unwrap());
     assert_eq!(1, *nex_loc.get(1).unwrap());
     assert_eq!(0, *nex_loc.get(2).unwrap());
 
     let nex_loc = ecp_box.next_plastic_loc(&vec![2, 2, 0]).unwrap();
-    assert_eq!(0, *nex_loc.get(0).unwrap());
+    assert_eq!(0, *nex_loc.first().unwrap());
     assert_eq!(0, *nex_loc.get(1).unwrap());
     assert_eq!(1, *nex_loc.get(2).unwrap());
 
@@ -28,12 +29,12 @@ fn test_get_next_actuator() {
     let ecp_box = EcpBox::new(125, 10, 123, 7);
 
     let nex_loc = ecp_box.next_actuator_loc(&vec![0, 5, 0]).unwrap();
-    assert_eq!(1, *nex_loc.get(0).unwrap());
+    assert_eq!(1, *nex_loc.first().unwrap());
     assert_eq!(5, *nex_loc.get(1).unwrap());
     assert_eq!(0, *nex_loc.get(2).unwrap());
 
     let nex_loc = ecp_box.next_actuator_loc(&vec![4, 5, 0]).unwrap();
-    assert_eq!(0, *nex_loc.get(0).unwrap());
+    assert_eq!(0, *nex_loc.first().unwrap());
     assert_eq!(5, *nex_loc.get(1).unwrap());
     assert_eq!(1, *nex_loc.get(2).unwrap());
 
@@ -58,3 +59,92 @@ fn test_if_plastic_synapse_with_actuator() {
     assert_eq!(plastic.len(), 26);
     assert_eq!(actuators.len(), 0);
 }
+
+#[test]
+fn test_try_new_errors() {
+    assert!(EcpBox::try_new(27, 10, 9, 7).is_ok());
+    assert!(EcpBox::try_new(64, 10, 9, 26).is_ok());
+
+    match EcpBox::try_new(26, 10, 10, 7) {
+        Err(EncephalonError::InvalidGeometry { parameter, .. }) => {
+            assert_eq!(parameter, "num_plastic")
+        }
+        _ => panic!("Expected an invalid num_plastic"),
+    }
+
+    match EcpBox::try_new(27, 10, 10, 8) {
+        Err(EncephalonError::InvalidGeometry { parameter, .. }) => {
+            assert_eq!(parameter, "nearby_count")
+        }
+        _ => panic!("Expected an invalid nearby_count"),
+    }
+
+    match EcpBox::try_new(8, 10, 10, 26) {
+        Err(EncephalonError::InvalidGeometry { parameter, .. }) => {
+            assert_eq!(parameter, "nearby_count")
+        }
+        _ => panic!("Expected a nearby cube that doesn't fit"),
+    }
+
+    match EcpBox::try_new(27, 2, 5, 7) {
+        Err(EncephalonError::InvalidGeometry { parameter, .. }) => {
+            assert_eq!(parameter, "num_sensory")
+        }
+        _ => panic!("Expected an invalid num_sensory"),
+    }
+}
+
+#[test]
+fn test_nearby_plastic_neurons() {
+    let ecp_box = EcpBox::new(125, 10, 123, 26);
+
+    // Matches the actuators each plastic neuron has nearby
+    let mut actuator_loc = Some(ecp_box.first_actuator_loc());
+    while let Some(loc) = actuator_loc {
+        let mut expected = Vec::new();
+        let mut plastic_loc = Some(ecp_box.first_plastic_loc());
+        while let Some(plastic) = plastic_loc {
+            if ecp_box.get_nearby_rx_neurons(&plastic).1.contains(&loc) {
+                expected.push(plastic.clone());
+            }
+            plastic_loc = ecp_box.next_plastic_loc(&plastic);
+        }
+
+        assert_eq!(ecp_box.get_nearby_plastic_neurons(&loc), expected);
+        actuator_loc = ecp_box.next_actuator_loc(&loc);
+    }
+
+    // Actuators past the ones laid out are still near the top of the cube
+    let plastic = ecp_box.get_nearby_plastic_neurons(&[4, 5, 4]);
+    assert!(!plastic.is_empty());
+    assert!(plastic
+        .iter()
+        .all(|loc| loc[1] >= 2 && loc[0] >= 3 && loc[2] >= 3));
+}
+
+#[test]
+fn test_vacant_locs() {
+    let ecp_box = EcpBox::new(27, 2, 4, 7);
+
+    let taken = |loc: &[i32]| loc == [0, -1, 0] || loc == [1, -1, 0];
+    assert_eq!(ecp_box.vacant_sensory_loc(&taken), Some(vec![0, -1, 1]));
+    assert_eq!(ecp_box.vacant_sensory_loc(&|_| true), None);
+
+    // Sensors spill over onto the rest of the plane below the plastic neurons
+    let taken = |loc: &[i32]| loc[0] < 2 && loc[2] < 2;
+    assert_eq!(ecp_box.vacant_sensory_loc(&taken), Some(vec![2, -1, 0]));
+    let taken = |loc: &[i32]| loc != [2, -1, 2];
+    assert_eq!(ecp_box.vacant_sensory_loc(&taken), Some(vec![2, -1, 2]));
+
+    let (plastic, actuators) = ecp_box.get_nearby_rx_neurons(&vec![2, -1, 2]);
+    assert_eq!(plastic.len(), 8);
+    assert!(actuators.is_empty());
+    assert!(plastic
+        .iter()
+        .all(|loc| loc.iter().all(|&i| (0..3).contains(&i))));
+
+    // Actuators can go anywhere on the plane above the plastic neurons
+    let taken = |loc: &[i32]| loc[2] == 0;
+    assert_eq!(ecp_box.vacant_actuator_loc(&taken), Some(vec![0, 3, 1]));
+    assert_eq!(ecp_box.vacant_actuator_loc(&|_| true), None);
+}
@@ -1,32 +1,75 @@
 use indexmap::IndexMap;
-use rand::Rng;
+use rand::{Rng, RngCore};
 
 use crate::actuator::Actuator;
-use crate::ecp_geometry::EcpGeometry;
-use crate::neuron::{
-    ActuatorNeuron, ChargeCycle, Neuronic, PlasticNeuron, RxNeuronic, SensoryNeuron, SynapseType,
-    TxNeuronic,
-};
+use crate::cycle_stats::CycleStats;
+use crate::ecp_geometry::{EcpGeometry, SynapticDelay};
+use crate::fire_recorder::{FireEvent, FireRecorder, NeuronKind};
+use crate::learning_rule::{LearningRule, WeightModifier};
+use crate::neuron::{ChargeCycle, NeuronArena, NeuronId, SynapseType};
 use crate::neuron_interfaces::{ActuatorInterface, SensoryInterface};
-use crate::reflex::Reflex;
+use crate::observer::EncephalonObserver;
+use crate::reflex::{Reflex, ReflexId, ReflexSource, ReflexTarget};
 use crate::sensor::Sensor;
-use std::rc::Rc;
+use crate::structural_plasticity::{StructuralChanges, StructuralPlasticity};
+use crate::synapse_snapshot::{SynapseKind, SynapseSnapshot, SynapseState};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+pub use crate::error::EncephalonError;
+pub use builder::EncephalonBuilder;
+use builder::NeuronParameters;
+pub(crate) use builder::NeuronTemplate;
+pub use loader::EncephalonLoader;
+
+mod builder;
+mod graph;
+mod loader;
+mod observation;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod persistence;
+mod stats;
+mod structural;
 
 pub struct Encephalon {
     ecp_geometry: Box<dyn EcpGeometry>,
-    plastic_neurons: IndexMap<Vec<i32>, Rc<PlasticNeuron>>,
-    actuator_neurons: IndexMap<Vec<i32>, Rc<ActuatorNeuron>>,
-    sensory_neurons: IndexMap<Vec<i32>, Rc<SensoryNeuron>>,
+    neurons: NeuronArena,
+    plastic_neurons: IndexMap<Vec<i32>, NeuronId>,
+    actuator_neurons: IndexMap<Vec<i32>, NeuronId>,
+    sensory_neurons: IndexMap<Vec<i32>, NeuronId>,
     actuator_interfaces: IndexMap<String, ActuatorInterface>,
     sensory_interfaces: IndexMap<String, SensoryInterface>,
     cycle: ChargeCycle,
+    rng: Box<dyn RngCore + Send + Sync>,
+    template: NeuronTemplate,
+    // Every reflex in the order it was added, which is also the order of the static
+    // synapses of each sensory or plastic neuron
+    reflexes: IndexMap<ReflexId, Reflex>,
+    next_reflex_id: u64,
+    // Delay of the synapses between neurons, including those grown later
+    synaptic_delay: SynapticDelay,
+    structural_plasticity: Option<StructuralPlasticity>,
+    structural_changes: StructuralChanges,
+    fire_recorder: Option<FireRecorder>,
+    cycle_stats: bool,
+    observers: Vec<Box<dyn EncephalonObserver>>,
+    #[cfg(feature = "parallel")]
+    executor: parallel::ThreadedExecutor,
 }
 
 impl Encephalon {
+    /// Panics if the number of sensors or actuators doesn't match the geometry, or if a
+    /// reflex refers to an unknown sensor or actuator. The parameters are used as given,
+    /// see `EncephalonBuilder` for a validated, fallible way of constructing an encephalon
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ecp_geometry: Box<dyn EcpGeometry>,
-        mut sensors: Vec<Rc<dyn Sensor>>,
-        mut actuators: Vec<Rc<dyn Actuator>>,
+        sensors: Vec<Arc<dyn Sensor>>,
+        actuators: Vec<Arc<dyn Actuator>>,
         reflexes: Vec<Reflex>,
 
         //Neuron parameters
@@ -36,265 +79,876 @@ impl Encephalon {
         fire_threshold: f32,
         synapse_weight_ranges: (f32, f32),
     ) -> Encephalon {
-        if ecp_geometry.get_num_sensory() != sensors.len() as u32 {
-            panic!(
-                "The number of sensors passed to the encephalon doesn't \
-             match the number of sensor neuron positions within the specified ecp_geometry"
-            );
-        } else if ecp_geometry.get_num_actuator() != actuators.len() as u32 {
-            panic!(
-                "The number of actuators passed to the encephalon doesn't \
-             match the number of actuator neuron positions within the specified ecp_geometry"
-            );
-        }
+        EncephalonBuilder::new(ecp_geometry)
+            .sensors(sensors)
+            .actuators(actuators)
+            .reflexes(reflexes)
+            .charge_bins(charge_bins)
+            .weight_modifier(weight_modifier)
+            .synaptic_type_ratio(synaptic_type_ratio)
+            .fire_threshold(fire_threshold)
+            .synapse_weight_ranges(synapse_weight_ranges)
+            .build_unvalidated()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
+    /// Builds the encephalon from parameters that have already been validated
+    fn from_parameters(
+        ecp_geometry: Box<dyn EcpGeometry>,
+        mut sensors: Vec<Arc<dyn Sensor>>,
+        mut actuators: Vec<Arc<dyn Actuator>>,
+        reflexes: Vec<Reflex>,
+        params: &NeuronParameters,
+        rng: Box<dyn RngCore + Send + Sync>,
+    ) -> Result<Encephalon, EncephalonError> {
         let mut encephalon = Encephalon {
             ecp_geometry,
+            neurons: params
+                .stdp
+                .map_or_else(NeuronArena::new, NeuronArena::with_stdp),
             plastic_neurons: IndexMap::new(),
             actuator_neurons: IndexMap::new(),
             sensory_neurons: IndexMap::new(),
             actuator_interfaces: IndexMap::new(),
             sensory_interfaces: IndexMap::new(),
             cycle: ChargeCycle::Odd,
+            rng,
+            template: params.template(),
+            reflexes: IndexMap::new(),
+            next_reflex_id: 0,
+            synaptic_delay: params.synaptic_delay,
+            structural_plasticity: params.structural_plasticity,
+            structural_changes: StructuralChanges::default(),
+            fire_recorder: None,
+            cycle_stats: false,
+            observers: Vec::new(),
+            #[cfg(feature = "parallel")]
+            executor: parallel::ThreadedExecutor::new(params.threads),
         };
 
+        encephalon
+            .neurons
+            .set_weight_regulation(params.weight_regulation);
+        encephalon
+            .neurons
+            .set_adaptive_threshold(params.adaptive_threshold);
+        encephalon.neurons.set_refractory(params.refractory);
+        encephalon
+            .neurons
+            .set_leaky_integration(params.leaky_integration);
+
         //Populate plastic neurons
         let mut plastic_loc_option = Some(encephalon.ecp_geometry.first_plastic_loc());
-        loop {
-            if let Some(loc) = &plastic_loc_option {
-                encephalon.plastic_neurons.insert(
-                    loc.clone(),
-                    Rc::new(PlasticNeuron::new(
-                        charge_bins,
-                        weight_modifier,
-                        fire_threshold,
-                    )),
-                );
-                plastic_loc_option = encephalon.ecp_geometry.next_plastic_loc(loc);
-            } else {
-                break;
-            }
+        while let Some(loc) = &plastic_loc_option {
+            let neuron = encephalon.neurons.add_plastic_neuron(
+                params.plastic_charge_bins,
+                Arc::clone(&params.plastic_learning_rule),
+                params.plastic_fire_threshold,
+            );
+            encephalon
+                .neurons
+                .set_aggregation(neuron, params.plastic_aggregation);
+            encephalon.plastic_neurons.insert(loc.clone(), neuron);
+            plastic_loc_option = encephalon.ecp_geometry.next_plastic_loc(loc);
         }
 
         //Populate sensory neurons
         let mut sensory_loc_option = Some(encephalon.ecp_geometry.first_sensory_loc());
-        loop {
-            if let Some(loc) = &sensory_loc_option {
-                let neuron = Rc::new(SensoryNeuron::new(weight_modifier));
-
-                encephalon
-                    .sensory_neurons
-                    .insert(loc.clone(), Rc::clone(&neuron));
-
-                let sensor = sensors.pop().unwrap();
-                encephalon.sensory_interfaces.insert(
-                    sensor.get_name(),
-                    SensoryInterface::new(sensor, Rc::clone(&neuron)),
-                );
-
-                sensory_loc_option = encephalon.ecp_geometry.next_sensory_loc(loc);
-            } else {
-                break;
-            }
+        while let Some(loc) = &sensory_loc_option {
+            let neuron = encephalon
+                .neurons
+                .add_sensory_neuron(Arc::clone(&params.sensory_learning_rule));
+
+            encephalon.sensory_neurons.insert(loc.clone(), neuron);
+
+            let sensor = sensors.pop().ok_or(EncephalonError::InvalidGeometry {
+                parameter: "num_sensory",
+                reason: "the geometry has more sensory locations than sensors".into(),
+            })?;
+            encephalon
+                .sensory_interfaces
+                .insert(sensor.get_name(), SensoryInterface::new(sensor, neuron));
+
+            sensory_loc_option = encephalon.ecp_geometry.next_sensory_loc(loc);
         }
 
         //Populate actuator neurons
         let mut actuator_loc_option = Some(encephalon.ecp_geometry.first_actuator_loc());
-        loop {
-            if let Some(loc) = &actuator_loc_option {
-                let neuron = Rc::new(ActuatorNeuron::new(charge_bins, fire_threshold));
-
-                encephalon
-                    .actuator_neurons
-                    .insert(loc.clone(), Rc::clone(&neuron));
-
-                let actuator = actuators.pop().unwrap();
-                encephalon.actuator_interfaces.insert(
-                    actuator.get_name(),
-                    ActuatorInterface::new(actuator, Rc::clone(&neuron)),
-                );
-
-                actuator_loc_option = encephalon.ecp_geometry.next_actuator_loc(loc);
-            } else {
-                break;
-            }
+        while let Some(loc) = &actuator_loc_option {
+            let neuron = encephalon
+                .neurons
+                .add_actuator_neuron(params.actuator_charge_bins, params.actuator_fire_threshold);
+            encephalon
+                .neurons
+                .set_aggregation(neuron, params.actuator_aggregation);
+
+            encephalon.actuator_neurons.insert(loc.clone(), neuron);
+
+            let actuator = actuators.pop().ok_or(EncephalonError::InvalidGeometry {
+                parameter: "num_actuator",
+                reason: "the geometry has more actuator locations than actuators".into(),
+            })?;
+            encephalon.actuator_interfaces.insert(
+                actuator.get_name(),
+                ActuatorInterface::new(actuator, neuron),
+            );
+
+            actuator_loc_option = encephalon.ecp_geometry.next_actuator_loc(loc);
         }
 
-        //Closure to generate synaptic weight
-        let gen_weight =
-            || rand::thread_rng().gen_range(synapse_weight_ranges.0, synapse_weight_ranges.1);
+        //Make synapses for plastic and sensory neurons
+        let sources = encephalon
+            .plastic_neurons
+            .iter()
+            .chain(encephalon.sensory_neurons.iter())
+            .map(|(loc, &neuron)| (loc.clone(), neuron))
+            .collect::<Vec<_>>();
 
-        let gen_synapse_type = || {
+        for (loc, neuron) in sources {
+            encephalon.connect_to_nearby(&loc, neuron);
+        }
 
-            // SynapseType::Excitatory //For time being, all are excitatory
+        //Make reflexes
+        for reflex in reflexes.iter() {
+            encephalon.add_reflex(reflex)?;
+        }
 
-            let type_threshold = synaptic_type_ratio / (synaptic_type_ratio + 1.);
-            let val = rand::thread_rng().gen_range(0.0, 1.0);
+        Ok(encephalon)
+    }
 
-            if val > type_threshold {
-                SynapseType::Inhibitory
-            } else {
-                SynapseType::Excitatory
-            }
+    /// Gives `neuron` a plastic synapse to every neuron the geometry places near `loc`
+    #[allow(clippy::ptr_arg)]
+    fn connect_to_nearby(&mut self, loc: &Vec<i32>, neuron: NeuronId) {
+        let (plastic_locs, actuator_locs) = self.ecp_geometry.get_nearby_rx_neurons(loc);
+
+        let targets = plastic_locs
+            .iter()
+            .filter_map(|plastic_loc| {
+                self.plastic_neurons
+                    .get(plastic_loc)
+                    .map(|&target| (plastic_loc, target))
+            })
+            .chain(actuator_locs.iter().filter_map(|actuator_loc| {
+                self.actuator_neurons
+                    .get(actuator_loc)
+                    .map(|&target| (actuator_loc, target))
+            }))
+            .collect::<Vec<_>>();
+
+        for (target_loc, target) in targets {
+            self.add_random_synapse(neuron, loc, target, target_loc);
+        }
+    }
+
+    /// Plastic synapse with a random weight and type, delayed according to the distance
+    /// between the neurons
+    fn add_random_synapse(
+        &mut self,
+        source: NeuronId,
+        source_loc: &[i32],
+        target: NeuronId,
+        target_loc: &[i32],
+    ) {
+        let (low, high) = self.template.synapse_weight_ranges;
+        let weight = self.rng.gen_range(low, high);
+
+        let ratio = self.template.synaptic_type_ratio;
+        let type_threshold = ratio / (ratio + 1.);
+        let synapse_type = if self.rng.gen_range(0.0, 1.0) > type_threshold {
+            SynapseType::Inhibitory
+        } else {
+            SynapseType::Excitatory
         };
 
-        //Make synapses for plastic neurons
-        for (loc, p_neuron) in encephalon.plastic_neurons.iter() {
-            let (plastic_locs, actuator_locs) = encephalon.ecp_geometry.get_nearby_rx_neurons(loc);
-
-            for plastic_loc in plastic_locs.iter() {
-                p_neuron.add_plastic_synapse(
-                    gen_weight(),
-                    gen_synapse_type(),
-                    Rc::clone(encephalon.plastic_neurons.get(plastic_loc).unwrap())
-                        as Rc<dyn RxNeuronic>,
-                )
-            }
+        let distance = self.ecp_geometry.distance(source_loc, target_loc);
+        self.neurons.add_plastic_synapse_with_delay(
+            source,
+            weight,
+            synapse_type,
+            target,
+            self.synaptic_delay.delay(distance),
+        );
+    }
+
+    /// Places a sensory neuron for `sensor` at the first vacant location of the
+    /// geometry and connects it to the neurons nearby. Returns the location
+    pub fn add_sensor(&mut self, sensor: Arc<dyn Sensor>) -> Result<Vec<i32>, EncephalonError> {
+        let name = sensor.get_name();
+        if self.sensory_interfaces.contains_key(&name) {
+            return Err(EncephalonError::DuplicateSensorName(name));
+        }
+
+        let sensory_neurons = &self.sensory_neurons;
+        let loc = self
+            .ecp_geometry
+            .vacant_sensory_loc(&|loc| sensory_neurons.contains_key(loc))
+            .ok_or(EncephalonError::NoVacantLocation("sensory"))?;
+
+        let neuron = self
+            .neurons
+            .add_sensory_neuron(Arc::clone(&self.template.sensory_learning_rule));
+        self.sensory_neurons.insert(loc.clone(), neuron);
+        self.sensory_interfaces
+            .insert(name, SensoryInterface::new(sensor, neuron));
+
+        self.connect_to_nearby(&loc, neuron);
 
-            for actuator_loc in actuator_locs.iter() {
-                p_neuron.add_plastic_synapse(
-                    gen_weight(),
-                    gen_synapse_type(),
-                    Rc::clone(encephalon.actuator_neurons.get(actuator_loc).unwrap())
-                        as Rc<dyn RxNeuronic>,
-                )
+        Ok(loc)
+    }
+
+    /// Removes the sensor named `sensor_name` along with its neuron, synapses and
+    /// reflexes, and hands it back
+    pub fn remove_sensor(&mut self, sensor_name: &str) -> Result<Arc<dyn Sensor>, EncephalonError> {
+        let interface = self
+            .sensory_interfaces
+            .shift_remove(sensor_name)
+            .ok_or_else(|| EncephalonError::UnknownSensor(sensor_name.to_string()))?;
+        let neuron = interface.sensory_neuron;
+
+        self.reflexes.retain(|_, reflex| match reflex.source() {
+            ReflexSource::Sensor(name) => name != sensor_name,
+            ReflexSource::Plastic(_) => true,
+        });
+        self.sensory_neurons.retain(|_, &mut n| n != neuron);
+        self.neurons.remove_sensory_neuron(neuron);
+        self.shift_neurons_after(neuron);
+
+        Ok(interface.sensor)
+    }
+
+    /// Places an actuator neuron for `actuator` at the first vacant location of the
+    /// geometry, and connects the plastic neurons nearby to it. Returns the location
+    pub fn add_actuator(
+        &mut self,
+        actuator: Arc<dyn Actuator>,
+    ) -> Result<Vec<i32>, EncephalonError> {
+        let name = actuator.get_name();
+        if self.actuator_interfaces.contains_key(&name) {
+            return Err(EncephalonError::DuplicateActuatorName(name));
+        }
+
+        let actuator_neurons = &self.actuator_neurons;
+        let loc = self
+            .ecp_geometry
+            .vacant_actuator_loc(&|loc| actuator_neurons.contains_key(loc))
+            .ok_or(EncephalonError::NoVacantLocation("actuator"))?;
+
+        let neuron = self.neurons.add_actuator_neuron(
+            self.template.actuator_charge_bins,
+            self.template.actuator_fire_threshold,
+        );
+        self.neurons
+            .set_aggregation(neuron, self.template.actuator_aggregation);
+        self.actuator_neurons.insert(loc.clone(), neuron);
+        self.actuator_interfaces
+            .insert(name, ActuatorInterface::new(actuator, neuron));
+
+        for source_loc in self.ecp_geometry.get_nearby_plastic_neurons(&loc) {
+            if let Some(&source) = self.plastic_neurons.get(&source_loc) {
+                self.add_random_synapse(source, &source_loc, neuron, &loc);
             }
         }
 
-        //Make synapses for sensory neurons
-        for (loc, s_neuron) in encephalon.sensory_neurons.iter() {
-            let (plastic_locs, actuator_locs) = encephalon.ecp_geometry.get_nearby_rx_neurons(loc);
+        Ok(loc)
+    }
 
-            for plastic_loc in plastic_locs.iter() {
-                s_neuron.add_plastic_synapse(
-                    gen_weight(),
-                    gen_synapse_type(),
-                    Rc::clone(encephalon.plastic_neurons.get(plastic_loc).unwrap())
-                        as Rc<dyn RxNeuronic>,
-                )
+    /// Removes the actuator named `actuator_name` along with its neuron and every
+    /// synapse targeting it, and hands it back
+    pub fn remove_actuator(
+        &mut self,
+        actuator_name: &str,
+    ) -> Result<Arc<dyn Actuator>, EncephalonError> {
+        let interface = self
+            .actuator_interfaces
+            .shift_remove(actuator_name)
+            .ok_or_else(|| EncephalonError::UnknownActuator(actuator_name.to_string()))?;
+        let neuron = interface.actuator_neuron;
+
+        self.reflexes.retain(|_, reflex| match reflex.target() {
+            ReflexTarget::Actuator(name) => name != actuator_name,
+            ReflexTarget::Plastic(_) => true,
+        });
+        self.actuator_neurons.retain(|_, &mut n| n != neuron);
+        self.neurons.remove_actuator_neuron(neuron);
+        self.shift_neurons_after(neuron);
+
+        Ok(interface.actuator)
+    }
+
+    /// Moves everything that refers to a neuron after `removed` down one place, the
+    /// same way the arena did when `removed` was taken out of it
+    fn shift_neurons_after(&mut self, removed: NeuronId) {
+        let shift = |neuron: &mut NeuronId| match (*neuron, removed) {
+            (NeuronId::Sensory(i), NeuronId::Sensory(r)) if i > r => {
+                *neuron = NeuronId::Sensory(i - 1)
+            }
+            (NeuronId::Actuator(i), NeuronId::Actuator(r)) if i > r => {
+                *neuron = NeuronId::Actuator(i - 1)
             }
+            _ => {}
+        };
+
+        for neuron in self
+            .sensory_neurons
+            .values_mut()
+            .chain(self.actuator_neurons.values_mut())
+        {
+            shift(neuron);
+        }
+
+        for interface in self.sensory_interfaces.values_mut() {
+            shift(&mut interface.sensory_neuron);
+        }
+
+        for interface in self.actuator_interfaces.values_mut() {
+            shift(&mut interface.actuator_neuron);
+        }
+    }
+
+    /// Hard wires two neurons together with a static synapse, returning the id the
+    /// reflex can later be changed or removed with
+    pub fn add_reflex(&mut self, reflex: &Reflex) -> Result<ReflexId, EncephalonError> {
+        let (source, target) = self.reflex_neurons(reflex)?;
 
-            for actuator_loc in actuator_locs.iter() {
-                s_neuron.add_plastic_synapse(
-                    gen_weight(),
-                    gen_synapse_type(),
-                    Rc::clone(encephalon.actuator_neurons.get(actuator_loc).unwrap())
-                        as Rc<dyn RxNeuronic>,
-                )
+        Ok(self.wire_reflex(reflex, source, target))
+    }
+
+    /// Hard wires a chain of reflexes, each starting at the plastic neuron the previous
+    /// one ends at, such as a sensor driving an actuator through an interneuron.
+    /// Nothing is added if any of the reflexes can't be, otherwise the ids are returned
+    /// in the order of `reflexes`
+    pub fn add_reflex_arc(
+        &mut self,
+        reflexes: &[Reflex],
+    ) -> Result<Vec<ReflexId>, EncephalonError> {
+        for hop in reflexes.windows(2) {
+            let joined = match (hop[0].target(), hop[1].source()) {
+                (ReflexTarget::Plastic(end), ReflexSource::Plastic(start)) => end == start,
+                _ => false,
+            };
+
+            if !joined {
+                return Err(EncephalonError::InvalidParameter {
+                    parameter: "reflexes",
+                    reason: "each reflex of an arc must start at the plastic neuron the \
+                             previous one ends at"
+                        .into(),
+                });
             }
         }
 
-        //Make reflexes
-        for reflex in reflexes.iter() {
-            encephalon.add_reflex(reflex);
+        let neurons = reflexes
+            .iter()
+            .map(|reflex| self.reflex_neurons(reflex))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(reflexes
+            .iter()
+            .zip(neurons)
+            .map(|(reflex, (source, target))| self.wire_reflex(reflex, source, target))
+            .collect())
+    }
+
+    /// Source and target neuron of a reflex, checking its delay along the way
+    fn reflex_neurons(&self, reflex: &Reflex) -> Result<(NeuronId, NeuronId), EncephalonError> {
+        check_reflex_delay(reflex)?;
+
+        Ok((
+            self.reflex_source(reflex.source())?,
+            self.reflex_target(reflex.target())?,
+        ))
+    }
+
+    fn reflex_source(&self, source: &ReflexSource) -> Result<NeuronId, EncephalonError> {
+        match source {
+            ReflexSource::Sensor(name) => self
+                .sensory_interfaces
+                .get(name)
+                .map(|interface| interface.sensory_neuron)
+                .ok_or_else(|| EncephalonError::UnknownSensor(name.clone())),
+            ReflexSource::Plastic(loc) => self.plastic_neuron(loc),
         }
+    }
 
-        encephalon
+    fn reflex_target(&self, target: &ReflexTarget) -> Result<NeuronId, EncephalonError> {
+        match target {
+            ReflexTarget::Actuator(name) => self
+                .actuator_interfaces
+                .get(name)
+                .map(|interface| interface.actuator_neuron)
+                .ok_or_else(|| EncephalonError::UnknownActuator(name.clone())),
+            ReflexTarget::Plastic(loc) => self.plastic_neuron(loc),
+        }
     }
 
-    fn add_reflex(&mut self, reflex: &Reflex) {
-        let sensory_interface = self.sensory_interfaces.get(&reflex.sensor_name).unwrap();
-        let actuator_interface = self.actuator_interfaces.get(&reflex.actuator_name).unwrap();
+    fn plastic_neuron(&self, loc: &[i32]) -> Result<NeuronId, EncephalonError> {
+        self.plastic_neurons
+            .get(loc)
+            .copied()
+            .ok_or_else(|| EncephalonError::UnknownPlasticNeuron(loc.to_vec()))
+    }
 
-        sensory_interface.sensory_neuron.add_static_synapse(
+    fn wire_reflex(&mut self, reflex: &Reflex, source: NeuronId, target: NeuronId) -> ReflexId {
+        self.neurons.add_static_synapse_with_delay(
+            source,
             reflex.weight,
             reflex.synapse_type,
-            Rc::clone(&actuator_interface.actuator_neuron) as Rc<dyn RxNeuronic>,
+            target,
+            reflex.delay,
         );
+
+        let id = ReflexId(self.next_reflex_id);
+        self.next_reflex_id += 1;
+        self.reflexes.insert(id, reflex.clone());
+
+        id
+    }
+
+    /// Every reflex along with its id, in the order they were added. See `reflex`
+    pub fn reflexes(&self) -> impl Iterator<Item = (ReflexId, Reflex)> + '_ {
+        self.reflexes
+            .keys()
+            .filter_map(move |&id| Some((id, self.reflex(id)?)))
+    }
+
+    /// The weight is read from the reflex's static synapse, so it includes whatever
+    /// learning has done to it since the reflex was added
+    pub fn reflex(&self, id: ReflexId) -> Option<Reflex> {
+        let (source, index) = self.reflex_synapse(id).ok()?;
+
+        let mut reflex = self.reflexes[&id].clone();
+        reflex.weight = self.neurons.synapses(source).1.weights[index];
+
+        Some(reflex)
+    }
+
+    /// The weight must be finite and non-negative, as in `Reflex::try_new`
+    pub fn update_reflex_weight(
+        &mut self,
+        id: ReflexId,
+        weight: f32,
+    ) -> Result<(), EncephalonError> {
+        if !weight.is_finite() || weight < 0. {
+            return Err(EncephalonError::InvalidParameter {
+                parameter: "weight",
+                reason: "reflex weights must be finite and non-negative".into(),
+            });
+        }
+
+        let (source, index) = self.reflex_synapse(id)?;
+        self.neurons.synapses_mut(source).1.weights[index] = weight;
+
+        Ok(())
+    }
+
+    /// Removes the static synapse of a reflex and hands the reflex back
+    pub fn remove_reflex(&mut self, id: ReflexId) -> Result<Reflex, EncephalonError> {
+        let (source, index) = self.reflex_synapse(id)?;
+        let reflex = self.reflex(id).unwrap();
+
+        self.neurons.synapses_mut(source).1.remove(index);
+        self.reflexes.shift_remove(&id);
+
+        Ok(reflex)
     }
 
-    pub fn run_cycle(&mut self) {
+    /// Source neuron of a reflex, along with the index of the reflex into its static
+    /// synapses
+    fn reflex_synapse(&self, id: ReflexId) -> Result<(NeuronId, usize), EncephalonError> {
+        let (position, _, reflex) = self
+            .reflexes
+            .get_full(&id)
+            .ok_or(EncephalonError::UnknownReflex(id))?;
+
+        let index = self
+            .reflexes
+            .values()
+            .take(position)
+            .filter(|other| other.source() == reflex.source())
+            .count();
+
+        Ok((self.reflex_source(reflex.source())?, index))
+    }
+
+    /// Returns the stats of the cycle when enabled with `set_cycle_stats`
+    pub fn run_cycle(&mut self) -> Option<CycleStats> {
+        let stats = self.run_neurons(false);
+        self.restructure_if_due();
+        self.end_cycle();
+
+        stats
+    }
+
+    /// Returns the stats of the cycle when enabled with `set_cycle_stats`
+    pub fn run_static_cycle(&mut self) -> Option<CycleStats> {
+        let stats = self.run_neurons(true);
+        self.end_cycle();
+
+        stats
+    }
+
+    fn run_neurons(&mut self, static_cycle: bool) -> Option<CycleStats> {
+        let observed = !self.observers.is_empty();
+        if observed {
+            self.notify_cycle_start();
+        }
+
         self.cycle = self.cycle.next_cycle();
 
         for sensory_interface in self.sensory_interfaces.values() {
-            sensory_interface.run_cycle();
+            sensory_interface.run_cycle(&mut self.neurons);
         }
 
         for actuator_interface in self.actuator_interfaces.values() {
-            actuator_interface.run_cycle();
+            actuator_interface.run_cycle(&self.neurons);
         }
 
-        for sensory_neuron in self.sensory_neurons.values() {
-            sensory_neuron.run_cycle(self.cycle);
+        if observed {
+            self.notify_actuator_outputs();
         }
 
-        for plastic_neuron in self.plastic_neurons.values() {
-            plastic_neuron.run_cycle(self.cycle);
+        let weights_before = if self.cycle_stats || observed {
+            self.weights_before_cycle()
+        } else {
+            Vec::new()
+        };
+
+        self.run_arena(static_cycle);
+
+        if observed {
+            self.notify_weight_updates(&weights_before);
+            self.notify_fires();
         }
 
-        for actuator_neuron in self.actuator_neurons.values() {
-            actuator_neuron.run_cycle(self.cycle);
+        if self.cycle_stats {
+            Some(self.cycle_stats(&weights_before))
+        } else {
+            None
         }
     }
 
-    pub fn run_static_cycle(&mut self) {
-        self.cycle = self.cycle.next_cycle();
+    fn end_cycle(&mut self) {
+        self.record_fires();
 
-        for sensory_interface in self.sensory_interfaces.values() {
-            sensory_interface.run_cycle();
+        if !self.observers.is_empty() {
+            self.notify_cycle_end();
         }
+    }
 
-        for actuator_interface in self.actuator_interfaces.values() {
-            actuator_interface.run_cycle();
+    fn run_arena(&mut self, static_cycle: bool) {
+        #[cfg(feature = "parallel")]
+        {
+            if self.executor.threads() > 1 {
+                self.neurons
+                    .run_neurons(self.cycle, static_cycle, &self.executor);
+                return;
+            }
         }
 
-        for sensory_neuron in self.sensory_neurons.values() {
-            sensory_neuron.run_static_cycle(self.cycle);
+        if static_cycle {
+            self.neurons.run_static_cycle(self.cycle);
+        } else {
+            self.neurons.run_cycle(self.cycle);
         }
+    }
 
-        for plastic_neuron in self.plastic_neurons.values() {
-            plastic_neuron.run_static_cycle(self.cycle);
-        }
+    /// Whether `run_cycle` and `run_static_cycle` return stats. Collecting them walks
+    /// every synapse twice each cycle
+    pub fn set_cycle_stats(&mut self, enabled: bool) {
+        self.cycle_stats = enabled;
+    }
+
+    pub fn cycle_stats_enabled(&self) -> bool {
+        self.cycle_stats
+    }
 
-        for actuator_neuron in self.actuator_neurons.values() {
-            actuator_neuron.run_static_cycle(self.cycle);
+    /// Calls `observer` back from the next cycle on. Without observers nothing is
+    /// spent on calling them back
+    pub fn add_observer(&mut self, observer: Box<dyn EncephalonObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Detaches every observer, handing them back in the order they were added
+    pub fn take_observers(&mut self) -> Vec<Box<dyn EncephalonObserver>> {
+        std::mem::take(&mut self.observers)
+    }
+
+    /// Starts recording every neuron that fires from the next cycle on, or stops
+    /// recording with `None`. Hands back the recorder that was replaced
+    pub fn set_fire_recorder(&mut self, recorder: Option<FireRecorder>) -> Option<FireRecorder> {
+        std::mem::replace(&mut self.fire_recorder, recorder)
+    }
+
+    pub fn fire_recorder(&self) -> Option<&FireRecorder> {
+        self.fire_recorder.as_ref()
+    }
+
+    pub fn fire_recorder_mut(&mut self) -> Option<&mut FireRecorder> {
+        self.fire_recorder.as_mut()
+    }
+
+    /// Hands the neurons that fired in the cycle just run to the fire recorder, if any
+    fn record_fires(&mut self) {
+        let recorder = match &mut self.fire_recorder {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        let cycle_number = self.neurons.cycle_number() - 1;
+        let neurons = [
+            (NeuronKind::Sensory, &self.sensory_neurons),
+            (NeuronKind::Plastic, &self.plastic_neurons),
+            (NeuronKind::Actuator, &self.actuator_neurons),
+        ];
+
+        for (kind, locs) in neurons.iter() {
+            for (loc, &neuron) in locs.iter() {
+                if let Some(measure) = self.neurons.last_cycle_fire(neuron, self.cycle) {
+                    recorder.record(FireEvent {
+                        cycle: cycle_number,
+                        loc: loc.clone(),
+                        kind: *kind,
+                        measure,
+                    });
+                }
+            }
         }
     }
 
-    pub fn clear(&self) {
-        for actuator_interface in self.actuator_interfaces.values() {
-            actuator_interface.clear();
+    /// Number of threads each cycle is evaluated on. The results are identical
+    /// regardless of the number of threads
+    #[cfg(feature = "parallel")]
+    pub fn threads(&self) -> usize {
+        self.executor.threads()
+    }
+
+    /// Restarts the worker threads of the encephalon, if the number changes. Panics if
+    /// `threads` is 0
+    #[cfg(feature = "parallel")]
+    pub fn set_threads(&mut self, threads: usize) {
+        assert!(threads > 0, "An encephalon needs at least one thread");
+
+        if threads != self.executor.threads() {
+            self.executor = parallel::ThreadedExecutor::new(threads);
         }
+    }
+
+    pub fn structural_plasticity(&self) -> Option<&StructuralPlasticity> {
+        self.structural_plasticity.as_ref()
+    }
+
+    /// Number of synapses pruned and grown by structural plasticity so far
+    pub fn structural_changes(&self) -> StructuralChanges {
+        self.structural_changes
+    }
 
-        for sensory_neuron in self.sensory_neurons.values() {
-            sensory_neuron.clear();
+    /// Number of plastic and static synapses currently in the encephalon
+    pub fn synapse_count(&self) -> usize {
+        self.sensory_neurons
+            .values()
+            .chain(self.plastic_neurons.values())
+            .map(|&neuron| {
+                let (plastic_synapses, static_synapses) = self.neurons.synapses(neuron);
+                plastic_synapses.len() + static_synapses.len()
+            })
+            .sum()
+    }
+
+    /// Kind and location of every neuron synapses can target, by rx index
+    fn rx_locs(&self) -> HashMap<u32, (NeuronKind, &Vec<i32>)> {
+        self.plastic_neurons
+            .iter()
+            .map(|(loc, &neuron)| (neuron, loc, NeuronKind::Plastic))
+            .chain(
+                self.actuator_neurons
+                    .iter()
+                    .map(|(loc, &neuron)| (neuron, loc, NeuronKind::Actuator)),
+            )
+            .map(|(neuron, loc, kind)| (self.neurons.rx_index(neuron), (kind, loc)))
+            .collect()
+    }
+
+    /// Every synapse as it is now, those of sensory neurons first and then those of
+    /// plastic neurons, each neuron's plastic synapses before its static ones
+    pub fn synapse_snapshot(&self) -> SynapseSnapshot {
+        let targets = self.rx_locs();
+
+        let sources = self
+            .sensory_neurons
+            .iter()
+            .map(|(loc, &neuron)| (loc, neuron, NeuronKind::Sensory))
+            .chain(
+                self.plastic_neurons
+                    .iter()
+                    .map(|(loc, &neuron)| (loc, neuron, NeuronKind::Plastic)),
+            );
+
+        let mut synapses = Vec::new();
+        for (source, neuron, source_kind) in sources {
+            let (plastic_synapses, static_synapses) = self.neurons.synapses(neuron);
+
+            for (kind, synapses_of_kind) in [
+                (SynapseKind::Plastic, plastic_synapses),
+                (SynapseKind::Static, static_synapses),
+            ] {
+                for i in 0..synapses_of_kind.len() {
+                    let (target_kind, target) = targets[&synapses_of_kind.targets[i]];
+
+                    synapses.push(SynapseState {
+                        source_kind,
+                        source: source.clone(),
+                        target_kind,
+                        target: target.clone(),
+                        kind,
+                        synapse_type: synapses_of_kind.types[i],
+                        weight: synapses_of_kind.weights[i],
+                        delay: synapses_of_kind.delays[i],
+                    });
+                }
+            }
         }
 
-        for plastic_neuron in self.plastic_neurons.values() {
-            plastic_neuron.clear();
+        SynapseSnapshot {
+            cycle: self.neurons.cycle_number(),
+            synapses,
         }
+    }
 
-        for actuator_neuron in self.actuator_neurons.values() {
-            actuator_neuron.clear();
+    /// Current fire threshold of the plastic neuron at `loc`, which only differs from
+    /// its fire threshold with adaptive thresholds
+    pub fn plastic_fire_threshold(&self, loc: &[i32]) -> Option<f32> {
+        self.plastic_neurons
+            .get(loc)
+            .map(|&neuron| self.neurons.current_fire_threshold(neuron))
+    }
+
+    /// Current fire threshold of the neuron driving the actuator named `actuator_name`
+    pub fn actuator_fire_threshold(&self, actuator_name: &str) -> Option<f32> {
+        self.actuator_interfaces
+            .get(actuator_name)
+            .map(|interface| {
+                self.neurons
+                    .current_fire_threshold(interface.actuator_neuron)
+            })
+    }
+
+    pub fn clear(&mut self) {
+        for actuator_interface in self.actuator_interfaces.values() {
+            actuator_interface.clear();
         }
+
+        self.neurons.clear();
     }
 
+    /// Panics if the sensor can't be added, see `try_add_reflex_sensor`
     pub fn add_reflex_sensor(
         &mut self,
-        sensor: Rc<dyn Sensor>,
+        sensor: Arc<dyn Sensor>,
         sensor_id: i32, //This must be unique to this sensor
         reflexes: Vec<Reflex>,
         weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
     ) {
-        let sensory_neuron = Rc::new(SensoryNeuron::new(weight_modifier));
+        self.try_add_reflex_sensor(sensor, sensor_id, reflexes, weight_modifier)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    /// Adds a sensor that is only connected through its reflexes. Nothing is added
+    /// if the id or name is already taken, if a reflex refers to an unknown sensor
+    /// or actuator, or if a reflex has a delay of 0
+    pub fn try_add_reflex_sensor(
+        &mut self,
+        sensor: Arc<dyn Sensor>,
+        sensor_id: i32, //This must be unique to this sensor
+        reflexes: Vec<Reflex>,
+        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
+    ) -> Result<(), EncephalonError> {
         let sensor_label = vec![sensor_id];
+        let sensor_name = sensor.get_name();
 
         if self.sensory_neurons.contains_key(&sensor_label) {
-            panic!("Sensor with this {:?} id already exists", sensor_label);
+            return Err(EncephalonError::DuplicateSensorLocation(sensor_label));
         }
 
-        self.sensory_neurons
-            .insert(sensor_label, Rc::clone(&sensory_neuron));
+        if self.sensory_interfaces.contains_key(&sensor_name) {
+            return Err(EncephalonError::DuplicateSensorName(sensor_name));
+        }
 
-        self.sensory_interfaces.insert(
-            sensor.get_name(),
-            SensoryInterface::new(sensor, Rc::clone(&sensory_neuron)),
-        );
+        for reflex in &reflexes {
+            check_reflex_delay(reflex)?;
+
+            if *reflex.source() != ReflexSource::Sensor(sensor_name.clone()) {
+                self.reflex_source(reflex.source())?;
+            }
+
+            self.reflex_target(reflex.target())?;
+        }
+
+        let sensory_neuron = self
+            .neurons
+            .add_sensory_neuron(Arc::new(WeightModifier(weight_modifier)));
+
+        self.sensory_neurons.insert(sensor_label, sensory_neuron);
+
+        self.sensory_interfaces
+            .insert(sensor_name, SensoryInterface::new(sensor, sensory_neuron));
 
         for reflex in &reflexes {
-            self.add_reflex(reflex);
+            self.add_reflex(reflex)?;
         }
+
+        Ok(())
     }
+
+    /// Writes the geometry parameters, every neuron and every synapse (along with
+    /// the current charges and fire receipts) to `path`, so that the encephalon
+    /// can later be restored exactly with `Encephalon::load`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, persistence::write_encephalon(self))
+    }
+
+    /// Restores an encephalon written by `Encephalon::save`.
+    ///
+    /// The geometry is rebuilt as a `G` from the saved parameters. Sensors and actuators
+    /// are matched to their neurons by name, so exactly the sensors and actuators the
+    /// saved encephalon was using must be passed. Weight modifiers can't be written to
+    /// disk, so `weight_modifier` is used for every restored neuron. See
+    /// `EncephalonLoader` for seeding the restored encephalon.
+    pub fn load<G: EcpGeometry + 'static, P: AsRef<Path>>(
+        path: P,
+        sensors: Vec<Arc<dyn Sensor>>,
+        actuators: Vec<Arc<dyn Actuator>>,
+        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
+    ) -> io::Result<Encephalon> {
+        Encephalon::load_with_learning_rule::<G, P>(
+            path,
+            sensors,
+            actuators,
+            Arc::new(WeightModifier(weight_modifier)),
+        )
+    }
+
+    /// Same as `load`, with `learning_rule` used for every restored neuron
+    pub fn load_with_learning_rule<G: EcpGeometry + 'static, P: AsRef<Path>>(
+        path: P,
+        sensors: Vec<Arc<dyn Sensor>>,
+        actuators: Vec<Arc<dyn Actuator>>,
+        learning_rule: Arc<dyn LearningRule>,
+    ) -> io::Result<Encephalon> {
+        EncephalonLoader::new(sensors, actuators)
+            .learning_rule(learning_rule)
+            .load::<G, P>(path)
+    }
+}
+
+fn check_reflex_delay(reflex: &Reflex) -> Result<(), EncephalonError> {
+    if reflex.delay == 0 {
+        return Err(EncephalonError::InvalidParameter {
+            parameter: "delay",
+            reason: "reflex delays must be at least one cycle".into(),
+        });
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -0,0 +1,705 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::actuator::Actuator;
+use crate::ecp_geometry::{EcpGeometry, SynapticDelay};
+use crate::encephalon::{Encephalon, EncephalonError};
+use crate::learning_rule::{LearningRule, Stdp, WeightModifier};
+use crate::neuron::{
+    basic_weight_modifier, AdaptiveThreshold, Aggregation, LeakyIntegration, MeasureAggregation,
+    Refractory,
+};
+use crate::reflex::Reflex;
+use crate::sensor::Sensor;
+use crate::structural_plasticity::StructuralPlasticity;
+use crate::weight_regulation::{Homeostasis, Normalization, WeightRegulation};
+
+/// Resolved parameters for each class of neuron
+pub(crate) struct NeuronParameters {
+    pub(crate) plastic_charge_bins: u8,
+    pub(crate) actuator_charge_bins: u8,
+    pub(crate) plastic_aggregation: Aggregation,
+    pub(crate) actuator_aggregation: Aggregation,
+    pub(crate) plastic_fire_threshold: f32,
+    pub(crate) actuator_fire_threshold: f32,
+    pub(crate) plastic_learning_rule: Arc<dyn LearningRule>,
+    pub(crate) sensory_learning_rule: Arc<dyn LearningRule>,
+    pub(crate) stdp: Option<Stdp>,
+    pub(crate) weight_regulation: WeightRegulation,
+    pub(crate) adaptive_threshold: Option<AdaptiveThreshold>,
+    pub(crate) refractory: Option<Refractory>,
+    pub(crate) leaky_integration: Option<LeakyIntegration>,
+    pub(crate) synaptic_delay: SynapticDelay,
+    pub(crate) structural_plasticity: Option<StructuralPlasticity>,
+    pub(crate) synaptic_type_ratio: f32,
+    pub(crate) synapse_weight_ranges: (f32, f32),
+    #[cfg(feature = "parallel")]
+    pub(crate) threads: usize,
+}
+
+/// Parameters the neurons and synapses added after an encephalon is built are created
+/// with
+#[derive(Clone)]
+pub(crate) struct NeuronTemplate {
+    pub(crate) sensory_learning_rule: Arc<dyn LearningRule>,
+    pub(crate) actuator_charge_bins: u8,
+    pub(crate) actuator_fire_threshold: f32,
+    pub(crate) actuator_aggregation: Aggregation,
+    pub(crate) synaptic_type_ratio: f32,
+    pub(crate) synapse_weight_ranges: (f32, f32),
+}
+
+impl NeuronTemplate {
+    /// Template of an encephalon built with the default parameters
+    pub(crate) fn with_learning_rule(learning_rule: Arc<dyn LearningRule>) -> NeuronTemplate {
+        NeuronTemplate {
+            sensory_learning_rule: learning_rule,
+            actuator_charge_bins: EncephalonBuilder::DEFAULT_CHARGE_BINS,
+            actuator_fire_threshold: EncephalonBuilder::DEFAULT_FIRE_THRESHOLD,
+            actuator_aggregation: Aggregation::default(),
+            synaptic_type_ratio: EncephalonBuilder::DEFAULT_SYNAPTIC_TYPE_RATIO,
+            synapse_weight_ranges: EncephalonBuilder::DEFAULT_SYNAPSE_WEIGHT_RANGES,
+        }
+    }
+}
+
+/// Builds an `Encephalon` with named parameters.
+///
+/// Parameters that aren't set fall back to defaults, and parameters set for all
+/// neurons (like `fire_threshold`) can be overridden for a single class of neuron
+/// (like `actuator_fire_threshold`). `build` validates everything and reports the
+/// first invalid parameter instead of panicking.
+pub struct EncephalonBuilder {
+    ecp_geometry: Box<dyn EcpGeometry>,
+    sensors: Vec<Arc<dyn Sensor>>,
+    actuators: Vec<Arc<dyn Actuator>>,
+    reflexes: Vec<Reflex>,
+
+    charge_bins: u8,
+    aggregation: Aggregation,
+    learning_rule: Arc<dyn LearningRule>,
+    synaptic_type_ratio: f32,
+    fire_threshold: f32,
+    synapse_weight_ranges: (f32, f32),
+
+    // Per class overrides
+    plastic_charge_bins: Option<u8>,
+    actuator_charge_bins: Option<u8>,
+    plastic_aggregation: Option<Aggregation>,
+    actuator_aggregation: Option<Aggregation>,
+    plastic_fire_threshold: Option<f32>,
+    actuator_fire_threshold: Option<f32>,
+    plastic_learning_rule: Option<Arc<dyn LearningRule>>,
+    sensory_learning_rule: Option<Arc<dyn LearningRule>>,
+
+    // Replaces the learning rules when set
+    stdp: Option<Stdp>,
+    weight_regulation: WeightRegulation,
+    adaptive_threshold: Option<AdaptiveThreshold>,
+    refractory: Option<Refractory>,
+    leaky_integration: Option<LeakyIntegration>,
+    synaptic_delay: SynapticDelay,
+    structural_plasticity: Option<StructuralPlasticity>,
+
+    // Source of every random decision, seeded from entropy if not set
+    rng: Option<Box<dyn RngCore + Send + Sync>>,
+
+    #[cfg(feature = "parallel")]
+    threads: usize,
+}
+
+impl EncephalonBuilder {
+    pub const DEFAULT_CHARGE_BINS: u8 = 10;
+    pub const DEFAULT_SYNAPTIC_TYPE_RATIO: f32 = 2.;
+    pub const DEFAULT_FIRE_THRESHOLD: f32 = 10.;
+    pub const DEFAULT_SYNAPSE_WEIGHT_RANGES: (f32, f32) = (2., 5.);
+
+    pub fn new(ecp_geometry: Box<dyn EcpGeometry>) -> EncephalonBuilder {
+        EncephalonBuilder {
+            ecp_geometry,
+            sensors: Vec::new(),
+            actuators: Vec::new(),
+            reflexes: Vec::new(),
+            charge_bins: EncephalonBuilder::DEFAULT_CHARGE_BINS,
+            aggregation: Aggregation::default(),
+            learning_rule: Arc::new(WeightModifier(basic_weight_modifier)),
+            synaptic_type_ratio: EncephalonBuilder::DEFAULT_SYNAPTIC_TYPE_RATIO,
+            fire_threshold: EncephalonBuilder::DEFAULT_FIRE_THRESHOLD,
+            synapse_weight_ranges: EncephalonBuilder::DEFAULT_SYNAPSE_WEIGHT_RANGES,
+            plastic_charge_bins: None,
+            actuator_charge_bins: None,
+            plastic_aggregation: None,
+            actuator_aggregation: None,
+            plastic_fire_threshold: None,
+            actuator_fire_threshold: None,
+            plastic_learning_rule: None,
+            sensory_learning_rule: None,
+            stdp: None,
+            weight_regulation: WeightRegulation::default(),
+            adaptive_threshold: None,
+            refractory: None,
+            leaky_integration: None,
+            synaptic_delay: SynapticDelay::default(),
+            structural_plasticity: None,
+            rng: None,
+            #[cfg(feature = "parallel")]
+            threads: 1,
+        }
+    }
+
+    pub fn sensors(mut self, sensors: Vec<Arc<dyn Sensor>>) -> EncephalonBuilder {
+        self.sensors.extend(sensors);
+        self
+    }
+
+    pub fn sensor(mut self, sensor: Arc<dyn Sensor>) -> EncephalonBuilder {
+        self.sensors.push(sensor);
+        self
+    }
+
+    pub fn actuators(mut self, actuators: Vec<Arc<dyn Actuator>>) -> EncephalonBuilder {
+        self.actuators.extend(actuators);
+        self
+    }
+
+    pub fn actuator(mut self, actuator: Arc<dyn Actuator>) -> EncephalonBuilder {
+        self.actuators.push(actuator);
+        self
+    }
+
+    pub fn reflexes(mut self, reflexes: Vec<Reflex>) -> EncephalonBuilder {
+        self.reflexes.extend(reflexes);
+        self
+    }
+
+    pub fn reflex(mut self, reflex: Reflex) -> EncephalonBuilder {
+        self.reflexes.push(reflex);
+        self
+    }
+
+    /// Number of charge bins for plastic and actuator neurons
+    pub fn charge_bins(mut self, charge_bins: u8) -> EncephalonBuilder {
+        self.charge_bins = charge_bins;
+        self
+    }
+
+    pub fn plastic_charge_bins(mut self, charge_bins: u8) -> EncephalonBuilder {
+        self.plastic_charge_bins = Some(charge_bins);
+        self
+    }
+
+    pub fn actuator_charge_bins(mut self, charge_bins: u8) -> EncephalonBuilder {
+        self.actuator_charge_bins = Some(charge_bins);
+        self
+    }
+
+    /// How plastic and actuator neurons aggregate their charge, a weighted average with
+    /// subtractive inhibition by default
+    pub fn aggregation(mut self, aggregation: Aggregation) -> EncephalonBuilder {
+        self.aggregation = aggregation;
+        self
+    }
+
+    pub fn plastic_aggregation(mut self, aggregation: Aggregation) -> EncephalonBuilder {
+        self.plastic_aggregation = Some(aggregation);
+        self
+    }
+
+    pub fn actuator_aggregation(mut self, aggregation: Aggregation) -> EncephalonBuilder {
+        self.actuator_aggregation = Some(aggregation);
+        self
+    }
+
+    /// Learning rule for sensory and plastic neurons, `basic_weight_modifier` by default
+    pub fn learning_rule<R: LearningRule + 'static>(
+        mut self,
+        learning_rule: R,
+    ) -> EncephalonBuilder {
+        self.learning_rule = Arc::new(learning_rule);
+        self
+    }
+
+    pub fn plastic_learning_rule<R: LearningRule + 'static>(
+        mut self,
+        learning_rule: R,
+    ) -> EncephalonBuilder {
+        self.plastic_learning_rule = Some(Arc::new(learning_rule));
+        self
+    }
+
+    pub fn sensory_learning_rule<R: LearningRule + 'static>(
+        mut self,
+        learning_rule: R,
+    ) -> EncephalonBuilder {
+        self.sensory_learning_rule = Some(Arc::new(learning_rule));
+        self
+    }
+
+    /// Same as `learning_rule(WeightModifier(weight_modifier))`
+    pub fn weight_modifier(
+        self,
+        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
+    ) -> EncephalonBuilder {
+        self.learning_rule(WeightModifier(weight_modifier))
+    }
+
+    pub fn plastic_weight_modifier(
+        self,
+        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
+    ) -> EncephalonBuilder {
+        self.plastic_learning_rule(WeightModifier(weight_modifier))
+    }
+
+    pub fn sensory_weight_modifier(
+        self,
+        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
+    ) -> EncephalonBuilder {
+        self.sensory_learning_rule(WeightModifier(weight_modifier))
+    }
+
+    /// Makes every synapse learn with spike-timing-dependent plasticity instead of the
+    /// learning rules
+    pub fn stdp(mut self, stdp: Stdp) -> EncephalonBuilder {
+        self.stdp = Some(stdp);
+        self
+    }
+
+    /// Makes the fire thresholds of plastic and actuator neurons rise when they fire
+    /// and decay back over the following cycles
+    pub fn adaptive_threshold(
+        mut self,
+        adaptive_threshold: AdaptiveThreshold,
+    ) -> EncephalonBuilder {
+        self.adaptive_threshold = Some(adaptive_threshold);
+        self
+    }
+
+    /// Gives plastic and actuator neurons a refractory period after they fire
+    pub fn refractory(mut self, refractory: Refractory) -> EncephalonBuilder {
+        self.refractory = Some(refractory);
+        self
+    }
+
+    /// Carries part of the charge of plastic and actuator neurons that don't fire over
+    /// into the next cycle
+    pub fn leaky_integration(mut self, leaky_integration: LeakyIntegration) -> EncephalonBuilder {
+        self.leaky_integration = Some(leaky_integration);
+        self
+    }
+
+    /// Upper bound on learned weights, unbounded by default
+    pub fn max_weight(mut self, max_weight: f32) -> EncephalonBuilder {
+        self.weight_regulation.max_weight = Some(max_weight);
+        self
+    }
+
+    pub fn normalization(mut self, normalization: Normalization) -> EncephalonBuilder {
+        self.weight_regulation.normalization = Some(normalization);
+        self
+    }
+
+    pub fn homeostasis(mut self, homeostasis: Homeostasis) -> EncephalonBuilder {
+        self.weight_regulation.homeostasis = Some(homeostasis);
+        self
+    }
+
+    /// Delay of the synapses between neurons, one cycle by default. Reflexes keep
+    /// their own delay
+    pub fn synaptic_delay(mut self, synaptic_delay: SynapticDelay) -> EncephalonBuilder {
+        self.synaptic_delay = synaptic_delay;
+        self
+    }
+
+    /// Prunes weak plastic synapses and grows new ones between co-active nearby neurons
+    pub fn structural_plasticity(
+        mut self,
+        structural_plasticity: StructuralPlasticity,
+    ) -> EncephalonBuilder {
+        self.structural_plasticity = Some(structural_plasticity);
+        self
+    }
+
+    /// Ratio of excitatory to inhibitory synapses
+    pub fn synaptic_type_ratio(mut self, synaptic_type_ratio: f32) -> EncephalonBuilder {
+        self.synaptic_type_ratio = synaptic_type_ratio;
+        self
+    }
+
+    /// Fire threshold for plastic and actuator neurons
+    pub fn fire_threshold(mut self, fire_threshold: f32) -> EncephalonBuilder {
+        self.fire_threshold = fire_threshold;
+        self
+    }
+
+    pub fn plastic_fire_threshold(mut self, fire_threshold: f32) -> EncephalonBuilder {
+        self.plastic_fire_threshold = Some(fire_threshold);
+        self
+    }
+
+    pub fn actuator_fire_threshold(mut self, fire_threshold: f32) -> EncephalonBuilder {
+        self.actuator_fire_threshold = Some(fire_threshold);
+        self
+    }
+
+    /// Range the initial weights of plastic synapses are drawn from
+    pub fn synapse_weight_ranges(mut self, synapse_weight_ranges: (f32, f32)) -> EncephalonBuilder {
+        self.synapse_weight_ranges = synapse_weight_ranges;
+        self
+    }
+
+    /// Makes every random decision of the encephalon reproducible: two encephalons built
+    /// with the same seed and parameters are identical
+    pub fn seed(mut self, seed: u64) -> EncephalonBuilder {
+        self.rng = Some(Box::new(StdRng::seed_from_u64(seed)));
+        self
+    }
+
+    /// Uses `rng` for every random decision of the encephalon
+    pub fn rng<R: RngCore + Send + Sync + 'static>(mut self, rng: R) -> EncephalonBuilder {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    /// Number of threads each cycle is evaluated on, 1 by default. The results are
+    /// identical regardless of the number of threads
+    #[cfg(feature = "parallel")]
+    pub fn threads(mut self, threads: usize) -> EncephalonBuilder {
+        self.threads = threads;
+        self
+    }
+
+    pub fn build(self) -> Result<Encephalon, EncephalonError> {
+        self.finish(true)
+    }
+
+    /// Builds the encephalon the way `Encephalon::new` always has, only checking that
+    /// the sensors and actuators fit the geometry
+    pub(crate) fn build_unvalidated(self) -> Result<Encephalon, EncephalonError> {
+        self.finish(false)
+    }
+
+    fn finish(self, validate: bool) -> Result<Encephalon, EncephalonError> {
+        let expected_sensors = self.ecp_geometry.get_num_sensory();
+        if expected_sensors as usize != self.sensors.len() {
+            return Err(EncephalonError::SensorCountMismatch {
+                expected: expected_sensors,
+                found: self.sensors.len(),
+            });
+        }
+
+        let expected_actuators = self.ecp_geometry.get_num_actuator();
+        if expected_actuators as usize != self.actuators.len() {
+            return Err(EncephalonError::ActuatorCountMismatch {
+                expected: expected_actuators,
+                found: self.actuators.len(),
+            });
+        }
+
+        if validate {
+            let mut sensor_names = HashSet::new();
+            for sensor in self.sensors.iter() {
+                let name = sensor.get_name();
+                if !sensor_names.insert(name.clone()) {
+                    return Err(EncephalonError::DuplicateSensorName(name));
+                }
+            }
+
+            let mut actuator_names = HashSet::new();
+            for actuator in self.actuators.iter() {
+                let name = actuator.get_name();
+                if !actuator_names.insert(name.clone()) {
+                    return Err(EncephalonError::DuplicateActuatorName(name));
+                }
+            }
+        }
+
+        let learning_rule = &self.learning_rule;
+        let params = NeuronParameters {
+            plastic_charge_bins: self.plastic_charge_bins.unwrap_or(self.charge_bins),
+            actuator_charge_bins: self.actuator_charge_bins.unwrap_or(self.charge_bins),
+            plastic_aggregation: self.plastic_aggregation.unwrap_or(self.aggregation),
+            actuator_aggregation: self.actuator_aggregation.unwrap_or(self.aggregation),
+            plastic_fire_threshold: self.plastic_fire_threshold.unwrap_or(self.fire_threshold),
+            actuator_fire_threshold: self.actuator_fire_threshold.unwrap_or(self.fire_threshold),
+            plastic_learning_rule: self
+                .plastic_learning_rule
+                .unwrap_or_else(|| Arc::clone(learning_rule)),
+            sensory_learning_rule: self
+                .sensory_learning_rule
+                .unwrap_or_else(|| Arc::clone(learning_rule)),
+            stdp: self.stdp,
+            weight_regulation: self.weight_regulation,
+            adaptive_threshold: self.adaptive_threshold,
+            refractory: self.refractory,
+            leaky_integration: self.leaky_integration,
+            synaptic_delay: self.synaptic_delay,
+            structural_plasticity: self.structural_plasticity,
+            synaptic_type_ratio: self.synaptic_type_ratio,
+            synapse_weight_ranges: self.synapse_weight_ranges,
+            #[cfg(feature = "parallel")]
+            threads: self.threads,
+        };
+
+        if validate {
+            params.validate()?;
+        }
+
+        Encephalon::from_parameters(
+            self.ecp_geometry,
+            self.sensors,
+            self.actuators,
+            self.reflexes,
+            &params,
+            self.rng.unwrap_or_else(|| Box::new(StdRng::from_entropy())),
+        )
+    }
+}
+
+impl NeuronParameters {
+    pub(crate) fn template(&self) -> NeuronTemplate {
+        NeuronTemplate {
+            sensory_learning_rule: Arc::clone(&self.sensory_learning_rule),
+            actuator_charge_bins: self.actuator_charge_bins,
+            actuator_fire_threshold: self.actuator_fire_threshold,
+            actuator_aggregation: self.actuator_aggregation,
+            synaptic_type_ratio: self.synaptic_type_ratio,
+            synapse_weight_ranges: self.synapse_weight_ranges,
+        }
+    }
+
+    fn validate(&self) -> Result<(), EncephalonError> {
+        let invalid = |parameter: &'static str, reason: &str| {
+            Err(EncephalonError::InvalidParameter {
+                parameter,
+                reason: reason.into(),
+            })
+        };
+
+        if self.plastic_charge_bins == 0 {
+            return invalid("plastic_charge_bins", "must be at least 1");
+        }
+
+        if self.actuator_charge_bins == 0 {
+            return invalid("actuator_charge_bins", "must be at least 1");
+        }
+
+        let valid_aggregation = |aggregation: &Aggregation| match aggregation.measure {
+            MeasureAggregation::Softmax { temperature } => {
+                temperature.is_finite() && temperature > 0.
+            }
+            _ => true,
+        };
+
+        if !valid_aggregation(&self.plastic_aggregation) {
+            return invalid(
+                "plastic_aggregation",
+                "softmax temperature must be finite and positive",
+            );
+        }
+
+        if !valid_aggregation(&self.actuator_aggregation) {
+            return invalid(
+                "actuator_aggregation",
+                "softmax temperature must be finite and positive",
+            );
+        }
+
+        if !self.plastic_fire_threshold.is_finite() {
+            return invalid("plastic_fire_threshold", "must be finite");
+        }
+
+        if !self.actuator_fire_threshold.is_finite() {
+            return invalid("actuator_fire_threshold", "must be finite");
+        }
+
+        if !self.synaptic_type_ratio.is_finite() || self.synaptic_type_ratio < 0. {
+            return invalid("synaptic_type_ratio", "must be finite and non-negative");
+        }
+
+        if let Some(stdp) = &self.stdp {
+            let non_negative = |value: f32| value.is_finite() && value >= 0.;
+            if !non_negative(stdp.potentiation) || !non_negative(stdp.depression) {
+                return invalid("stdp", "amplitudes must be finite and non-negative");
+            }
+
+            let positive = |value: f32| value.is_finite() && value > 0.;
+            if !positive(stdp.potentiation_time_constant)
+                || !positive(stdp.depression_time_constant)
+            {
+                return invalid("stdp", "time constants must be finite and positive");
+            }
+
+            if stdp.window == 0 {
+                return invalid("stdp", "window must be at least 1");
+            }
+        }
+
+        if let Some(adaptive_threshold) = &self.adaptive_threshold {
+            if !adaptive_threshold.increment.is_finite() || adaptive_threshold.increment < 0. {
+                return invalid(
+                    "adaptive_threshold",
+                    "increment must be finite and non-negative",
+                );
+            }
+
+            if !adaptive_threshold.time_constant.is_finite()
+                || adaptive_threshold.time_constant <= 0.
+            {
+                return invalid(
+                    "adaptive_threshold",
+                    "time constant must be finite and positive",
+                );
+            }
+        }
+
+        if let Some(refractory) = &self.refractory {
+            let rise = refractory.relative_threshold_rise;
+            if !rise.is_finite() || rise < 0. {
+                return invalid(
+                    "refractory",
+                    "relative threshold rise must be finite and non-negative",
+                );
+            }
+        }
+
+        if let Some(leaky_integration) = &self.leaky_integration {
+            if !(0. ..=1.).contains(&leaky_integration.carry_over) {
+                return invalid("leaky_integration", "carry over must be between 0 and 1");
+            }
+        }
+
+        match self.synaptic_delay {
+            SynapticDelay::Fixed(0) => {
+                return invalid("synaptic_delay", "must be at least one cycle");
+            }
+            SynapticDelay::Fixed(_) => {}
+            SynapticDelay::Distance {
+                distance_per_cycle,
+                max_delay,
+            } => {
+                if !distance_per_cycle.is_finite() || distance_per_cycle <= 0. {
+                    return invalid(
+                        "synaptic_delay",
+                        "distance per cycle must be finite and positive",
+                    );
+                }
+
+                if max_delay == 0 {
+                    return invalid("synaptic_delay", "max delay must be at least one cycle");
+                }
+            }
+        }
+
+        if let Some(structural) = &self.structural_plasticity {
+            if structural.interval == 0 {
+                return invalid("structural_plasticity", "interval must be at least 1");
+            }
+
+            if !structural.prune_threshold.is_finite() || structural.prune_threshold < 0. {
+                return invalid(
+                    "structural_plasticity",
+                    "prune threshold must be finite and non-negative",
+                );
+            }
+
+            if structural.prune_after == 0 {
+                return invalid("structural_plasticity", "prune after must be at least 1");
+            }
+
+            if !(1..=64).contains(&structural.growth_coactivity) {
+                return invalid(
+                    "structural_plasticity",
+                    "growth coactivity must be between 1 and 64",
+                );
+            }
+
+            if !(0. ..=1.).contains(&structural.growth_probability) {
+                return invalid(
+                    "structural_plasticity",
+                    "growth probability must be between 0 and 1",
+                );
+            }
+
+            let max_weight = self.weight_regulation.weight_bound();
+            if !structural.growth_weight.is_finite()
+                || structural.growth_weight < 0.
+                || structural.growth_weight > max_weight
+            {
+                return invalid(
+                    "structural_plasticity",
+                    "growth weight must be finite, non-negative and at most max_weight",
+                );
+            }
+        }
+
+        let regulation = &self.weight_regulation;
+        if let Some(max_weight) = regulation.max_weight {
+            if !max_weight.is_finite() || max_weight < self.synapse_weight_ranges.1 {
+                return invalid(
+                    "max_weight",
+                    "must be finite and at least the upper bound of synapse_weight_ranges",
+                );
+            }
+        }
+
+        if let Some(normalization) = &regulation.normalization {
+            if !normalization.total_weight.is_finite() || normalization.total_weight <= 0. {
+                return invalid("normalization", "total weight must be finite and positive");
+            }
+
+            if normalization.interval == 0 {
+                return invalid("normalization", "interval must be at least 1");
+            }
+        }
+
+        if let Some(homeostasis) = &regulation.homeostasis {
+            if !(homeostasis.target_rate > 0. && homeostasis.target_rate <= 1.) {
+                return invalid("homeostasis", "target rate must be between 0 and 1");
+            }
+
+            if !(homeostasis.rate_time_constant.is_finite() && homeostasis.rate_time_constant >= 1.)
+            {
+                return invalid("homeostasis", "rate time constant must be at least 1");
+            }
+
+            if !homeostasis.scaling_rate.is_finite() || homeostasis.scaling_rate < 0. {
+                return invalid(
+                    "homeostasis",
+                    "scaling rate must be finite and non-negative",
+                );
+            }
+
+            if homeostasis.interval == 0 {
+                return invalid("homeostasis", "interval must be at least 1");
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            if self.threads == 0 {
+                return invalid("threads", "must be at least 1");
+            }
+        }
+
+        let (low, high) = self.synapse_weight_ranges;
+        if !low.is_finite() || !high.is_finite() {
+            return invalid("synapse_weight_ranges", "bounds must be finite");
+        }
+
+        if low < 0. {
+            return invalid("synapse_weight_ranges", "lower bound must be non-negative");
+        }
+
+        if low >= high {
+            return invalid(
+                "synapse_weight_ranges",
+                "lower bound must be less than the upper bound",
+            );
+        }
+
+        Ok(())
+    }
+}
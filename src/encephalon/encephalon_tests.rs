@@ -1,14 +1,26 @@
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use indexmap::IndexMap;
 
 use crate::actuator::custom_actuator::BasicActuator;
 use crate::actuator::Actuator;
-use crate::ecp_geometry::{EcpBox, EcpGeometry};
-use crate::encephalon::Encephalon;
-use crate::neuron::SynapseType;
-use crate::neuron::TxNeuronic;
-use crate::reflex::Reflex;
-use crate::sensor::custom_sensors::ConstantSensor;
+use crate::ecp_geometry::{EcpBox, EcpGeometry, SynapticDelay};
+use crate::encephalon::{Encephalon, EncephalonBuilder, EncephalonError, EncephalonLoader};
+use crate::fire_recorder::{
+    read_binary, write_binary, write_csv, FireEvent, FireFormat, FireRecorder, NeuronKind,
+};
+use crate::learning_rule::{LearningInput, Stdp};
+use crate::neuron::{
+    AdaptiveThreshold, Aggregation, ChargeCycle, Inhibition, LeakyIntegration, MeasureAggregation,
+    NeuronId, Refractory, SynapseType,
+};
+use crate::observer::EncephalonObserver;
+use crate::reflex::{Reflex, ReflexSource, ReflexTarget};
+use crate::sensor::custom_sensors::{BasicSensor, ConstantSensor};
 use crate::sensor::Sensor;
+use crate::structural_plasticity::StructuralPlasticity;
+use crate::synapse_snapshot::{SynapseDiff, SynapseKind};
+use crate::weight_regulation::{Homeostasis, Normalization, NormalizationDirection};
 
 fn weight_modifier(target_measure: f32, weight_measure: f32) -> f32 {
     let x = (target_measure - weight_measure).abs();
@@ -16,25 +28,25 @@ fn weight_modifier(target_measure: f32, weight_measure: f32) -> f32 {
 
     let numerator = (-15. * x).exp() - (-1.5_f32).exp();
 
-    return numerator / denominator;
+    numerator / denominator
 }
 
 #[test]
 fn basic_encephalon_test() {
     let sensor_names = ["1", "2", "3", "4"];
 
-    let mut sensors: Vec<Rc<dyn Sensor>> = Vec::new();
+    let mut sensors: Vec<Arc<dyn Sensor>> = Vec::new();
 
     for name in &sensor_names {
-        sensors.push(Rc::new(ConstantSensor::new((*name).into(), 0.5)));
+        sensors.push(Arc::new(ConstantSensor::new((*name).into(), 0.5)));
     }
 
     let actuator_names = ["act1", "act2", "act3"];
 
-    let mut actuators: Vec<Rc<dyn Actuator>> = Vec::new();
+    let mut actuators: Vec<Arc<dyn Actuator>> = Vec::new();
 
     for name in &actuator_names {
-        actuators.push(Rc::new(BasicActuator::new((*name).into())));
+        actuators.push(Arc::new(BasicActuator::new((*name).into())));
     }
 
     let reflexes = vec![
@@ -44,7 +56,7 @@ fn basic_encephalon_test() {
         Reflex::new("3".into(), "act3".into(), SynapseType::Inhibitory, 20.),
     ];
 
-    let ecp_g = Box::new(EcpBox::new(6_u32.pow(3) as u32, 3, 4, 26));
+    let ecp_g = Box::new(EcpBox::new(6_u32.pow(3), 3, 4, 26));
 
     let mut ecp = Encephalon::new(
         ecp_g,
@@ -60,38 +72,32 @@ fn basic_encephalon_test() {
 
     // Check neurons are all making the proper number of connections
     for plastic_neuron in ecp.plastic_neurons.values() {
-        assert_eq!(26, plastic_neuron.get_plastic_synapses().len());
+        assert_eq!(26, ecp.neurons.synapses(*plastic_neuron).0.len());
     }
 
     for sensory_neuron in ecp.sensory_neurons.values() {
-        assert_eq!(27, sensory_neuron.get_plastic_synapses().len());
+        assert_eq!(27, ecp.neurons.synapses(*sensory_neuron).0.len());
     }
 
     // Check static synapses are made
     assert_eq!(
-        ecp.sensory_interfaces
-            .get("1".into())
-            .unwrap()
-            .sensory_neuron
-            .get_static_synapses()
+        ecp.neurons
+            .synapses(ecp.sensory_interfaces.get("1").unwrap().sensory_neuron)
+            .1
             .len(),
         2
     );
     assert_eq!(
-        ecp.sensory_interfaces
-            .get("2".into())
-            .unwrap()
-            .sensory_neuron
-            .get_static_synapses()
+        ecp.neurons
+            .synapses(ecp.sensory_interfaces.get("2").unwrap().sensory_neuron)
+            .1
             .len(),
         1
     );
     assert_eq!(
-        ecp.sensory_interfaces
-            .get("3".into())
-            .unwrap()
-            .sensory_neuron
-            .get_static_synapses()
+        ecp.neurons
+            .synapses(ecp.sensory_interfaces.get("3").unwrap().sensory_neuron)
+            .1
             .len(),
         1
     );
@@ -100,3 +106,1963 @@ fn basic_encephalon_test() {
         ecp.run_cycle();
     }
 }
+
+fn save_test_sensors() -> Vec<Arc<dyn Sensor>> {
+    vec![
+        Arc::new(ConstantSensor::new("1".into(), 0.5)),
+        Arc::new(ConstantSensor::new("2".into(), 0.3)),
+        Arc::new(ConstantSensor::new("3 and more".into(), 0.9)),
+        Arc::new(ConstantSensor::new("4".into(), 0.1)),
+    ]
+}
+
+fn save_test_actuators() -> Vec<Arc<BasicActuator>> {
+    vec![
+        Arc::new(BasicActuator::new("act1".into())),
+        Arc::new(BasicActuator::new("act2".into())),
+        Arc::new(BasicActuator::new("act3".into())),
+    ]
+}
+
+fn as_dyn_actuators(actuators: &[Arc<BasicActuator>]) -> Vec<Arc<dyn Actuator>> {
+    actuators
+        .iter()
+        .map(|actuator| Arc::clone(actuator) as Arc<dyn Actuator>)
+        .collect()
+}
+
+#[test]
+fn test_save_and_load() {
+    let original_actuators = save_test_actuators();
+
+    let reflexes = vec![
+        Reflex::new("1".into(), "act1".into(), SynapseType::Excitatory, 20.),
+        Reflex::new("2".into(), "act1".into(), SynapseType::Inhibitory, 20.),
+        Reflex::new(
+            "3 and more".into(),
+            "act3".into(),
+            SynapseType::Excitatory,
+            20.,
+        ),
+    ];
+
+    let mut ecp = Encephalon::new(
+        Box::new(EcpBox::new(64, 3, 4, 26)),
+        save_test_sensors(),
+        as_dyn_actuators(&original_actuators),
+        reflexes,
+        10,
+        weight_modifier,
+        2.,
+        10.,
+        (2., 5.),
+    );
+
+    // Train for a while so the plastic weights move away from their initial values
+    for _ in 0..50 {
+        ecp.run_cycle();
+    }
+
+    let path = std::env::temp_dir().join(format!("aesir_save_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+
+    let loaded_actuators = save_test_actuators();
+    let mut loaded = Encephalon::load::<EcpBox, _>(
+        &path,
+        save_test_sensors(),
+        as_dyn_actuators(&loaded_actuators),
+        weight_modifier,
+    )
+    .unwrap();
+
+    // Saving the loaded encephalon gives back exactly the same file
+    let resaved_path =
+        std::env::temp_dir().join(format!("aesir_resave_test_{}.ecp", std::process::id()));
+    loaded.save(&resaved_path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        std::fs::read_to_string(&resaved_path).unwrap()
+    );
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&resaved_path).unwrap();
+
+    for _ in 0..50 {
+        ecp.run_static_cycle();
+        loaded.run_static_cycle();
+
+        for (original, loaded) in original_actuators.iter().zip(loaded_actuators.iter()) {
+            assert_eq!(
+                original.get_control_value().to_bits(),
+                loaded.get_control_value().to_bits()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_save_and_load_learning_state() {
+    let build = |actuators: &[Arc<BasicActuator>]| {
+        EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(actuators))
+            .stdp(Stdp::default())
+            .max_weight(8.)
+            .normalization(Normalization::new(
+                NormalizationDirection::Incoming,
+                60.,
+                10,
+            ))
+            .homeostasis(Homeostasis::new(0.2, 20., 0.1, 5))
+            .adaptive_threshold(AdaptiveThreshold::new(2., 5.))
+            .refractory(Refractory::new(1, 2, 3.))
+            .leaky_integration(LeakyIntegration::new(0.5))
+            .plastic_aggregation(Aggregation::new(
+                MeasureAggregation::WeightedMedian,
+                Inhibition::Shunting,
+            ))
+            .actuator_aggregation(Aggregation::new(
+                MeasureAggregation::Softmax { temperature: 2. },
+                Inhibition::Subtractive,
+            ))
+            .synaptic_delay(SynapticDelay::Distance {
+                distance_per_cycle: 1.5,
+                max_delay: 3,
+            })
+            .structural_plasticity(StructuralPlasticity::new(7, 1., 2, 3, 1., 2.))
+            .seed(7)
+            .build()
+            .unwrap()
+    };
+
+    let original_actuators = save_test_actuators();
+    let mut ecp = build(&original_actuators);
+
+    for _ in 0..30 {
+        ecp.run_cycle();
+    }
+
+    let path = std::env::temp_dir().join(format!("aesir_stdp_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+
+    let loaded_actuators = save_test_actuators();
+    let mut loaded = Encephalon::load::<EcpBox, _>(
+        &path,
+        save_test_sensors(),
+        as_dyn_actuators(&loaded_actuators),
+        weight_modifier,
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.neurons.stdp(), Some(&Stdp::default()));
+    assert_eq!(
+        loaded.neurons.weight_regulation(),
+        ecp.neurons.weight_regulation()
+    );
+    assert_eq!(
+        loaded.neurons.adaptive_threshold(),
+        ecp.neurons.adaptive_threshold()
+    );
+    assert_eq!(loaded.neurons.refractory(), ecp.neurons.refractory());
+    assert_eq!(
+        loaded.neurons.leaky_integration(),
+        ecp.neurons.leaky_integration()
+    );
+    assert_eq!(loaded.synaptic_delay, ecp.synaptic_delay);
+    assert_eq!(loaded.structural_plasticity(), ecp.structural_plasticity());
+    assert_eq!(loaded.structural_changes(), ecp.structural_changes());
+    for loc in ecp.plastic_neurons.keys() {
+        assert_eq!(
+            loaded.plastic_fire_threshold(loc),
+            ecp.plastic_fire_threshold(loc)
+        );
+    }
+    for (neurons, loaded_neurons) in [
+        (&ecp.plastic_neurons, &loaded.plastic_neurons),
+        (&ecp.actuator_neurons, &loaded.actuator_neurons),
+    ] {
+        for (loc, &neuron) in neurons.iter() {
+            let loaded_neuron = *loaded_neurons.get(loc).unwrap();
+            assert_eq!(
+                loaded.neurons.aggregation(loaded_neuron),
+                ecp.neurons.aggregation(neuron)
+            );
+        }
+    }
+
+    // Fire histories and firing rates are restored, so learning carries on exactly as before
+    for _ in 0..30 {
+        ecp.run_cycle();
+        loaded.run_cycle();
+
+        for (original, loaded) in original_actuators.iter().zip(loaded_actuators.iter()) {
+            assert_eq!(
+                original.get_control_value().to_bits(),
+                loaded.get_control_value().to_bits()
+            );
+        }
+    }
+
+    for (loc, &neuron) in ecp.sensory_neurons.iter() {
+        let loaded_neuron = *loaded.sensory_neurons.get(loc).unwrap();
+        assert_eq!(
+            ecp.neurons.synapses(neuron).0.weights,
+            loaded.neurons.synapses(loaded_neuron).0.weights
+        );
+        assert_eq!(
+            ecp.neurons.synapses(neuron).0.delays,
+            loaded.neurons.synapses(loaded_neuron).0.delays
+        );
+    }
+    assert_eq!(loaded.structural_changes(), ecp.structural_changes());
+    assert_eq!(loaded.synapse_count(), ecp.synapse_count());
+}
+
+#[test]
+fn test_structural_plasticity() {
+    let build = |structural_plasticity: Option<StructuralPlasticity>| {
+        let mut builder = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(&save_test_actuators()))
+            .learning_rule(|_: &LearningInput| -1.)
+            .seed(5);
+
+        if let Some(structural_plasticity) = structural_plasticity {
+            builder = builder.structural_plasticity(structural_plasticity);
+        }
+
+        builder.build().unwrap()
+    };
+
+    // Every synapse that takes part in a back to back firing weakens until it's pruned,
+    // while those between neurons that keep firing in step grow back
+    let mut fixed = build(None);
+    let mut restructured = build(Some(StructuralPlasticity::new(5, 1., 2, 1, 1., 3.)));
+    let initial_count = fixed.synapse_count();
+    assert_eq!(restructured.synapse_count(), initial_count);
+
+    for _ in 0..100 {
+        fixed.run_cycle();
+        restructured.run_cycle();
+    }
+
+    let changes = restructured.structural_changes();
+    assert!(changes.pruned > 0);
+    assert!(changes.grown > 0);
+    assert_eq!(
+        restructured.synapse_count() as u64 + changes.pruned,
+        initial_count as u64 + changes.grown
+    );
+
+    assert_eq!(fixed.synapse_count(), initial_count);
+    assert_eq!(fixed.structural_changes().pruned, 0);
+
+    // Synapses are only ever grown between neurons that aren't connected yet
+    for &neuron in restructured
+        .plastic_neurons
+        .values()
+        .chain(restructured.sensory_neurons.values())
+    {
+        let targets = &restructured.neurons.synapses(neuron).0.targets;
+        let mut unique = targets.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), targets.len());
+    }
+}
+
+#[test]
+fn test_distance_delays() {
+    let actuators = save_test_actuators();
+    let ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&actuators))
+        .reflex(Reflex::new("1".into(), "act1".into(), SynapseType::Excitatory, 5.).with_delay(4))
+        .synaptic_delay(SynapticDelay::Distance {
+            distance_per_cycle: 1.5,
+            max_delay: 2,
+        })
+        .seed(3)
+        .build()
+        .unwrap();
+
+    // Neighbours less than 1.5 apart get a delay of 1, and the rest the max of 2
+    let geometry = &ecp.ecp_geometry;
+    let mut seen = [false; 2];
+    for (loc, &neuron) in ecp.plastic_neurons.iter() {
+        let (plastic_locs, actuator_locs) = geometry.get_nearby_rx_neurons(loc);
+        let delays = &ecp.neurons.synapses(neuron).0.delays;
+
+        for (target_loc, &delay) in plastic_locs.iter().chain(&actuator_locs).zip(delays) {
+            let expected = if geometry.distance(loc, target_loc) < 1.5 {
+                1
+            } else {
+                2
+            };
+            assert_eq!(delay, expected);
+            seen[delay as usize - 1] = true;
+        }
+    }
+    assert_eq!(seen, [true, true]);
+
+    let sensor = ecp.sensory_interfaces.get("1").unwrap().sensory_neuron;
+    assert_eq!(ecp.neurons.synapses(sensor).1.delays, vec![4]);
+
+    let actuator = ecp.actuator_interfaces.get("act1").unwrap().actuator_neuron;
+    assert_eq!(ecp.neurons.internal_charge(actuator).max_delay(), 4);
+}
+
+#[test]
+fn test_add_and_remove_sensors_and_actuators() {
+    let actuators = save_test_actuators();
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&actuators))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act3".into(),
+            SynapseType::Excitatory,
+            1000.,
+        ))
+        .actuator_fire_threshold(4.)
+        .seed(11)
+        .build()
+        .unwrap();
+    let initial_count = ecp.synapse_count();
+
+    // Once the laid out spots are taken a sensor goes elsewhere on the sensory plane,
+    // still connected to plastic neurons in the cube
+    let full_sensor: Arc<dyn Sensor> = Arc::new(ConstantSensor::new("6".into(), 0.7));
+    assert_eq!(ecp.add_sensor(full_sensor), Ok(vec![2, -1, 0]));
+    let sensor = ecp.sensory_interfaces["6"].sensory_neuron;
+    let nearby = ecp.ecp_geometry.get_nearby_rx_neurons(&vec![2, -1, 0]).0;
+    assert!(nearby
+        .iter()
+        .all(|loc| ecp.plastic_neurons.contains_key(loc)));
+    assert_eq!(ecp.neurons.synapses(sensor).0.len(), nearby.len());
+    ecp.remove_sensor("6").unwrap();
+    assert_eq!(ecp.synapse_count(), initial_count);
+
+    let extra_sensor: Arc<dyn Sensor> = Arc::new(ConstantSensor::new("5".into(), 0.7));
+
+    let sensor_loc = ecp
+        .sensory_neurons
+        .iter()
+        .find(|(_, &neuron)| ecp.sensory_interfaces["2"].sensory_neuron == neuron)
+        .map(|(loc, _)| loc.clone())
+        .unwrap();
+    let removed_count = {
+        let (plastic_synapses, static_synapses) = ecp
+            .neurons
+            .synapses(ecp.sensory_interfaces["2"].sensory_neuron);
+        plastic_synapses.len() + static_synapses.len()
+    };
+    assert_eq!(ecp.remove_sensor("2").unwrap().get_name(), "2");
+    assert_eq!(ecp.synapse_count(), initial_count - removed_count);
+    assert!(matches!(
+        ecp.remove_sensor("2"),
+        Err(EncephalonError::UnknownSensor(_))
+    ));
+
+    assert_eq!(ecp.add_sensor(extra_sensor), Ok(sensor_loc.clone()));
+    let sensor = ecp.sensory_interfaces["5"].sensory_neuron;
+    assert_eq!(ecp.sensory_neurons[&sensor_loc], sensor);
+    assert_eq!(
+        ecp.neurons.synapses(sensor).0.len(),
+        ecp.ecp_geometry.get_nearby_rx_neurons(&sensor_loc).0.len()
+    );
+
+    // A new actuator takes the next spot above the plastic neurons
+    let extra_actuator = Arc::new(BasicActuator::new("act4".into()));
+    assert_eq!(
+        ecp.add_actuator(Arc::clone(&extra_actuator) as Arc<dyn Actuator>),
+        Ok(vec![3, 4, 0])
+    );
+    assert_eq!(
+        ecp.add_actuator(Arc::clone(&extra_actuator) as Arc<dyn Actuator>),
+        Err(EncephalonError::DuplicateActuatorName("act4".into()))
+    );
+    let actuator = ecp.actuator_interfaces["act4"].actuator_neuron;
+    assert_eq!(ecp.neurons.fire_threshold(actuator), 4.);
+
+    let targets_of = |ecp: &Encephalon, rx: u32| {
+        ecp.sensory_neurons
+            .values()
+            .chain(ecp.plastic_neurons.values())
+            .map(|&neuron| {
+                let (plastic_synapses, static_synapses) = ecp.neurons.synapses(neuron);
+                plastic_synapses
+                    .targets
+                    .iter()
+                    .chain(&static_synapses.targets)
+                    .filter(|&&target| target == rx)
+                    .count()
+            })
+            .sum::<usize>()
+    };
+    let incoming = targets_of(&ecp, ecp.neurons.rx_index(actuator));
+    assert_eq!(
+        incoming,
+        ecp.ecp_geometry
+            .get_nearby_plastic_neurons(&[3, 4, 0])
+            .len()
+    );
+
+    // Removing an actuator detaches every synapse targeting it
+    let act1 = ecp.actuator_interfaces["act1"].actuator_neuron;
+    let act1_incoming = targets_of(&ecp, ecp.neurons.rx_index(act1));
+    let count = ecp.synapse_count();
+    ecp.remove_actuator("act1").unwrap();
+    assert_eq!(ecp.synapse_count(), count - act1_incoming);
+    assert_eq!(ecp.actuator_neurons.len(), 3);
+
+    // The reflex to act3 keeps working, and the new actuator is driven by its synapses
+    for _ in 0..10 {
+        ecp.run_cycle();
+    }
+    assert!((actuators[2].get_control_value() - 0.5).abs() < 0.05);
+    assert!(extra_actuator.get_control_value() > 0.);
+
+    // And the rewired encephalon can be saved and restored
+    let path = std::env::temp_dir().join(format!("aesir_rewired_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+
+    let mut sensors = save_test_sensors();
+    sensors.remove(1);
+    sensors.push(Arc::new(ConstantSensor::new("5".into(), 0.7)));
+    let mut loaded_actuators = save_test_actuators();
+    loaded_actuators.remove(0);
+    loaded_actuators.push(Arc::new(BasicActuator::new("act4".into())));
+    let mut loaded = Encephalon::load::<EcpBox, _>(
+        &path,
+        sensors,
+        as_dyn_actuators(&loaded_actuators),
+        weight_modifier,
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // act1 was at the spot freed up
+    assert_eq!(loaded.synapse_count(), ecp.synapse_count());
+    assert_eq!(
+        loaded.add_actuator(Arc::new(BasicActuator::new("act5".into()))),
+        Ok(vec![2, 4, 0])
+    );
+    let act5 = loaded.actuator_interfaces["act5"].actuator_neuron;
+    assert_eq!(loaded.neurons.fire_threshold(act5), 4.);
+}
+
+#[test]
+fn test_save_and_load_after_removing() {
+    // Sensors with the same measure charge the same bins, so the order their impulses
+    // are summed in shows in the charges
+    let sensors = || {
+        ["1", "2", "3", "4"]
+            .iter()
+            .map(|&name| Arc::new(ConstantSensor::new(name.into(), 0.5)) as Arc<dyn Sensor>)
+            .collect::<Vec<Arc<dyn Sensor>>>()
+    };
+    let build = |actuators: &[Arc<BasicActuator>]| {
+        EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+            .sensors(sensors())
+            .actuators(as_dyn_actuators(actuators))
+            .weight_modifier(weight_modifier)
+            .seed(5)
+            .build()
+            .unwrap()
+    };
+
+    let original_actuators = save_test_actuators();
+    let mut ecp = build(&original_actuators);
+
+    for _ in 0..20 {
+        ecp.run_cycle();
+    }
+
+    // Neurons in the middle of the arena are removed, so the ones after them move
+    ecp.remove_sensor("3").unwrap();
+    ecp.remove_actuator("act2").unwrap();
+
+    for _ in 0..20 {
+        ecp.run_cycle();
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "aesir_removed_save_test_{}.ecp",
+        std::process::id()
+    ));
+    ecp.save(&path).unwrap();
+
+    let mut loaded_sensors = sensors();
+    loaded_sensors.retain(|sensor| sensor.get_name() != "3");
+    let mut loaded_actuators = save_test_actuators();
+    loaded_actuators.remove(1);
+    let mut loaded = EncephalonLoader::new(loaded_sensors, as_dyn_actuators(&loaded_actuators))
+        .weight_modifier(weight_modifier)
+        .load::<EcpBox, _>(&path)
+        .unwrap();
+
+    let original_actuators = [&original_actuators[0], &original_actuators[2]];
+    for _ in 0..50 {
+        ecp.run_cycle();
+        loaded.run_cycle();
+
+        for (original, loaded) in original_actuators.iter().zip(loaded_actuators.iter()) {
+            assert_eq!(
+                original.get_control_value().to_bits(),
+                loaded.get_control_value().to_bits()
+            );
+        }
+    }
+
+    // Both go on charging, weighting and firing exactly alike
+    let resaved_path = std::env::temp_dir().join(format!(
+        "aesir_removed_resave_test_{}.ecp",
+        std::process::id()
+    ));
+    ecp.save(&path).unwrap();
+    loaded.save(&resaved_path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        std::fs::read_to_string(&resaved_path).unwrap()
+    );
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&resaved_path).unwrap();
+}
+
+#[test]
+fn test_reflex_management() {
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            5.,
+        ))
+        .reflex(Reflex::new("1".into(), "act2".into(), SynapseType::Inhibitory, 6.).with_delay(2))
+        .reflex(Reflex::new(
+            "2".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            7.,
+        ))
+        .seed(13)
+        .build()
+        .unwrap();
+
+    let ids = ecp.reflexes().map(|(id, _)| id).collect::<Vec<_>>();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(
+        ecp.reflex(ids[1]),
+        Some(Reflex::new("1".into(), "act2".into(), SynapseType::Inhibitory, 6.).with_delay(2))
+    );
+
+    let sensor = ecp.sensory_interfaces["1"].sensory_neuron;
+    ecp.update_reflex_weight(ids[1], 1.5).unwrap();
+    assert_eq!(ecp.neurons.synapses(sensor).1.weights, vec![5., 1.5]);
+    assert_eq!(ecp.reflex(ids[1]).unwrap().weight, 1.5);
+    assert!(matches!(
+        ecp.update_reflex_weight(ids[1], -1.),
+        Err(EncephalonError::InvalidParameter {
+            parameter: "weight",
+            ..
+        })
+    ));
+
+    // The other reflexes of the sensor keep their synapses
+    let removed = ecp.remove_reflex(ids[0]).unwrap();
+    assert_eq!(*removed.target(), ReflexTarget::Actuator("act1".into()));
+    assert_eq!(ecp.neurons.synapses(sensor).1.weights, vec![1.5]);
+    assert_eq!(ecp.neurons.synapses(sensor).1.delays, vec![2]);
+    assert_eq!(
+        ecp.remove_reflex(ids[0]),
+        Err(EncephalonError::UnknownReflex(ids[0]))
+    );
+
+    let id = ecp
+        .add_reflex(&Reflex::new(
+            "3 and more".into(),
+            "act3".into(),
+            SynapseType::Excitatory,
+            4.,
+        ))
+        .unwrap();
+    assert!(!ids.contains(&id));
+
+    // Reflexes go along with their sensor or actuator
+    ecp.remove_actuator("act1").unwrap();
+    assert_eq!(
+        ecp.reflexes().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![ids[1], id]
+    );
+
+    let path = std::env::temp_dir().join(format!("aesir_reflex_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+
+    let mut actuators = save_test_actuators();
+    actuators.remove(0);
+    let mut loaded = Encephalon::load::<EcpBox, _>(
+        &path,
+        save_test_sensors(),
+        as_dyn_actuators(&actuators),
+        weight_modifier,
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        loaded.reflexes().collect::<Vec<_>>(),
+        ecp.reflexes().collect::<Vec<_>>()
+    );
+    loaded.update_reflex_weight(id, 2.).unwrap();
+    let sensor = loaded.sensory_interfaces["3 and more"].sensory_neuron;
+    assert_eq!(loaded.neurons.synapses(sensor).1.weights, vec![2.]);
+    assert!(loaded
+        .add_reflex(&Reflex::new(
+            "4".into(),
+            "act2".into(),
+            SynapseType::Excitatory,
+            1.
+        ))
+        .is_ok_and(|new_id| new_id > id));
+}
+
+#[test]
+fn test_reflex_weights_follow_learning() {
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            20.,
+        ))
+        .actuator_fire_threshold(4.)
+        .seed(17)
+        .build()
+        .unwrap();
+    let id = ecp.reflexes().next().unwrap().0;
+
+    for _ in 0..50 {
+        ecp.run_cycle();
+    }
+
+    // Learning changes the weights of reflexes just like those of any other synapse
+    let weight = ecp.reflex(id).unwrap().weight;
+    assert_ne!(weight, 20.);
+
+    let snapshot = ecp.synapse_snapshot();
+    let static_weights = snapshot
+        .synapses
+        .iter()
+        .filter(|synapse| synapse.kind == SynapseKind::Static)
+        .map(|synapse| synapse.weight)
+        .collect::<Vec<_>>();
+    assert_eq!(static_weights, vec![weight]);
+    assert_eq!(
+        ecp.reflexes()
+            .map(|(_, reflex)| reflex.weight)
+            .collect::<Vec<_>>(),
+        vec![weight]
+    );
+    assert_eq!(ecp.remove_reflex(id).unwrap().weight, weight);
+}
+
+#[test]
+fn test_reflex_arc() {
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .seed(17)
+        .build()
+        .unwrap();
+
+    let interneuron = ecp.plastic_neurons.keys().next().unwrap().clone();
+    let arc = vec![
+        Reflex::between(
+            ReflexSource::Sensor("3 and more".into()),
+            ReflexTarget::Plastic(interneuron.clone()),
+            SynapseType::Excitatory,
+            1000.,
+        ),
+        Reflex::between(
+            ReflexSource::Plastic(interneuron.clone()),
+            ReflexTarget::Actuator("act2".into()),
+            SynapseType::Excitatory,
+            1000.,
+        )
+        .with_delay(2),
+    ];
+
+    // Hops that don't meet, or end at neurons that don't exist, add nothing
+    let broken = vec![arc[1].clone(), arc[0].clone()];
+    assert!(matches!(
+        ecp.add_reflex_arc(&broken),
+        Err(EncephalonError::InvalidParameter {
+            parameter: "reflexes",
+            ..
+        })
+    ));
+    let missing = vec![
+        Reflex::between(
+            ReflexSource::Sensor("3 and more".into()),
+            ReflexTarget::Plastic(vec![-1, -1, -1]),
+            SynapseType::Excitatory,
+            1000.,
+        ),
+        Reflex::between(
+            ReflexSource::Plastic(vec![-1, -1, -1]),
+            ReflexTarget::Actuator("act2".into()),
+            SynapseType::Excitatory,
+            1000.,
+        ),
+    ];
+    assert_eq!(
+        ecp.add_reflex_arc(&missing),
+        Err(EncephalonError::UnknownPlasticNeuron(vec![-1, -1, -1]))
+    );
+    assert_eq!(ecp.reflexes().count(), 0);
+
+    let ids = ecp.add_reflex_arc(&arc).unwrap();
+    assert_eq!(ids.len(), 2);
+
+    let sensor = ecp.sensory_interfaces["3 and more"].sensory_neuron;
+    let plastic = ecp.plastic_neurons[&interneuron];
+    let actuator = ecp.actuator_interfaces["act2"].actuator_neuron;
+    assert_eq!(
+        ecp.neurons.synapses(sensor).1.targets,
+        vec![ecp.neurons.rx_index(plastic)]
+    );
+    assert_eq!(
+        ecp.neurons.synapses(plastic).1.targets,
+        vec![ecp.neurons.rx_index(actuator)]
+    );
+    assert_eq!(ecp.neurons.synapses(plastic).1.delays, vec![2]);
+
+    let path = std::env::temp_dir().join(format!("aesir_arc_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+    let loaded = Encephalon::load::<EcpBox, _>(
+        &path,
+        save_test_sensors(),
+        as_dyn_actuators(&save_test_actuators()),
+        weight_modifier,
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        loaded.reflexes().collect::<Vec<_>>(),
+        ecp.reflexes().collect::<Vec<_>>()
+    );
+    let loaded_plastic = loaded.plastic_neurons[&interneuron];
+    assert_eq!(loaded.neurons.synapses(loaded_plastic).1.delays, vec![2]);
+
+    // The sensor drives the actuator through the interneuron
+    for _ in 0..8 {
+        ecp.run_cycle();
+    }
+    assert_eq!(ecp.neurons.fire_tracker(plastic).activity & 0b111, 0b111);
+    assert_eq!(ecp.neurons.fire_tracker(actuator).activity & 0b111, 0b111);
+
+    ecp.update_reflex_weight(ids[1], 500.).unwrap();
+    assert_eq!(ecp.neurons.synapses(plastic).1.weights, vec![500.]);
+
+    // Removing the sensor takes the first hop along, but not the second
+    ecp.remove_sensor("3 and more").unwrap();
+    assert_eq!(
+        ecp.reflexes().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![ids[1]]
+    );
+    assert_eq!(ecp.remove_reflex(ids[1]).unwrap().delay, 2);
+    assert_eq!(ecp.neurons.synapses(plastic).1.len(), 0);
+}
+
+#[test]
+fn test_load_rejects_unknown_version() {
+    let path = std::env::temp_dir().join(format!("aesir_version_test_{}.ecp", std::process::id()));
+    std::fs::write(&path, "aesir-encephalon 9999\ngeometry 27 1 1 26\n").unwrap();
+
+    let result = Encephalon::load::<EcpBox, _>(
+        &path,
+        save_test_sensors(),
+        as_dyn_actuators(&save_test_actuators()),
+        weight_modifier,
+    );
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        result.err().unwrap().kind(),
+        std::io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_load_rejects_corrupted_counts() {
+    let ecp = EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .seed(3)
+        .build()
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("aesir_count_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+
+    for (count, corrupted) in [
+        ("\nplastic 27\n", "\nplastic 18446744073709551615\n"),
+        ("\nplastic 27\n", "\nplastic 26\n"),
+        ("\nactuator 3\n", "\nactuator 4000000000\n"),
+        ("\nsensory 4\n", "\nsensory 5\n"),
+    ] {
+        assert!(saved.contains(count));
+        std::fs::write(&path, saved.replace(count, corrupted)).unwrap();
+
+        let result = Encephalon::load::<EcpBox, _>(
+            &path,
+            save_test_sensors(),
+            as_dyn_actuators(&save_test_actuators()),
+            weight_modifier,
+        );
+        assert_eq!(
+            result.err().unwrap().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_builder_overrides() {
+    let ecp = EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            20.,
+        ))
+        .fire_threshold(8.)
+        .actuator_fire_threshold(4.)
+        .actuator_charge_bins(5)
+        .build()
+        .unwrap();
+
+    for plastic_neuron in ecp.plastic_neurons.values() {
+        assert_eq!(ecp.neurons.fire_threshold(*plastic_neuron), 8.);
+        assert_eq!(
+            ecp.neurons.internal_charge(*plastic_neuron).bins,
+            EncephalonBuilder::DEFAULT_CHARGE_BINS
+        );
+    }
+
+    for actuator_neuron in ecp.actuator_neurons.values() {
+        assert_eq!(ecp.neurons.fire_threshold(*actuator_neuron), 4.);
+        assert_eq!(ecp.neurons.internal_charge(*actuator_neuron).bins, 5);
+    }
+}
+
+#[test]
+fn test_builder_errors() {
+    let builder = || {
+        EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(&save_test_actuators()))
+    };
+
+    assert_eq!(
+        EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .build()
+            .err()
+            .unwrap(),
+        EncephalonError::ActuatorCountMismatch {
+            expected: 3,
+            found: 0
+        }
+    );
+
+    assert_eq!(
+        builder()
+            .sensor(Arc::new(ConstantSensor::new("5".into(), 0.5)))
+            .build()
+            .err()
+            .unwrap(),
+        EncephalonError::SensorCountMismatch {
+            expected: 4,
+            found: 5
+        }
+    );
+
+    // The sensory grid of a geometry that wasn't validated can outnumber the sensors
+    match EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 5, 26)))
+        .sensors(
+            (1..=5)
+                .map(|i| Arc::new(ConstantSensor::new(i.to_string(), 0.5)) as Arc<dyn Sensor>)
+                .collect(),
+        )
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .build()
+    {
+        Err(EncephalonError::InvalidGeometry { parameter, .. }) => {
+            assert_eq!(parameter, "num_sensory")
+        }
+        _ => panic!("Expected an invalid num_sensory"),
+    }
+
+    match builder().synapse_weight_ranges((5., 2.)).build() {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "synapse_weight_ranges")
+        }
+        _ => panic!("Expected an invalid synapse_weight_ranges"),
+    }
+
+    match builder()
+        .stdp(Stdp {
+            window: 0,
+            ..Stdp::default()
+        })
+        .build()
+    {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => assert_eq!(parameter, "stdp"),
+        _ => panic!("Expected an invalid stdp"),
+    }
+
+    match builder().max_weight(4.).build() {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "max_weight")
+        }
+        _ => panic!("Expected an invalid max_weight"),
+    }
+
+    match builder()
+        .homeostasis(Homeostasis::new(0., 10., 0.1, 1))
+        .build()
+    {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "homeostasis")
+        }
+        _ => panic!("Expected an invalid homeostasis"),
+    }
+
+    match builder()
+        .adaptive_threshold(AdaptiveThreshold::new(1., 0.))
+        .build()
+    {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "adaptive_threshold")
+        }
+        _ => panic!("Expected an invalid adaptive_threshold"),
+    }
+
+    match builder()
+        .leaky_integration(LeakyIntegration::new(1.5))
+        .build()
+    {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "leaky_integration")
+        }
+        _ => panic!("Expected an invalid leaky_integration"),
+    }
+
+    match builder()
+        .actuator_aggregation(Aggregation::new(
+            MeasureAggregation::Softmax { temperature: 0. },
+            Inhibition::Subtractive,
+        ))
+        .build()
+    {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "actuator_aggregation")
+        }
+        _ => panic!("Expected an invalid actuator_aggregation"),
+    }
+
+    match builder().synaptic_delay(SynapticDelay::Fixed(0)).build() {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "synaptic_delay")
+        }
+        _ => panic!("Expected an invalid synaptic_delay"),
+    }
+
+    match builder()
+        .max_weight(8.)
+        .structural_plasticity(StructuralPlasticity::new(10, 1., 2, 3, 0.5, 9.))
+        .build()
+    {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "structural_plasticity")
+        }
+        _ => panic!("Expected an invalid structural_plasticity"),
+    }
+
+    match builder().refractory(Refractory::new(1, 2, -1.)).build() {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "refractory")
+        }
+        _ => panic!("Expected an invalid refractory"),
+    }
+
+    match builder().plastic_charge_bins(0).build() {
+        Err(EncephalonError::InvalidParameter { parameter, .. }) => {
+            assert_eq!(parameter, "plastic_charge_bins")
+        }
+        _ => panic!("Expected an invalid plastic_charge_bins"),
+    }
+
+    let duplicate_actuators = vec![
+        Arc::new(BasicActuator::new("act1".into())) as Arc<dyn Actuator>,
+        Arc::new(BasicActuator::new("act1".into())),
+        Arc::new(BasicActuator::new("act2".into())),
+    ];
+    assert_eq!(
+        EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(duplicate_actuators)
+            .build()
+            .err()
+            .unwrap(),
+        EncephalonError::DuplicateActuatorName("act1".into())
+    );
+}
+
+#[test]
+fn test_unknown_reflex_endpoints() {
+    let builder = || {
+        EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(&save_test_actuators()))
+    };
+
+    assert_eq!(
+        builder()
+            .reflex(Reflex::new(
+                "missing".into(),
+                "act1".into(),
+                SynapseType::Excitatory,
+                20.
+            ))
+            .build()
+            .err()
+            .unwrap(),
+        EncephalonError::UnknownSensor("missing".into())
+    );
+
+    let mut ecp = builder().build().unwrap();
+
+    assert_eq!(
+        ecp.add_reflex(&Reflex::new(
+            "1".into(),
+            "missing".into(),
+            SynapseType::Excitatory,
+            20.
+        )),
+        Err(EncephalonError::UnknownActuator("missing".into()))
+    );
+
+    // A failed reflex sensor leaves the encephalon untouched
+    let result = ecp.try_add_reflex_sensor(
+        Arc::new(ConstantSensor::new("reflex".into(), 0.5)),
+        0,
+        vec![
+            Reflex::new("reflex".into(), "act1".into(), SynapseType::Excitatory, 20.),
+            Reflex::new("reflex".into(), "act9".into(), SynapseType::Excitatory, 20.),
+        ],
+        weight_modifier,
+    );
+    assert_eq!(result, Err(EncephalonError::UnknownActuator("act9".into())));
+    assert_eq!(ecp.sensory_neurons.len(), 4);
+    assert!(!ecp.sensory_interfaces.contains_key("reflex"));
+
+    let mut reflex = Reflex::new("reflex".into(), "act1".into(), SynapseType::Excitatory, 20.);
+    reflex.delay = 0;
+    let result = ecp.try_add_reflex_sensor(
+        Arc::new(ConstantSensor::new("reflex".into(), 0.5)),
+        0,
+        vec![reflex],
+        weight_modifier,
+    );
+    assert!(matches!(
+        result,
+        Err(EncephalonError::InvalidParameter {
+            parameter: "delay",
+            ..
+        })
+    ));
+    assert_eq!(ecp.sensory_neurons.len(), 4);
+    assert!(!ecp.sensory_interfaces.contains_key("reflex"));
+
+    ecp.try_add_reflex_sensor(
+        Arc::new(ConstantSensor::new("reflex".into(), 0.5)),
+        0,
+        vec![Reflex::new(
+            "reflex".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            20.,
+        )],
+        weight_modifier,
+    )
+    .unwrap();
+
+    assert_eq!(
+        ecp.try_add_reflex_sensor(
+            Arc::new(ConstantSensor::new("other".into(), 0.5)),
+            0,
+            Vec::new(),
+            weight_modifier,
+        ),
+        Err(EncephalonError::DuplicateSensorLocation(vec![0]))
+    );
+
+    assert!(Reflex::try_new("1".into(), "act1".into(), SynapseType::Excitatory, -1.).is_err());
+}
+
+#[test]
+fn test_legacy_constructors_dont_validate() {
+    // Only the fallible constructors reject these
+    let reflex = Reflex::new("1".into(), "act1".into(), SynapseType::Excitatory, -1.);
+    assert_eq!(reflex.weight, -1.);
+
+    let ecp_box = EcpBox::new(27, 0, 0, 26);
+    assert_eq!(ecp_box.get_num_actuator(), 0);
+    assert!(EcpBox::try_new(27, 0, 0, 26).is_err());
+
+    let actuators = save_test_actuators();
+    let mut ecp = Encephalon::new(
+        Box::new(EcpBox::new(27, 3, 4, 26)),
+        save_test_sensors(),
+        as_dyn_actuators(&actuators),
+        vec![reflex],
+        10,
+        weight_modifier,
+        2.,
+        10.,
+        (-1., 3.),
+    );
+
+    ecp.run_cycle();
+}
+
+#[test]
+fn test_seeded_encephalons_are_identical() {
+    let build = |seed: u64, actuators: &[Arc<BasicActuator>]| {
+        EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(actuators))
+            .reflex(Reflex::new(
+                "1".into(),
+                "act1".into(),
+                SynapseType::Excitatory,
+                20.,
+            ))
+            .synaptic_type_ratio(4.)
+            .seed(seed)
+            .build()
+            .unwrap()
+    };
+
+    let actuators_a = save_test_actuators();
+    let actuators_b = save_test_actuators();
+    let actuators_c = save_test_actuators();
+
+    let mut ecp_a = build(42, &actuators_a);
+    let mut ecp_b = build(42, &actuators_b);
+    let ecp_c = build(43, &actuators_c);
+
+    let synapse_table = |ecp: &Encephalon| {
+        ecp.plastic_neurons
+            .values()
+            .flat_map(|&neuron| {
+                let synapses = ecp.neurons.synapses(neuron).0;
+
+                synapses
+                    .weights
+                    .iter()
+                    .zip(synapses.types.iter())
+                    .map(|(weight, synapse_type)| {
+                        (
+                            weight.to_bits(),
+                            matches!(synapse_type, SynapseType::Excitatory),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(synapse_table(&ecp_a), synapse_table(&ecp_b));
+    assert_ne!(synapse_table(&ecp_a), synapse_table(&ecp_c));
+
+    for _ in 0..200 {
+        ecp_a.run_cycle();
+        ecp_b.run_cycle();
+
+        for (a, b) in actuators_a.iter().zip(actuators_b.iter()) {
+            assert_eq!(
+                a.get_control_value().to_bits(),
+                b.get_control_value().to_bits()
+            );
+        }
+    }
+
+    assert_eq!(synapse_table(&ecp_a), synapse_table(&ecp_b));
+}
+
+#[test]
+fn test_seeded_loads_are_identical() {
+    let actuators = save_test_actuators();
+    let ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&actuators))
+        .seed(42)
+        .build()
+        .unwrap();
+
+    let path =
+        std::env::temp_dir().join(format!("aesir_seeded_load_test_{}.ecp", std::process::id()));
+    ecp.save(&path).unwrap();
+
+    let load = |seed: u64| {
+        let mut loaded = EncephalonLoader::new(
+            save_test_sensors(),
+            as_dyn_actuators(&save_test_actuators()),
+        )
+        .weight_modifier(weight_modifier)
+        .seed(seed)
+        .load::<EcpBox, _>(&path)
+        .unwrap();
+
+        // The synapses to the new actuator are drawn from the restored rng
+        loaded
+            .add_actuator(Arc::new(BasicActuator::new("act4".into())))
+            .unwrap();
+        loaded.synapse_snapshot()
+    };
+
+    assert_eq!(load(7), load(7));
+    assert_ne!(load(7), load(8));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_encephalon_on_worker_thread() {
+    assert_send_sync::<Encephalon>();
+
+    let sensor = Arc::new(BasicSensor::new("1".into()));
+    let mut sensors = save_test_sensors();
+    sensors[0] = Arc::clone(&sensor) as Arc<dyn Sensor>;
+
+    let actuators = save_test_actuators();
+
+    let ecp = EncephalonBuilder::new(Box::new(EcpBox::new(27, 3, 4, 26)))
+        .sensors(sensors)
+        .actuators(as_dyn_actuators(&actuators))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            20.,
+        ))
+        .seed(7)
+        .build()
+        .unwrap();
+
+    // The encephalon is owned by the worker, while this thread feeds it sensor values
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    let worker = std::thread::spawn(move || {
+        let mut ecp = ecp;
+
+        for () in rx {
+            ecp.run_cycle();
+        }
+
+        ecp
+    });
+
+    for i in 0..20 {
+        sensor.set_measure((i % 10) as f32 / 10.);
+        tx.send(()).unwrap();
+    }
+    drop(tx);
+
+    let mut ecp = worker.join().unwrap();
+
+    // The actuator driven by the reflex fires with the sensor's measure
+    sensor.set_measure(0.55);
+    ecp.clear();
+    for _ in 0..3 {
+        ecp.run_static_cycle();
+    }
+    assert!(actuators[0].get_control_value() > 0.);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_matches_sequential() {
+    let build = |threads: usize, actuators: &[Arc<BasicActuator>]| {
+        EncephalonBuilder::new(Box::new(EcpBox::new(125, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(actuators))
+            .reflex(Reflex::new(
+                "1".into(),
+                "act1".into(),
+                SynapseType::Excitatory,
+                20.,
+            ))
+            .synaptic_type_ratio(4.)
+            .fire_threshold(6.)
+            // Everything plastic neurons are evaluated with, since that is split across
+            // threads as well
+            .adaptive_threshold(AdaptiveThreshold::new(0.5, 5.))
+            .refractory(Refractory::new(1, 2, 1.))
+            .leaky_integration(LeakyIntegration::new(0.5))
+            .seed(11)
+            .threads(threads)
+            .build()
+            .unwrap()
+    };
+
+    let sequential_actuators = save_test_actuators();
+    let parallel_actuators = save_test_actuators();
+
+    let mut sequential = build(1, &sequential_actuators);
+    let mut parallel = build(4, &parallel_actuators);
+    assert_eq!(parallel.threads(), 4);
+    sequential.set_cycle_stats(true);
+    parallel.set_cycle_stats(true);
+
+    for i in 0..200 {
+        // The worker threads are restarted, and the chunks are split differently
+        if i == 100 {
+            parallel.set_threads(3);
+        }
+
+        let stats = if i < 150 {
+            (sequential.run_cycle(), parallel.run_cycle())
+        } else {
+            (sequential.run_static_cycle(), parallel.run_static_cycle())
+        };
+        assert!(stats.0.is_some());
+        assert_eq!(stats.0, stats.1);
+
+        for (a, b) in sequential_actuators.iter().zip(parallel_actuators.iter()) {
+            assert_eq!(
+                a.get_control_value().to_bits(),
+                b.get_control_value().to_bits()
+            );
+        }
+    }
+
+    for (&a, &b) in sequential
+        .plastic_neurons
+        .values()
+        .zip(parallel.plastic_neurons.values())
+    {
+        for (a, b) in sequential
+            .neurons
+            .synapses(a)
+            .0
+            .weights
+            .iter()
+            .zip(parallel.neurons.synapses(b).0.weights.iter())
+        {
+            assert_eq!(a.to_bits(), b.to_bits());
+        }
+    }
+}
+
+#[test]
+fn test_homeostasis_prevents_saturation() {
+    // Strengthens every synapse that takes part in a back to back firing
+    let build = |homeostasis: Option<Homeostasis>| {
+        let mut builder = EncephalonBuilder::new(Box::new(EcpBox::new(125, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(&save_test_actuators()))
+            .learning_rule(|_: &LearningInput| 0.5)
+            .seed(3);
+
+        if let Some(homeostasis) = homeostasis {
+            builder = builder.homeostasis(homeostasis);
+        }
+
+        builder.build().unwrap()
+    };
+
+    let firing_fraction = |ecp: &mut Encephalon| {
+        let mut fires = 0;
+        for _ in 0..100 {
+            ecp.run_cycle();
+
+            fires += ecp
+                .plastic_neurons
+                .values()
+                .filter(|&&neuron| {
+                    let receipts = ecp.neurons.fire_tracker(neuron).receipts;
+                    match ecp.cycle {
+                        ChargeCycle::Even => receipts.0.fired(),
+                        ChargeCycle::Odd => receipts.1.fired(),
+                    }
+                })
+                .count();
+        }
+
+        fires as f32 / (100 * ecp.plastic_neurons.len()) as f32
+    };
+
+    let mut unregulated = build(None);
+    let mut regulated = build(Some(Homeostasis::new(0.1, 20., 0.5, 1)));
+
+    for _ in 0..300 {
+        unregulated.run_cycle();
+        regulated.run_cycle();
+    }
+
+    let unregulated_fraction = firing_fraction(&mut unregulated);
+    let regulated_fraction = firing_fraction(&mut regulated);
+    assert!(unregulated_fraction > 0.8);
+    assert!(regulated_fraction < 0.3);
+}
+
+#[test]
+fn test_fire_recorder() {
+    let build = || {
+        EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+            .sensors(save_test_sensors())
+            .actuators(as_dyn_actuators(&save_test_actuators()))
+            .seed(23)
+            .build()
+            .unwrap()
+    };
+
+    let mut ecp = build();
+    ecp.run_cycle();
+    assert!(ecp.fire_recorder().is_none());
+
+    ecp.set_fire_recorder(Some(FireRecorder::buffer(10_000)));
+    for _ in 0..5 {
+        ecp.run_cycle();
+    }
+    ecp.run_static_cycle();
+
+    let recorder = ecp.set_fire_recorder(None).unwrap();
+    let events = recorder.events().cloned().collect::<Vec<_>>();
+    assert_eq!(recorder.recorded(), events.len() as u64);
+    assert_eq!(recorder.dropped(), 0);
+    assert!(events.iter().all(|event| (1..7).contains(&event.cycle)));
+
+    // Sensory neurons fire every cycle with the measure of their sensor
+    let sensory = events
+        .iter()
+        .filter(|event| event.kind == NeuronKind::Sensory)
+        .collect::<Vec<_>>();
+    assert_eq!(sensory.len(), 4 * 6);
+    assert!(sensory
+        .iter()
+        .any(|event| event.measure == 0.9 && event.cycle == 6));
+
+    assert!(events.iter().any(|event| event.kind == NeuronKind::Plastic));
+
+    // Every fire in the recorded cycles is there, and nothing else
+    for (loc, &neuron) in ecp.plastic_neurons.iter() {
+        let fires = events
+            .iter()
+            .filter(|event| event.kind == NeuronKind::Plastic && event.loc == *loc)
+            .count() as u32;
+        let activity = ecp.neurons.fire_tracker(neuron).activity & 0b11_1111;
+        assert_eq!(fires, activity.count_ones());
+    }
+
+    let mut csv = Vec::new();
+    write_csv(&mut csv, &events).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("cycle,kind,location,measure"));
+    let first = &events[0];
+    assert_eq!(
+        lines.next().unwrap(),
+        format!(
+            "1,sensory,{} {} {},{}",
+            first.loc[0], first.loc[1], first.loc[2], first.measure
+        )
+    );
+    assert_eq!(lines.count(), events.len() - 1);
+
+    let mut binary = Vec::new();
+    write_binary(&mut binary, &events).unwrap();
+    assert_eq!(read_binary(&mut binary.as_slice()).unwrap(), events);
+    assert!(read_binary(&mut &binary[..binary.len() - 1]).is_err());
+
+    // A full buffer keeps the latest events
+    let mut ecp = build();
+    ecp.run_cycle();
+    ecp.set_fire_recorder(Some(FireRecorder::buffer(8)));
+    for _ in 0..5 {
+        ecp.run_cycle();
+    }
+    ecp.run_static_cycle();
+    let recorder = ecp.fire_recorder().unwrap();
+    assert_eq!(recorder.recorded(), events.len() as u64);
+    assert_eq!(recorder.dropped(), events.len() as u64 - 8);
+    assert_eq!(
+        recorder.events().collect::<Vec<_>>(),
+        events[events.len() - 8..].iter().collect::<Vec<_>>()
+    );
+
+    // Streams write the same events as they happen
+    let path = std::env::temp_dir().join(format!("aesir_fires_test_{}.bin", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    let mut ecp = build();
+    ecp.run_cycle();
+    ecp.set_fire_recorder(Some(
+        FireRecorder::stream(Box::new(file), FireFormat::Binary).unwrap(),
+    ));
+    for _ in 0..5 {
+        ecp.run_cycle();
+    }
+    ecp.run_static_cycle();
+    ecp.set_fire_recorder(None).unwrap().finish().unwrap();
+
+    let streamed = read_binary(&mut std::fs::File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(streamed, events);
+}
+
+#[test]
+fn test_synapse_snapshot() {
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act1".into(),
+            SynapseType::Excitatory,
+            20.,
+        ))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act1".into(),
+            SynapseType::Inhibitory,
+            3.,
+        ))
+        .seed(29)
+        .build()
+        .unwrap();
+
+    let before = ecp.synapse_snapshot();
+    assert_eq!(before.cycle, 0);
+    assert_eq!(before.synapses.len(), ecp.synapse_count());
+
+    let reflexes = before
+        .synapses
+        .iter()
+        .filter(|synapse| synapse.kind == SynapseKind::Static)
+        .collect::<Vec<_>>();
+    assert_eq!(reflexes.len(), 2);
+    assert_eq!(reflexes[0].source_kind, NeuronKind::Sensory);
+    assert_eq!(reflexes[0].target_kind, NeuronKind::Actuator);
+    assert_eq!(
+        reflexes[0].target,
+        *ecp.actuator_neurons
+            .iter()
+            .find(|(_, &neuron)| neuron == ecp.actuator_interfaces["act1"].actuator_neuron)
+            .unwrap()
+            .0
+    );
+    assert_eq!(reflexes[1].synapse_type, SynapseType::Inhibitory);
+
+    assert_eq!(
+        before.diff(&before),
+        SynapseDiff {
+            changed: vec![],
+            added: vec![],
+            removed: vec![],
+            unchanged: before.synapses.len(),
+            total_change: 0.,
+        }
+    );
+
+    for _ in 0..10 {
+        ecp.run_cycle();
+    }
+    let reflex = ecp.reflexes().next().unwrap().0;
+    ecp.remove_reflex(reflex).unwrap();
+    let removed_loc = ecp
+        .sensory_neurons
+        .iter()
+        .find(|(_, &neuron)| neuron == ecp.sensory_interfaces["4"].sensory_neuron)
+        .unwrap()
+        .0
+        .clone();
+    ecp.remove_sensor("4").unwrap();
+
+    let after = ecp.synapse_snapshot();
+    assert_eq!(after.cycle, 10);
+    let diff = before.diff(&after);
+
+    assert!(!diff.changed.is_empty());
+    assert_eq!(
+        diff.changed.len() + diff.unchanged + diff.removed.len(),
+        before.synapses.len()
+    );
+    assert_eq!(
+        diff.changed.len() + diff.unchanged + diff.added.len(),
+        after.synapses.len()
+    );
+    let changes = diff
+        .changed
+        .iter()
+        .map(|change| change.weight_change().abs())
+        .collect::<Vec<_>>();
+    assert!(changes.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert!((changes.iter().sum::<f32>() - diff.total_change).abs() < 1e-3);
+    assert_eq!(diff.largest_changes(3), &diff.changed[..3]);
+
+    // Reflexes between the same neurons are told apart by their type
+    assert!(diff.added.is_empty());
+    let (removed_static, removed_plastic): (Vec<_>, Vec<_>) = diff
+        .removed
+        .iter()
+        .partition(|synapse| synapse.kind == SynapseKind::Static);
+    assert_eq!(removed_static.len(), 1);
+    assert_eq!(removed_static[0].synapse_type, SynapseType::Excitatory);
+    assert_eq!(removed_static[0].weight, 20.);
+    assert!(!removed_plastic.is_empty());
+    assert!(removed_plastic
+        .iter()
+        .all(|synapse| synapse.source == removed_loc));
+}
+
+#[test]
+fn test_cycle_stats() {
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .reflex(Reflex::new(
+            "2".into(),
+            "act3".into(),
+            SynapseType::Inhibitory,
+            7.,
+        ))
+        .seed(31)
+        .build()
+        .unwrap();
+
+    assert!(!ecp.cycle_stats_enabled());
+    assert_eq!(ecp.run_cycle(), None);
+
+    ecp.set_cycle_stats(true);
+    for _ in 0..5 {
+        let before = ecp.synapse_snapshot();
+        let stats = ecp.run_cycle().unwrap();
+        let after = ecp.synapse_snapshot();
+        assert_eq!(stats.cycle, before.cycle);
+
+        let fired = |neurons: &IndexMap<Vec<i32>, NeuronId>| {
+            neurons
+                .values()
+                .filter(|&&neuron| ecp.neurons.fire_tracker(neuron).activity & 1 == 1)
+                .count()
+        };
+        assert_eq!(stats.sensory_fired, 4);
+        assert_eq!(stats.plastic_fired, fired(&ecp.plastic_neurons));
+        assert_eq!(stats.actuator_fired, fired(&ecp.actuator_neurons));
+        assert_eq!(
+            stats.fired(),
+            stats.sensory_fired + stats.plastic_fired + stats.actuator_fired
+        );
+        assert!(stats.max_measure >= stats.mean_measure && stats.mean_measure > 0.);
+
+        let total_weight = after
+            .synapses
+            .iter()
+            .map(|synapse| synapse.weight)
+            .sum::<f32>();
+        assert!((stats.total_weight - total_weight).abs() < 1e-2);
+
+        let diff = before.diff(&after);
+        assert_eq!(stats.changed_synapses, diff.changed.len());
+        assert!((stats.weight_change - diff.total_change).abs() < 1e-3);
+
+        // Impulses are sent by every neuron that fired, at the weight before learning
+        let (mut excitatory, mut inhibitory) = (0., 0.);
+        for synapse in &before.synapses {
+            let source = match synapse.source_kind {
+                NeuronKind::Sensory => ecp.sensory_neurons[&synapse.source],
+                _ => ecp.plastic_neurons[&synapse.source],
+            };
+            if ecp.neurons.fire_tracker(source).activity & 1 == 0 {
+                continue;
+            }
+
+            match synapse.synapse_type {
+                SynapseType::Excitatory => excitatory += synapse.weight,
+                SynapseType::Inhibitory => inhibitory += synapse.weight,
+            }
+        }
+        assert!((stats.excitatory_impulse - excitatory).abs() < 1e-2);
+        assert!((stats.inhibitory_impulse - inhibitory).abs() < 1e-2);
+        assert!(stats.inhibitory_impulse >= 7.);
+    }
+
+    // Nothing learns in static cycles
+    let stats = ecp.run_static_cycle().unwrap();
+    assert_eq!(stats.changed_synapses, 0);
+    assert_eq!(stats.weight_change, 0.);
+
+    ecp.set_cycle_stats(false);
+    assert_eq!(ecp.run_cycle(), None);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Observed {
+    CycleStart(u64),
+    ActuatorOutput(String, f32),
+    WeightUpdate(Vec<i32>, Vec<i32>, f32, f32),
+    NeuronFire(NeuronKind, Vec<i32>, f32),
+    CycleEnd(u64),
+}
+
+struct LoggingObserver(Arc<Mutex<Vec<Observed>>>);
+
+impl EncephalonObserver for LoggingObserver {
+    fn on_cycle_start(&mut self, cycle: u64) {
+        self.0.lock().unwrap().push(Observed::CycleStart(cycle));
+    }
+
+    fn on_actuator_output(&mut self, name: &str, value: f32) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(Observed::ActuatorOutput(name.into(), value));
+    }
+
+    fn on_weight_update(&mut self, source: &[i32], target: &[i32], old: f32, new: f32) {
+        self.0.lock().unwrap().push(Observed::WeightUpdate(
+            source.to_vec(),
+            target.to_vec(),
+            old,
+            new,
+        ));
+    }
+
+    fn on_neuron_fire(&mut self, kind: NeuronKind, loc: &[i32], measure: f32) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(Observed::NeuronFire(kind, loc.to_vec(), measure));
+    }
+
+    fn on_cycle_end(&mut self, cycle: u64) {
+        self.0.lock().unwrap().push(Observed::CycleEnd(cycle));
+    }
+}
+
+/// Only cares about fires, leaving the other callbacks to their defaults
+struct FireCounter(Arc<Mutex<usize>>);
+
+impl EncephalonObserver for FireCounter {
+    fn on_neuron_fire(&mut self, _kind: NeuronKind, _loc: &[i32], _measure: f32) {
+        *self.0.lock().unwrap() += 1;
+    }
+}
+
+#[test]
+fn test_observers() {
+    let actuators = save_test_actuators();
+    let mut ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(save_test_sensors())
+        .actuators(as_dyn_actuators(&actuators))
+        .reflex(Reflex::new(
+            "1".into(),
+            "act2".into(),
+            SynapseType::Excitatory,
+            20.,
+        ))
+        .seed(37)
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        ecp.run_cycle();
+    }
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let fires = Arc::new(Mutex::new(0));
+    ecp.add_observer(Box::new(LoggingObserver(Arc::clone(&log))));
+    ecp.add_observer(Box::new(FireCounter(Arc::clone(&fires))));
+    ecp.set_fire_recorder(Some(FireRecorder::buffer(1000)));
+
+    let before = ecp.synapse_snapshot();
+    ecp.run_cycle();
+    let diff = before.diff(&ecp.synapse_snapshot());
+    let recorded = ecp.set_fire_recorder(None).unwrap();
+
+    let log = log.lock().unwrap().clone();
+    assert_eq!(log.first(), Some(&Observed::CycleStart(3)));
+    assert_eq!(log.last(), Some(&Observed::CycleEnd(3)));
+
+    // Callbacks come in the order they're declared in
+    let stage = |observed: &Observed| match observed {
+        Observed::CycleStart(_) => 0,
+        Observed::ActuatorOutput(..) => 1,
+        Observed::WeightUpdate(..) => 2,
+        Observed::NeuronFire(..) => 3,
+        Observed::CycleEnd(_) => 4,
+    };
+    assert!(log
+        .windows(2)
+        .all(|pair| stage(&pair[0]) <= stage(&pair[1])));
+
+    let outputs = log
+        .iter()
+        .filter_map(|observed| match observed {
+            Observed::ActuatorOutput(name, value) => Some((name.clone(), *value)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(outputs.len(), actuators.len());
+    for actuator in &actuators {
+        assert!(outputs.contains(&(actuator.get_name(), actuator.get_control_value())));
+    }
+
+    let updates = log
+        .iter()
+        .filter_map(|observed| match observed {
+            Observed::WeightUpdate(source, target, old, new) => Some((source, target, old, new)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(!updates.is_empty());
+    assert_eq!(updates.len(), diff.changed.len());
+    for change in &diff.changed {
+        assert!(updates.contains(&(
+            &change.synapse.source,
+            &change.synapse.target,
+            &change.weight_before,
+            &change.synapse.weight
+        )));
+    }
+
+    let observed_fires = log
+        .iter()
+        .filter_map(|observed| match observed {
+            Observed::NeuronFire(kind, loc, measure) => Some(FireEvent {
+                cycle: 3,
+                loc: loc.clone(),
+                kind: *kind,
+                measure: *measure,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        observed_fires,
+        recorded.events().cloned().collect::<Vec<_>>()
+    );
+    assert_eq!(*fires.lock().unwrap(), observed_fires.len());
+
+    // Detached observers aren't called anymore
+    assert_eq!(ecp.take_observers().len(), 2);
+    ecp.run_cycle();
+    assert_eq!(*fires.lock().unwrap(), observed_fires.len());
+}
+
+#[test]
+fn test_graph_export() {
+    let name = "eye \"left\" <&>";
+    let sensors: Vec<Arc<dyn Sensor>> = vec![
+        Arc::new(ConstantSensor::new("1".into(), 0.5)),
+        Arc::new(ConstantSensor::new("2".into(), 0.3)),
+        Arc::new(ConstantSensor::new(name.into(), 0.9)),
+        Arc::new(ConstantSensor::new("4".into(), 0.1)),
+    ];
+    let ecp = EncephalonBuilder::new(Box::new(EcpBox::new(64, 3, 4, 26)))
+        .sensors(sensors)
+        .actuators(as_dyn_actuators(&save_test_actuators()))
+        .reflex(Reflex::new(
+            name.into(),
+            "act1".into(),
+            SynapseType::Inhibitory,
+            12.5,
+        ))
+        .seed(41)
+        .build()
+        .unwrap();
+    let neuron_count =
+        ecp.sensory_neurons.len() + ecp.plastic_neurons.len() + ecp.actuator_neurons.len();
+
+    let mut dot = Vec::new();
+    ecp.write_dot(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+
+    assert!(dot.starts_with("digraph encephalon {\n") && dot.ends_with("}\n"));
+    assert_eq!(
+        dot.matches(" [label=").count() - dot.matches(" -> ").count(),
+        neuron_count
+    );
+    assert_eq!(dot.matches(" -> ").count(), ecp.synapse_count());
+    assert!(dot.contains("label=\"eye \\\"left\\\" <&>\\n["));
+
+    let sensor = ecp.sensory_interfaces[name].sensory_neuron;
+    let sensor_loc = ecp
+        .sensory_neurons
+        .iter()
+        .find(|(_, &neuron)| neuron == sensor)
+        .unwrap()
+        .0;
+    let act1 = ecp.actuator_interfaces["act1"].actuator_neuron;
+    let act1_loc = ecp
+        .actuator_neurons
+        .iter()
+        .find(|(_, &neuron)| neuron == act1)
+        .unwrap()
+        .0;
+    let join = |loc: &[i32]| {
+        loc.iter()
+            .map(|coordinate| coordinate.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let reflex_edge = format!(
+        "  \"sensory:{}\" -> \"actuator:{}\" [label=\"12.5\", style=dashed, color=red, \
+         synapse_weight=12.5, synapse_type=inhibitory, delay=1];",
+        join(sensor_loc),
+        join(act1_loc)
+    );
+    assert!(dot.lines().any(|line| line == reflex_edge));
+    assert_eq!(dot.matches("style=dashed").count(), 1);
+
+    let mut graphml = Vec::new();
+    ecp.write_graphml(&mut graphml).unwrap();
+    let graphml = String::from_utf8(graphml).unwrap();
+
+    assert!(graphml.starts_with("<?xml"));
+    assert!(graphml.trim_end().ends_with("</graphml>"));
+    assert_eq!(graphml.matches("<node ").count(), neuron_count);
+    assert_eq!(graphml.matches("<edge ").count(), ecp.synapse_count());
+    assert_eq!(
+        graphml.matches("<data key=\"name\">").count(),
+        ecp.sensory_neurons.len() + ecp.actuator_neurons.len()
+    );
+    assert!(graphml.contains("<data key=\"name\">eye &quot;left&quot; &lt;&amp;&gt;</data>"));
+    assert!(graphml.contains(&format!(
+        "<edge source=\"sensory:{}\" target=\"actuator:{}\">\n      \
+         <data key=\"synapse_kind\">static</data>\n      \
+         <data key=\"synapse_type\">inhibitory</data>\n      \
+         <data key=\"weight\">12.5</data>",
+        join(sensor_loc),
+        join(act1_loc)
+    )));
+}
@@ -0,0 +1,222 @@
+//! Export of the network topology for external graph tools. Every neuron is a node,
+//! identified by its kind and location, and every synapse an edge from its source to
+//! its target.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::encephalon::Encephalon;
+use crate::fire_recorder::NeuronKind;
+use crate::neuron::{NeuronId, SynapseType};
+use crate::synapse_snapshot::{SynapseKind, SynapseState};
+
+/// A neuron as a node of the graph
+struct Node<'a> {
+    kind: NeuronKind,
+    loc: &'a [i32],
+    /// Name of the sensor or actuator of the neuron
+    name: Option<&'a str>,
+}
+
+impl Encephalon {
+    /// Writes the network as a Graphviz DOT digraph. Static synapses are dashed, and
+    /// inhibitory ones red
+    pub fn write_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "digraph encephalon {{")?;
+
+        for node in self.graph_nodes() {
+            let label = match node.name {
+                Some(name) => format!("{}\\n{:?}", dot_escape(name), node.loc),
+                None => format!("{:?}", node.loc),
+            };
+            let shape = match node.kind {
+                NeuronKind::Sensory => "invtriangle",
+                NeuronKind::Plastic => "circle",
+                NeuronKind::Actuator => "box",
+            };
+
+            writeln!(
+                writer,
+                "  \"{}\" [label=\"{}\", shape={}, kind={}];",
+                node_id(node.kind, node.loc),
+                label,
+                shape,
+                node.kind.name()
+            )?;
+        }
+
+        for synapse in self.synapse_snapshot().synapses {
+            let style = match synapse.kind {
+                SynapseKind::Plastic => "solid",
+                SynapseKind::Static => "dashed",
+            };
+            let color = match synapse.synapse_type {
+                SynapseType::Excitatory => "black",
+                SynapseType::Inhibitory => "red",
+            };
+
+            writeln!(
+                writer,
+                "  \"{}\" -> \"{}\" [label=\"{}\", style={}, color={}, synapse_weight={}, \
+                 synapse_type={}, delay={}];",
+                node_id(synapse.source_kind, &synapse.source),
+                node_id(synapse.target_kind, &synapse.target),
+                synapse.weight,
+                style,
+                color,
+                synapse.weight,
+                synapse_type_name(&synapse),
+                synapse.delay
+            )?;
+        }
+
+        writeln!(writer, "}}")
+    }
+
+    /// Writes the network as GraphML, with the kind, location and name of each node
+    /// and the kind, type, weight and delay of each edge as data
+    pub fn write_graphml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+
+        let keys = [
+            ("kind", "node", "string"),
+            ("location", "node", "string"),
+            ("name", "node", "string"),
+            ("synapse_kind", "edge", "string"),
+            ("synapse_type", "edge", "string"),
+            ("weight", "edge", "double"),
+            ("delay", "edge", "int"),
+        ];
+        for (key, domain, key_type) in keys.iter() {
+            writeln!(
+                writer,
+                "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>",
+                key, domain, key_type
+            )?;
+        }
+
+        writeln!(
+            writer,
+            "  <graph id=\"encephalon\" edgedefault=\"directed\">"
+        )?;
+
+        for node in self.graph_nodes() {
+            writeln!(writer, "    <node id=\"{}\">", node_id(node.kind, node.loc))?;
+            writeln!(
+                writer,
+                "      <data key=\"kind\">{}</data>",
+                node.kind.name()
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"location\">{}</data>",
+                location(node.loc)
+            )?;
+            if let Some(name) = node.name {
+                writeln!(
+                    writer,
+                    "      <data key=\"name\">{}</data>",
+                    xml_escape(name)
+                )?;
+            }
+            writeln!(writer, "    </node>")?;
+        }
+
+        for synapse in self.synapse_snapshot().synapses {
+            writeln!(
+                writer,
+                "    <edge source=\"{}\" target=\"{}\">",
+                node_id(synapse.source_kind, &synapse.source),
+                node_id(synapse.target_kind, &synapse.target)
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"synapse_kind\">{}</data>",
+                match synapse.kind {
+                    SynapseKind::Plastic => "plastic",
+                    SynapseKind::Static => "static",
+                }
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"synapse_type\">{}</data>",
+                synapse_type_name(&synapse)
+            )?;
+            writeln!(
+                writer,
+                "      <data key=\"weight\">{}</data>",
+                synapse.weight
+            )?;
+            writeln!(writer, "      <data key=\"delay\">{}</data>", synapse.delay)?;
+            writeln!(writer, "    </edge>")?;
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")
+    }
+
+    /// Sensory, then plastic, then actuator neurons
+    fn graph_nodes(&self) -> Vec<Node<'_>> {
+        let names = self
+            .sensory_interfaces
+            .iter()
+            .map(|(name, interface)| (interface.sensory_neuron, name.as_str()))
+            .chain(
+                self.actuator_interfaces
+                    .iter()
+                    .map(|(name, interface)| (interface.actuator_neuron, name.as_str())),
+            )
+            .collect::<HashMap<NeuronId, &str>>();
+
+        let neurons = [
+            (NeuronKind::Sensory, &self.sensory_neurons),
+            (NeuronKind::Plastic, &self.plastic_neurons),
+            (NeuronKind::Actuator, &self.actuator_neurons),
+        ];
+
+        neurons
+            .iter()
+            .flat_map(|&(kind, locs)| locs.iter().map(move |(loc, neuron)| (kind, loc, neuron)))
+            .map(|(kind, loc, neuron)| Node {
+                kind,
+                loc,
+                name: names.get(neuron).copied(),
+            })
+            .collect()
+    }
+}
+
+/// Locations are only unique within a kind of neuron
+fn node_id(kind: NeuronKind, loc: &[i32]) -> String {
+    format!("{}:{}", kind.name(), location(loc))
+}
+
+fn location(loc: &[i32]) -> String {
+    loc.iter()
+        .map(|coordinate| coordinate.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn synapse_type_name(synapse: &SynapseState) -> &'static str {
+    match synapse.synapse_type {
+        SynapseType::Excitatory => "excitatory",
+        SynapseType::Inhibitory => "inhibitory",
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::actuator::Actuator;
+use crate::ecp_geometry::{EcpGeometry, SynapticDelay};
+use crate::encephalon::{persistence, Encephalon, NeuronTemplate};
+use crate::learning_rule::{LearningRule, WeightModifier};
+use crate::neuron::{basic_weight_modifier, ChargeCycle, NeuronArena};
+use crate::sensor::Sensor;
+use crate::structural_plasticity::StructuralChanges;
+
+/// Restores an `Encephalon` written by `Encephalon::save`, along with the things that
+/// aren't written to disk.
+///
+/// Sensors and actuators are matched to their neurons by name, so exactly the sensors
+/// and actuators the saved encephalon was using must be passed. The random number
+/// generator isn't saved either: without `seed` or `rng`, the random decisions of the
+/// restored encephalon (placing added sensors, growing synapses) are seeded from entropy.
+pub struct EncephalonLoader {
+    sensors: Vec<Arc<dyn Sensor>>,
+    actuators: Vec<Arc<dyn Actuator>>,
+    learning_rule: Arc<dyn LearningRule>,
+    rng: Option<Box<dyn RngCore + Send + Sync>>,
+    #[cfg(feature = "parallel")]
+    threads: usize,
+}
+
+impl EncephalonLoader {
+    pub fn new(
+        sensors: Vec<Arc<dyn Sensor>>,
+        actuators: Vec<Arc<dyn Actuator>>,
+    ) -> EncephalonLoader {
+        EncephalonLoader {
+            sensors,
+            actuators,
+            learning_rule: Arc::new(WeightModifier(basic_weight_modifier)),
+            rng: None,
+            #[cfg(feature = "parallel")]
+            threads: 1,
+        }
+    }
+
+    /// Weight modifier used for every restored neuron, `basic_weight_modifier` by default
+    pub fn weight_modifier(
+        self,
+        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
+    ) -> EncephalonLoader {
+        self.learning_rule(Arc::new(WeightModifier(weight_modifier)))
+    }
+
+    /// Learning rule used for every restored neuron
+    pub fn learning_rule(mut self, learning_rule: Arc<dyn LearningRule>) -> EncephalonLoader {
+        self.learning_rule = learning_rule;
+        self
+    }
+
+    /// Makes every random decision of the restored encephalon reproducible: the same
+    /// file loaded with the same seed always behaves the same way
+    pub fn seed(mut self, seed: u64) -> EncephalonLoader {
+        self.rng = Some(Box::new(StdRng::seed_from_u64(seed)));
+        self
+    }
+
+    /// Uses `rng` for every random decision of the restored encephalon
+    pub fn rng<R: RngCore + Send + Sync + 'static>(mut self, rng: R) -> EncephalonLoader {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    /// Number of threads each cycle is evaluated on, 1 by default
+    #[cfg(feature = "parallel")]
+    pub fn threads(mut self, threads: usize) -> EncephalonLoader {
+        self.threads = threads;
+        self
+    }
+
+    /// The geometry is rebuilt as a `G` from the saved parameters
+    pub fn load<G: EcpGeometry + 'static, P: AsRef<Path>>(self, path: P) -> io::Result<Encephalon> {
+        #[cfg(feature = "parallel")]
+        {
+            if self.threads == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    crate::error::EncephalonError::InvalidParameter {
+                        parameter: "threads",
+                        reason: "must be at least 1".into(),
+                    },
+                ));
+            }
+        }
+
+        let contents = fs::read_to_string(path)?;
+        let mut reader = persistence::Reader::new(&contents);
+
+        let geometry = reader.header()?;
+
+        let ecp_geometry = G::try_new(
+            geometry.num_plastic,
+            geometry.num_actuator,
+            geometry.num_sensory,
+            geometry.nearby_count,
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut encephalon = Encephalon {
+            ecp_geometry: Box::new(ecp_geometry),
+            neurons: NeuronArena::new(),
+            plastic_neurons: IndexMap::new(),
+            actuator_neurons: IndexMap::new(),
+            sensory_neurons: IndexMap::new(),
+            actuator_interfaces: IndexMap::new(),
+            sensory_interfaces: IndexMap::new(),
+            cycle: ChargeCycle::Odd,
+            rng: self.rng.unwrap_or_else(|| Box::new(StdRng::from_entropy())),
+            template: NeuronTemplate::with_learning_rule(Arc::clone(&self.learning_rule)),
+            reflexes: IndexMap::new(),
+            next_reflex_id: 0,
+            synaptic_delay: SynapticDelay::default(),
+            structural_plasticity: None,
+            structural_changes: StructuralChanges::default(),
+            fire_recorder: None,
+            cycle_stats: false,
+            observers: Vec::new(),
+            #[cfg(feature = "parallel")]
+            executor: super::parallel::ThreadedExecutor::new(self.threads),
+        };
+
+        persistence::read_neurons(
+            &mut reader,
+            &mut encephalon,
+            self.sensors,
+            self.actuators,
+            self.learning_rule,
+        )?;
+
+        Ok(encephalon)
+    }
+}
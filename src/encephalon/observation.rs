@@ -0,0 +1,83 @@
+//! Calling back the observers of an encephalon.
+
+use crate::encephalon::stats::SourceWeights;
+use crate::encephalon::Encephalon;
+use crate::fire_recorder::NeuronKind;
+
+impl Encephalon {
+    pub(crate) fn notify_cycle_start(&mut self) {
+        let cycle_number = self.neurons.cycle_number();
+
+        for observer in self.observers.iter_mut() {
+            observer.on_cycle_start(cycle_number);
+        }
+    }
+
+    pub(crate) fn notify_actuator_outputs(&mut self) {
+        for (name, interface) in self.actuator_interfaces.iter() {
+            let value = self.neurons.read_measure(interface.actuator_neuron);
+
+            for observer in self.observers.iter_mut() {
+                observer.on_actuator_output(name, value);
+            }
+        }
+    }
+
+    /// `before` are the weights from before the cycle, see `weights_before_cycle`
+    pub(crate) fn notify_weight_updates(&mut self, before: &[SourceWeights]) {
+        // Taken out so the locations of the targets can be borrowed meanwhile
+        let mut observers = std::mem::take(&mut self.observers);
+        let targets = self.rx_locs();
+        let sources = self
+            .sensory_neurons
+            .keys()
+            .chain(self.plastic_neurons.keys());
+
+        for (source, source_weights) in sources.zip(before) {
+            let (plastic_synapses, static_synapses) = self.neurons.synapses(source_weights.neuron);
+            let synapses = plastic_synapses
+                .targets
+                .iter()
+                .zip(&plastic_synapses.weights)
+                .chain(static_synapses.targets.iter().zip(&static_synapses.weights));
+
+            for ((target, &new), &old) in synapses.zip(&source_weights.weights) {
+                if new != old {
+                    let (_, target) = targets[target];
+
+                    for observer in observers.iter_mut() {
+                        observer.on_weight_update(source, target, old, new);
+                    }
+                }
+            }
+        }
+
+        self.observers = observers;
+    }
+
+    pub(crate) fn notify_fires(&mut self) {
+        let neurons = [
+            (NeuronKind::Sensory, &self.sensory_neurons),
+            (NeuronKind::Plastic, &self.plastic_neurons),
+            (NeuronKind::Actuator, &self.actuator_neurons),
+        ];
+
+        for (kind, locs) in neurons.iter() {
+            for (loc, &neuron) in locs.iter() {
+                if let Some(measure) = self.neurons.last_cycle_fire(neuron, self.cycle) {
+                    for observer in self.observers.iter_mut() {
+                        observer.on_neuron_fire(*kind, loc, measure);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn notify_cycle_end(&mut self) {
+        let cycle_number = self.neurons.cycle_number() - 1;
+
+        for observer in self.observers.iter_mut() {
+            observer.on_cycle_end(cycle_number);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Multi-threaded cycle execution.
+//!
+//! Within a phase (sensory, plastic or actuator) a neuron only reads its own charge for
+//! the current cycle and the fire receipts of the previous cycle, and only writes to
+//! the charge of other neurons for upcoming cycles. So the plastic neurons are evaluated
+//! in chunks on their own thread, and the synapses of each phase are split into chunks
+//! that are updated on their own thread with impulses buffered. The buffered impulses
+//! are then delivered in the same order the sequential path would have delivered them.
+//! This keeps the results identical to running on a single thread.
+//!
+//! The threads are started along with the encephalon and wait for work between phases,
+//! so a cycle only costs handing out the chunks rather than starting threads.
+
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, SendError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use crate::neuron::{FireTracker, ImpulseSink, InternalCharge, PendingImpulse, PhaseExecutor};
+
+/// Runs each phase split across `threads` threads, the calling thread included
+pub(crate) struct ThreadedExecutor {
+    threads: usize,
+    pool: WorkerPool,
+}
+
+impl ThreadedExecutor {
+    /// Starts `threads - 1` worker threads, which run until the executor is dropped.
+    /// Panics if `threads` is 0
+    pub(crate) fn new(threads: usize) -> ThreadedExecutor {
+        assert!(threads > 0, "An encephalon needs at least one thread");
+
+        ThreadedExecutor {
+            threads,
+            pool: WorkerPool::new(threads - 1),
+        }
+    }
+
+    pub(crate) fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Splits `items` into one chunk per thread, along with the index of each chunk's
+    /// first item
+    fn chunks<'a, T>(&self, items: &'a mut [T]) -> impl Iterator<Item = (usize, &'a mut [T])> {
+        let chunk_size = items.len().div_ceil(self.threads).max(1);
+
+        items
+            .chunks_mut(chunk_size)
+            .enumerate()
+            .map(move |(chunk_index, chunk)| (chunk_index * chunk_size, chunk))
+    }
+}
+
+impl PhaseExecutor for ThreadedExecutor {
+    fn run_phase<N, F>(
+        &self,
+        neurons: &mut [N],
+        cycle_number: u64,
+        fire_trackers: &[FireTracker],
+        internal_charges: &mut [InternalCharge],
+        run: F,
+    ) where
+        N: Send,
+        F: Fn(usize, &mut N, &[FireTracker], &mut ImpulseSink) + Sync,
+    {
+        let run = &run;
+        let chunks = self.chunks(neurons).collect::<Vec<_>>();
+        let mut buffers = chunks.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+
+        let jobs = chunks
+            .into_iter()
+            .zip(buffers.iter_mut())
+            .map(|((first, chunk), buffer)| {
+                Box::new(move || {
+                    let mut sink = ImpulseSink::Buffer(Vec::new());
+
+                    for (i, neuron) in chunk.iter_mut().enumerate() {
+                        run(first + i, neuron, fire_trackers, &mut sink);
+                    }
+
+                    if let ImpulseSink::Buffer(pending) = sink {
+                        *buffer = pending;
+                    }
+                }) as Box<dyn FnOnce() + Send + '_>
+            })
+            .collect();
+        self.pool.run(jobs);
+
+        deliver(buffers, cycle_number, internal_charges);
+    }
+
+    fn for_each<T, F>(&self, items: &mut [T], run: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut T) + Sync,
+    {
+        let run = &run;
+
+        let jobs = self
+            .chunks(items)
+            .map(|(first, chunk)| {
+                Box::new(move || {
+                    for (i, item) in chunk.iter_mut().enumerate() {
+                        run(first + i, item);
+                    }
+                }) as Box<dyn FnOnce() + Send + '_>
+            })
+            .collect();
+        self.pool.run(jobs);
+    }
+}
+
+fn deliver(
+    buffers: Vec<Vec<PendingImpulse>>,
+    cycle_number: u64,
+    internal_charges: &mut [InternalCharge],
+) {
+    for buffer in buffers {
+        for impulse in buffer {
+            impulse.deliver(cycle_number, internal_charges);
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Threads that wait for jobs for as long as the pool is alive
+struct WorkerPool {
+    jobs: Option<Sender<Job>>,
+    // Whether each job finished without panicking
+    done: Mutex<Receiver<bool>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(workers: usize) -> WorkerPool {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (done_sender, done) = mpsc::channel();
+
+        let workers = (0..workers)
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let done_sender = done_sender.clone();
+
+                thread::spawn(move || loop {
+                    let job = match lock(&job_receiver).recv() {
+                        Ok(job) => job,
+                        // The pool was dropped
+                        Err(_) => return,
+                    };
+
+                    let finished = panic::catch_unwind(AssertUnwindSafe(job)).is_ok();
+                    if done_sender.send(finished).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+
+        WorkerPool {
+            jobs: Some(jobs),
+            done: Mutex::new(done),
+            workers,
+        }
+    }
+
+    /// Runs every job, the last one on the calling thread, and only returns once all
+    /// of them have finished. Panics if any of them panicked
+    fn run<'a>(&self, mut jobs: Vec<Box<dyn FnOnce() + Send + 'a>>) {
+        let own_job = match jobs.pop() {
+            Some(job) => job,
+            None => return,
+        };
+
+        // Held until every job has reported back, so runs never mix up their reports
+        let done = lock(&self.done);
+
+        let mut sent = 0;
+        let mut finished = true;
+        for job in jobs {
+            // SAFETY: the job may borrow data that only lives for 'a. This function
+            // doesn't return or unwind before every job sent to a worker has reported
+            // back below, so the job can't outlive anything it borrows
+            let job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'a>, Job>(job) };
+
+            match self.jobs.as_ref().unwrap().send(job) {
+                Ok(()) => sent += 1,
+                // Only when every worker is gone, so the job is run here instead
+                Err(SendError(job)) => {
+                    finished &= panic::catch_unwind(AssertUnwindSafe(job)).is_ok();
+                }
+            }
+        }
+
+        let own_result = panic::catch_unwind(AssertUnwindSafe(own_job));
+
+        // A worker that can't report back has stopped, along with the job it ran
+        for _ in 0..sent {
+            finished &= done.recv().unwrap_or(false);
+        }
+
+        if let Err(payload) = own_result {
+            panic::resume_unwind(payload);
+        }
+        assert!(finished, "A job panicked on a worker thread");
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // Workers stop once no more jobs can be sent
+        self.jobs.take();
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Locks `mutex`, even if a panic poisoned it: the channels it guards stay usable
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,1378 @@
+//! Versioned text format used by `Encephalon::save` and `Encephalon::load`.
+//!
+//! The file is a whitespace separated stream of tokens. Line breaks are only
+//! there to keep the file readable. Floats are written as the hex of their
+//! bits so that a restored network is identical to the one that was saved.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+
+use crate::actuator::Actuator;
+use crate::ecp_geometry::SynapticDelay;
+use crate::encephalon::{Encephalon, NeuronTemplate};
+use crate::learning_rule::{LearningRule, Stdp};
+use crate::neuron::{
+    AdaptiveThreshold, Aggregation, ChargeCycle, FireReceipt, FireTracker, Inhibition,
+    LeakyIntegration, MeasureAggregation, NeuronArena, NeuronId, Refractory, SynapseType, Synapses,
+};
+use crate::neuron_interfaces::{ActuatorInterface, SensoryInterface};
+use crate::reflex::{Reflex, ReflexId, ReflexSource, ReflexTarget};
+use crate::sensor::Sensor;
+use crate::structural_plasticity::{StructuralChanges, StructuralPlasticity};
+use crate::weight_regulation::{
+    Homeostasis, Normalization, NormalizationDirection, WeightRegulation,
+};
+
+pub(crate) const MAGIC: &str = "aesir-encephalon";
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// Kind of neuron a synapse points at
+#[derive(Copy, Clone, PartialEq)]
+enum RxKind {
+    Plastic,
+    Actuator,
+}
+
+/// Geometry parameters, as passed to `EcpGeometry::new`
+pub(crate) struct GeometryRecord {
+    pub(crate) num_plastic: u32,
+    pub(crate) num_actuator: u32,
+    pub(crate) num_sensory: u32,
+    pub(crate) nearby_count: u32,
+}
+
+struct SynapseRecord {
+    synapse_type: SynapseType,
+    weight: f32,
+    target_kind: RxKind,
+    target_loc: Vec<i32>,
+    delay: u8,
+    weak_checks: u32,
+    reflex: Option<ReflexId>,
+}
+
+struct FireRecord {
+    receipts: (FireReceipt, FireReceipt),
+    last_fire: Option<u64>,
+    history: Vec<u64>,
+    activity: u64,
+}
+
+/// Weighted charges, weights and shunting inhibition of every upcoming cycle, starting
+/// with the next cycle to be evaluated
+struct ChargeRecord {
+    bins: u8,
+    aggregation: Aggregation,
+    cycles: Vec<(Vec<f32>, Vec<f32>, f32)>,
+}
+
+struct PlasticRecord {
+    loc: Vec<i32>,
+    fire_threshold: f32,
+    fire: FireRecord,
+    firing_rate: Option<f32>,
+    threshold_rise: f32,
+    charge: ChargeRecord,
+    plastic_synapses: Vec<SynapseRecord>,
+    static_synapses: Vec<SynapseRecord>,
+}
+
+struct ActuatorRecord {
+    loc: Vec<i32>,
+    name: String,
+    fire_threshold: f32,
+    measure: f32,
+    fire: FireRecord,
+    threshold_rise: f32,
+    charge: ChargeRecord,
+}
+
+struct SensoryRecord {
+    loc: Vec<i32>,
+    name: String,
+    measure: f32,
+    fire: FireRecord,
+    plastic_synapses: Vec<SynapseRecord>,
+    static_synapses: Vec<SynapseRecord>,
+}
+
+fn invalid_data<S: Into<String>>(msg: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Writes an encephalon into the text format
+pub(crate) fn write_encephalon(encephalon: &Encephalon) -> String {
+    let mut out = String::new();
+
+    let neurons = &encephalon.neurons;
+
+    // Used to resolve the target of every synapse back to its location
+    let mut rx_locs: HashMap<u32, (RxKind, &Vec<i32>)> = HashMap::new();
+    for (loc, &neuron) in encephalon.plastic_neurons.iter() {
+        rx_locs.insert(neurons.rx_index(neuron), (RxKind::Plastic, loc));
+    }
+    for (loc, &neuron) in encephalon.actuator_neurons.iter() {
+        rx_locs.insert(neurons.rx_index(neuron), (RxKind::Actuator, loc));
+    }
+
+    // Weak checks are only written for plastic synapses with structural plasticity, and
+    // reflex ids for static synapses, which are all reflexes
+    let structural = encephalon.structural_plasticity.is_some();
+    let write_synapses =
+        |out: &mut String, synapses: &Synapses, weak_checks: bool, reflex_ids: &[ReflexId]| {
+            for i in 0..synapses.len() {
+                let (kind, loc) = rx_locs
+                    .get(&synapses.targets[i])
+                    .expect("Synapse targets a neuron outside of the encephalon");
+
+                out.push_str(&format!(
+                    "synapse {} {} {} {} {}",
+                    synapse_type_token(synapses.types[i]),
+                    f32_token(synapses.weights[i]),
+                    match kind {
+                        RxKind::Plastic => "p",
+                        RxKind::Actuator => "a",
+                    },
+                    loc_token(loc),
+                    synapses.delays[i]
+                ));
+
+                if weak_checks {
+                    out.push_str(&format!(" {}", synapses.weak_checks[i]));
+                }
+                if let Some(id) = reflex_ids.get(i) {
+                    out.push_str(&format!(" {}", id.0));
+                }
+                out.push('\n');
+            }
+        };
+
+    out.push_str(&format!("{} {}\n", MAGIC, FORMAT_VERSION));
+
+    let geometry = &encephalon.ecp_geometry;
+    out.push_str(&format!(
+        "geometry {} {} {} {}\n",
+        geometry.get_num_plastic(),
+        geometry.get_num_actuator(),
+        geometry.get_num_sensory(),
+        geometry.get_nearby_count()
+    ));
+
+    out.push_str(&format!(
+        "cycle {} {}\n",
+        cycle_token(encephalon.cycle),
+        neurons.cycle_number()
+    ));
+
+    match neurons.stdp() {
+        Some(stdp) => out.push_str(&format!(
+            "stdp {} {} {} {} {}\n",
+            f32_token(stdp.potentiation),
+            f32_token(stdp.depression),
+            f32_token(stdp.potentiation_time_constant),
+            f32_token(stdp.depression_time_constant),
+            stdp.window
+        )),
+        None => out.push_str("stdp none\n"),
+    }
+
+    write_regulation(&mut out, neurons.weight_regulation());
+
+    match neurons.adaptive_threshold() {
+        Some(adaptive_threshold) => out.push_str(&format!(
+            "threshold {} {}\n",
+            f32_token(adaptive_threshold.increment),
+            f32_token(adaptive_threshold.time_constant)
+        )),
+        None => out.push_str("threshold none\n"),
+    }
+
+    match neurons.refractory() {
+        Some(refractory) => out.push_str(&format!(
+            "refractory {} {} {}\n",
+            refractory.absolute,
+            refractory.relative,
+            f32_token(refractory.relative_threshold_rise)
+        )),
+        None => out.push_str("refractory none\n"),
+    }
+
+    match neurons.leaky_integration() {
+        Some(leaky_integration) => out.push_str(&format!(
+            "leak {}\n",
+            f32_token(leaky_integration.carry_over)
+        )),
+        None => out.push_str("leak none\n"),
+    }
+
+    match encephalon.synaptic_delay {
+        SynapticDelay::Fixed(delay) => out.push_str(&format!("delay fixed {}\n", delay)),
+        SynapticDelay::Distance {
+            distance_per_cycle,
+            max_delay,
+        } => out.push_str(&format!(
+            "delay distance {} {}\n",
+            f32_token(distance_per_cycle),
+            max_delay
+        )),
+    }
+
+    match &encephalon.structural_plasticity {
+        Some(structural) => out.push_str(&format!(
+            "structural {} {} {} {} {} {} {} {}\n",
+            structural.interval,
+            f32_token(structural.prune_threshold),
+            structural.prune_after,
+            structural.growth_coactivity,
+            f32_token(structural.growth_probability),
+            f32_token(structural.growth_weight),
+            encephalon.structural_changes.pruned,
+            encephalon.structural_changes.grown
+        )),
+        None => out.push_str("structural none\n"),
+    }
+
+    let template = &encephalon.template;
+    out.push_str(&format!(
+        "template {} {} {} {} {} {}\n",
+        template.actuator_charge_bins,
+        f32_token(template.actuator_fire_threshold),
+        aggregation_token(&template.actuator_aggregation),
+        f32_token(template.synaptic_type_ratio),
+        f32_token(template.synapse_weight_ranges.0),
+        f32_token(template.synapse_weight_ranges.1)
+    ));
+
+    out.push_str(&format!("plastic {}\n", encephalon.plastic_neurons.len()));
+    for (loc, &neuron) in encephalon.plastic_neurons.iter() {
+        out.push_str(&format!(
+            "neuron {} {}\n",
+            loc_token(loc),
+            f32_token(neurons.fire_threshold(neuron))
+        ));
+        write_receipts(&mut out, neurons.fire_tracker(neuron), structural);
+        if let Some(rate) = neurons.firing_rate(neuron) {
+            out.push_str(&format!("rate {}\n", f32_token(rate)));
+        }
+        write_threshold_rise(&mut out, neurons, neuron);
+        write_charge(&mut out, neurons, neuron);
+
+        let reflex_ids = reflex_ids(encephalon, &ReflexSource::Plastic(loc.clone()));
+
+        let (plastic_synapses, static_synapses) = neurons.synapses(neuron);
+        out.push_str(&format!(
+            "synapses {} {}\n",
+            plastic_synapses.len(),
+            static_synapses.len()
+        ));
+        write_synapses(&mut out, plastic_synapses, structural, &[]);
+        write_synapses(&mut out, static_synapses, false, &reflex_ids);
+    }
+
+    let actuator_names = encephalon
+        .actuator_interfaces
+        .iter()
+        .map(|(name, interface)| (interface.actuator_neuron, name))
+        .collect::<HashMap<_, _>>();
+
+    out.push_str(&format!("actuator {}\n", encephalon.actuator_neurons.len()));
+    for (loc, neuron) in encephalon.actuator_neurons.iter() {
+        let name = actuator_names
+            .get(neuron)
+            .expect("Actuator neuron without an actuator interface");
+
+        out.push_str(&format!(
+            "neuron {} {} {} {}\n",
+            loc_token(loc),
+            name_token(name),
+            f32_token(neurons.fire_threshold(*neuron)),
+            f32_token(neurons.read_measure(*neuron))
+        ));
+        write_receipts(&mut out, neurons.fire_tracker(*neuron), structural);
+        write_threshold_rise(&mut out, neurons, *neuron);
+        write_charge(&mut out, neurons, *neuron);
+    }
+
+    let sensor_names = encephalon
+        .sensory_interfaces
+        .iter()
+        .map(|(name, interface)| (interface.sensory_neuron, name))
+        .collect::<HashMap<_, _>>();
+
+    out.push_str(&format!("sensory {}\n", encephalon.sensory_neurons.len()));
+    for (loc, neuron) in encephalon.sensory_neurons.iter() {
+        let name = sensor_names
+            .get(neuron)
+            .expect("Sensory neuron without a sensory interface");
+
+        out.push_str(&format!(
+            "neuron {} {} {}\n",
+            loc_token(loc),
+            name_token(name),
+            f32_token(neurons.sensory_measure(*neuron))
+        ));
+        write_receipts(&mut out, neurons.fire_tracker(*neuron), structural);
+
+        let reflex_ids = reflex_ids(encephalon, &ReflexSource::Sensor(name.to_string()));
+
+        let (plastic_synapses, static_synapses) = neurons.synapses(*neuron);
+        out.push_str(&format!(
+            "synapses {} {}\n",
+            plastic_synapses.len(),
+            static_synapses.len()
+        ));
+        write_synapses(&mut out, plastic_synapses, structural, &[]);
+        write_synapses(&mut out, static_synapses, false, &reflex_ids);
+    }
+
+    out
+}
+
+/// Ids of the reflexes starting at `source`, in the order of its static synapses
+fn reflex_ids(encephalon: &Encephalon, source: &ReflexSource) -> Vec<ReflexId> {
+    encephalon
+        .reflexes
+        .iter()
+        .filter(|(_, reflex)| reflex.source() == source)
+        .map(|(&id, _)| id)
+        .collect()
+}
+
+/// Recent activity is only written with structural plasticity
+fn write_receipts(out: &mut String, fire_tracker: &FireTracker, structural: bool) {
+    let (even, odd) = fire_tracker.receipts;
+
+    out.push_str(&format!(
+        "receipts {} {} {} {} {}\n",
+        even.fired as u8,
+        f32_token(even.measure),
+        odd.fired as u8,
+        f32_token(odd.measure),
+        fire_tracker
+            .last_fire()
+            .map_or("none".into(), |cycle_number| cycle_number.to_string())
+    ));
+
+    // Histories are only kept with STDP
+    if fire_tracker.history_window() > 0 {
+        out.push_str(&format!("history {}", fire_tracker.fire_history().count()));
+        for fire in fire_tracker.fire_history() {
+            out.push_str(&format!(" {}", fire));
+        }
+        out.push('\n');
+    }
+
+    if structural {
+        out.push_str(&format!("activity {:016x}\n", fire_tracker.activity));
+    }
+}
+
+/// Only written with adaptive thresholds
+fn write_threshold_rise(out: &mut String, neurons: &NeuronArena, neuron: NeuronId) {
+    if neurons.adaptive_threshold().is_some() {
+        out.push_str(&format!(
+            "rise {}\n",
+            f32_token(neurons.threshold_rise(neuron))
+        ));
+    }
+}
+
+fn write_regulation(out: &mut String, regulation: &WeightRegulation) {
+    let optional_f32 = |value: Option<f32>| value.map_or("none".into(), f32_token);
+
+    out.push_str(&format!(
+        "regulation {}",
+        optional_f32(regulation.max_weight)
+    ));
+
+    match &regulation.normalization {
+        Some(normalization) => out.push_str(&format!(
+            " {} {} {}",
+            match normalization.direction {
+                NormalizationDirection::Incoming => "incoming",
+                NormalizationDirection::Outgoing => "outgoing",
+            },
+            f32_token(normalization.total_weight),
+            normalization.interval
+        )),
+        None => out.push_str(" none"),
+    }
+
+    match &regulation.homeostasis {
+        Some(homeostasis) => out.push_str(&format!(
+            " {} {} {} {}",
+            f32_token(homeostasis.target_rate),
+            f32_token(homeostasis.rate_time_constant),
+            f32_token(homeostasis.scaling_rate),
+            homeostasis.interval
+        )),
+        None => out.push_str(" none"),
+    }
+
+    out.push('\n');
+}
+
+/// Charge of every upcoming cycle, starting with the next cycle to be evaluated.
+/// Shunting inhibition is only written for neurons that have it.
+fn write_charge(out: &mut String, neurons: &NeuronArena, neuron: NeuronId) {
+    let internal_charge = neurons.internal_charge(neuron);
+    let max_delay = internal_charge.max_delay();
+    let aggregation = internal_charge.aggregation();
+
+    let shunting = matches!(aggregation.inhibition, Inhibition::Shunting);
+    out.push_str(&format!(
+        "aggregation {}\n",
+        aggregation_token(&aggregation)
+    ));
+
+    out.push_str(&format!("charge {} {}", internal_charge.bins, max_delay));
+
+    let cycle_number = neurons.cycle_number();
+    for cycle_number in cycle_number..=cycle_number + max_delay as u64 {
+        let (charges, weights) = internal_charge.cycle_bins(cycle_number);
+
+        for value in charges.iter().chain(weights) {
+            out.push(' ');
+            out.push_str(&f32_token(*value));
+        }
+
+        if shunting {
+            out.push(' ');
+            out.push_str(&f32_token(internal_charge.shunt(cycle_number)));
+        }
+    }
+
+    out.push('\n');
+}
+
+fn aggregation_token(aggregation: &Aggregation) -> String {
+    let measure = match aggregation.measure {
+        MeasureAggregation::WeightedAverage => "average".to_string(),
+        MeasureAggregation::WeightedMedian => "median".to_string(),
+        MeasureAggregation::Mode => "mode".to_string(),
+        MeasureAggregation::Softmax { temperature } => {
+            format!("softmax {}", f32_token(temperature))
+        }
+    };
+    let inhibition = match aggregation.inhibition {
+        Inhibition::Subtractive => "subtractive",
+        Inhibition::Shunting => "shunting",
+    };
+
+    format!("{} {}", measure, inhibition)
+}
+
+fn f32_token(value: f32) -> String {
+    format!("{:08x}", value.to_bits())
+}
+
+fn f32_from_token(token: &str) -> io::Result<f32> {
+    u32::from_str_radix(token, 16)
+        .map(f32::from_bits)
+        .map_err(|_| invalid_data(format!("Invalid float `{}`", token)))
+}
+
+fn loc_token(loc: &[i32]) -> String {
+    format!(
+        "[{}]",
+        loc.iter()
+            .map(|coord| coord.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+fn cycle_token(cycle: ChargeCycle) -> &'static str {
+    match cycle {
+        ChargeCycle::Even => "even",
+        ChargeCycle::Odd => "odd",
+    }
+}
+
+fn synapse_type_token(synapse_type: SynapseType) -> &'static str {
+    match synapse_type {
+        SynapseType::Excitatory => "e",
+        SynapseType::Inhibitory => "i",
+    }
+}
+
+/// Names are percent encoded so they never contain whitespace
+fn name_token(name: &str) -> String {
+    let mut token = String::new();
+
+    for c in name.chars() {
+        if c.is_whitespace() || c == '%' {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                token.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            token.push(c);
+        }
+    }
+
+    token
+}
+
+/// Cursor over the tokens of a saved encephalon
+pub(crate) struct Reader<'a> {
+    tokens: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(contents: &'a str) -> Reader<'a> {
+        Reader {
+            tokens: contents.split_whitespace(),
+        }
+    }
+
+    fn next(&mut self) -> io::Result<&'a str> {
+        self.tokens
+            .next()
+            .ok_or_else(|| invalid_data("Unexpected end of encephalon file"))
+    }
+
+    fn expect(&mut self, tag: &str) -> io::Result<()> {
+        let token = self.next()?;
+
+        if token == tag {
+            Ok(())
+        } else {
+            Err(invalid_data(format!(
+                "Expected `{}` but found `{}`",
+                tag, token
+            )))
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(&mut self) -> io::Result<T> {
+        let token = self.next()?;
+
+        token
+            .parse()
+            .map_err(|_| invalid_data(format!("Invalid number `{}`", token)))
+    }
+
+    fn f32(&mut self) -> io::Result<f32> {
+        f32_from_token(self.next()?)
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        match self.next()? {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            token => Err(invalid_data(format!("Invalid flag `{}`", token))),
+        }
+    }
+
+    fn loc(&mut self) -> io::Result<Vec<i32>> {
+        let token = self.next()?;
+
+        let inner = token
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .ok_or_else(|| invalid_data(format!("Invalid location `{}`", token)))?;
+
+        if inner.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        inner
+            .split(',')
+            .map(|coord| {
+                coord
+                    .parse()
+                    .map_err(|_| invalid_data(format!("Invalid location `{}`", token)))
+            })
+            .collect()
+    }
+
+    fn name(&mut self) -> io::Result<String> {
+        let token = self.next()?;
+        let mut bytes = Vec::with_capacity(token.len());
+        let mut raw = token.bytes();
+
+        while let Some(byte) = raw.next() {
+            if byte == b'%' {
+                let hex = [
+                    raw.next().unwrap_or_default(),
+                    raw.next().unwrap_or_default(),
+                ];
+                let decoded = std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| invalid_data(format!("Invalid name `{}`", token)))?;
+
+                bytes.push(decoded);
+            } else {
+                bytes.push(byte);
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| invalid_data(format!("Invalid name `{}`", token)))
+    }
+
+    fn cycle(&mut self) -> io::Result<ChargeCycle> {
+        match self.next()? {
+            "even" => Ok(ChargeCycle::Even),
+            "odd" => Ok(ChargeCycle::Odd),
+            token => Err(invalid_data(format!("Invalid cycle `{}`", token))),
+        }
+    }
+
+    /// Number of neurons in a section, which has to be `expected`: the plastic neurons
+    /// of the geometry, or the sensors and actuators the encephalon is loaded with
+    fn count(&mut self, section: &str, expected: usize) -> io::Result<usize> {
+        self.expect(section)?;
+
+        let count: usize = self.parse()?;
+        if count != expected {
+            return Err(invalid_data(format!(
+                "Expected {} {} neurons, found {}",
+                expected, section, count
+            )));
+        }
+
+        Ok(count)
+    }
+
+    pub(crate) fn header(&mut self) -> io::Result<GeometryRecord> {
+        self.expect(MAGIC)?;
+
+        let version: u32 = self.parse()?;
+        if version != FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "Unsupported encephalon format version {} (expected {})",
+                version, FORMAT_VERSION
+            )));
+        }
+
+        self.expect("geometry")?;
+
+        Ok(GeometryRecord {
+            num_plastic: self.parse()?,
+            num_actuator: self.parse()?,
+            num_sensory: self.parse()?,
+            nearby_count: self.parse()?,
+        })
+    }
+
+    /// Fire receipts, followed by the fire history with STDP and the recent activity
+    /// with structural plasticity
+    fn fire_record(&mut self, stdp: bool, structural: bool) -> io::Result<FireRecord> {
+        self.expect("receipts")?;
+
+        let even = FireReceipt::new(self.bool()?, self.f32()?);
+        let odd = FireReceipt::new(self.bool()?, self.f32()?);
+
+        let last_fire = self.optional_u64()?;
+        let history = self.history(stdp)?;
+
+        let activity = if structural {
+            self.expect("activity")?;
+            let token = self.next()?;
+            u64::from_str_radix(token, 16)
+                .map_err(|_| invalid_data(format!("Invalid activity `{}`", token)))?
+        } else {
+            0
+        };
+
+        Ok(FireRecord {
+            receipts: (even, odd),
+            last_fire,
+            history,
+            activity,
+        })
+    }
+
+    /// A cycle number, or `none`
+    fn optional_u64(&mut self) -> io::Result<Option<u64>> {
+        match self.next()? {
+            "none" => Ok(None),
+            token => token
+                .parse()
+                .map(Some)
+                .map_err(|_| invalid_data(format!("Invalid number `{}`", token))),
+        }
+    }
+
+    fn refractory(&mut self) -> io::Result<Option<Refractory>> {
+        self.expect("refractory")?;
+
+        match self.next()? {
+            "none" => Ok(None),
+            token => {
+                let absolute = token
+                    .parse()
+                    .map_err(|_| invalid_data(format!("Invalid number `{}`", token)))?;
+
+                Ok(Some(Refractory::new(absolute, self.parse()?, self.f32()?)))
+            }
+        }
+    }
+
+    fn leaky_integration(&mut self) -> io::Result<Option<LeakyIntegration>> {
+        self.expect("leak")?;
+
+        Ok(self.optional_f32()?.map(LeakyIntegration::new))
+    }
+
+    fn synaptic_delay(&mut self) -> io::Result<SynapticDelay> {
+        self.expect("delay")?;
+
+        let synaptic_delay = match self.next()? {
+            "fixed" => SynapticDelay::Fixed(self.parse()?),
+            "distance" => SynapticDelay::Distance {
+                distance_per_cycle: self.f32()?,
+                max_delay: self.parse()?,
+            },
+            token => return Err(invalid_data(format!("Invalid synaptic delay `{}`", token))),
+        };
+
+        if synaptic_delay.delay(0.) == 0 {
+            return Err(invalid_data("Synaptic delays must be at least one cycle"));
+        }
+
+        Ok(synaptic_delay)
+    }
+
+    /// Parameters for sensors and actuators added later, using `learning_rule` for
+    /// sensory neurons
+    fn template(&mut self, learning_rule: &Arc<dyn LearningRule>) -> io::Result<NeuronTemplate> {
+        self.expect("template")?;
+
+        let actuator_charge_bins = self.parse()?;
+        if actuator_charge_bins == 0 {
+            return Err(invalid_data("Neurons must have at least one charge bin"));
+        }
+
+        Ok(NeuronTemplate {
+            sensory_learning_rule: Arc::clone(learning_rule),
+            actuator_charge_bins,
+            actuator_fire_threshold: self.f32()?,
+            actuator_aggregation: self.aggregation_tokens()?,
+            synaptic_type_ratio: self.f32()?,
+            synapse_weight_ranges: (self.f32()?, self.f32()?),
+        })
+    }
+
+    /// Structural plasticity along with the changes it has made so far
+    fn structural(&mut self) -> io::Result<Option<(StructuralPlasticity, StructuralChanges)>> {
+        self.expect("structural")?;
+
+        let interval = match self.next()? {
+            "none" => return Ok(None),
+            token => token
+                .parse()
+                .map_err(|_| invalid_data(format!("Invalid number `{}`", token)))?,
+        };
+
+        let structural = StructuralPlasticity::new(
+            interval,
+            self.f32()?,
+            self.parse()?,
+            self.parse()?,
+            self.f32()?,
+            self.f32()?,
+        );
+        let changes = StructuralChanges {
+            pruned: self.parse()?,
+            grown: self.parse()?,
+        };
+
+        Ok(Some((structural, changes)))
+    }
+
+    fn stdp(&mut self) -> io::Result<Option<Stdp>> {
+        self.expect("stdp")?;
+
+        let potentiation = match self.next()? {
+            "none" => return Ok(None),
+            token => f32_from_token(token)?,
+        };
+
+        Ok(Some(Stdp::new(
+            potentiation,
+            self.f32()?,
+            self.f32()?,
+            self.f32()?,
+            self.parse()?,
+        )))
+    }
+
+    /// A float, or `none`
+    fn optional_f32(&mut self) -> io::Result<Option<f32>> {
+        match self.next()? {
+            "none" => Ok(None),
+            token => f32_from_token(token).map(Some),
+        }
+    }
+
+    fn regulation(&mut self) -> io::Result<WeightRegulation> {
+        self.expect("regulation")?;
+
+        let max_weight = self.optional_f32()?;
+
+        let direction = match self.next()? {
+            "none" => None,
+            "incoming" => Some(NormalizationDirection::Incoming),
+            "outgoing" => Some(NormalizationDirection::Outgoing),
+            token => return Err(invalid_data(format!("Invalid normalization `{}`", token))),
+        };
+        let normalization = match direction {
+            Some(direction) => Some(Normalization::new(direction, self.f32()?, self.parse()?)),
+            None => None,
+        };
+
+        let homeostasis = match self.optional_f32()? {
+            Some(target_rate) => Some(Homeostasis::new(
+                target_rate,
+                self.f32()?,
+                self.f32()?,
+                self.parse()?,
+            )),
+            None => None,
+        };
+
+        Ok(WeightRegulation {
+            max_weight,
+            normalization,
+            homeostasis,
+        })
+    }
+
+    fn adaptive_threshold(&mut self) -> io::Result<Option<AdaptiveThreshold>> {
+        self.expect("threshold")?;
+
+        match self.optional_f32()? {
+            Some(increment) => Ok(Some(AdaptiveThreshold::new(increment, self.f32()?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Threshold rise of a plastic or actuator neuron, only present with adaptive
+    /// thresholds
+    fn threshold_rise(&mut self, adaptive: bool) -> io::Result<f32> {
+        if !adaptive {
+            return Ok(0.);
+        }
+
+        self.expect("rise")?;
+        self.f32()
+    }
+
+    /// Fire history following receipts, only present with STDP
+    fn history(&mut self, stdp: bool) -> io::Result<Vec<u64>> {
+        if !stdp {
+            return Ok(Vec::new());
+        }
+
+        self.expect("history")?;
+
+        // Grown as entries are read rather than sized from the file, like every other
+        // length in it
+        let len: usize = self.parse()?;
+        let mut history = Vec::new();
+        for _ in 0..len {
+            history.push(self.parse()?);
+        }
+
+        Ok(history)
+    }
+
+    fn aggregation(&mut self) -> io::Result<Aggregation> {
+        self.expect("aggregation")?;
+
+        self.aggregation_tokens()
+    }
+
+    fn aggregation_tokens(&mut self) -> io::Result<Aggregation> {
+        let measure = match self.next()? {
+            "average" => MeasureAggregation::WeightedAverage,
+            "median" => MeasureAggregation::WeightedMedian,
+            "mode" => MeasureAggregation::Mode,
+            "softmax" => MeasureAggregation::Softmax {
+                temperature: self.f32()?,
+            },
+            token => return Err(invalid_data(format!("Invalid aggregation `{}`", token))),
+        };
+        let inhibition = match self.next()? {
+            "subtractive" => Inhibition::Subtractive,
+            "shunting" => Inhibition::Shunting,
+            token => return Err(invalid_data(format!("Invalid inhibition `{}`", token))),
+        };
+
+        Ok(Aggregation::new(measure, inhibition))
+    }
+
+    fn charge(&mut self) -> io::Result<ChargeRecord> {
+        let aggregation = self.aggregation()?;
+        let shunting = matches!(aggregation.inhibition, Inhibition::Shunting);
+
+        self.expect("charge")?;
+
+        let bins: u8 = self.parse()?;
+        if bins == 0 {
+            return Err(invalid_data("Neurons must have at least one charge bin"));
+        }
+
+        let max_delay: u8 = self.parse()?;
+        if max_delay == 0 {
+            return Err(invalid_data("Synaptic delays must be at least one cycle"));
+        }
+
+        let cycles = (0..=max_delay)
+            .map(|_| -> io::Result<(Vec<f32>, Vec<f32>, f32)> {
+                let charges = (0..bins).map(|_| self.f32()).collect::<io::Result<_>>()?;
+                let weights = (0..bins).map(|_| self.f32()).collect::<io::Result<_>>()?;
+                let shunt = if shunting { self.f32()? } else { 0. };
+
+                Ok((charges, weights, shunt))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(ChargeRecord {
+            bins,
+            aggregation,
+            cycles,
+        })
+    }
+
+    /// Plastic synapses carry their weak checks with structural plasticity, and static
+    /// synapses their reflex id
+    fn synapses(
+        &mut self,
+        structural: bool,
+    ) -> io::Result<(Vec<SynapseRecord>, Vec<SynapseRecord>)> {
+        self.expect("synapses")?;
+
+        let num_plastic: usize = self.parse()?;
+        let num_static: usize = self.parse()?;
+
+        let mut read_synapse = |weak_checks: bool, reflex: bool| -> io::Result<SynapseRecord> {
+            self.expect("synapse")?;
+
+            let synapse_type = match self.next()? {
+                "e" => SynapseType::Excitatory,
+                "i" => SynapseType::Inhibitory,
+                token => return Err(invalid_data(format!("Invalid synapse type `{}`", token))),
+            };
+            let weight = self.f32()?;
+            let target_kind = match self.next()? {
+                "p" => RxKind::Plastic,
+                "a" => RxKind::Actuator,
+                token => return Err(invalid_data(format!("Invalid synapse target `{}`", token))),
+            };
+
+            let target_loc = self.loc()?;
+            let delay = self.parse()?;
+            if delay == 0 {
+                return Err(invalid_data("Synaptic delays must be at least one cycle"));
+            }
+            let weak_checks = if weak_checks { self.parse()? } else { 0 };
+            let reflex = if reflex {
+                Some(ReflexId(self.parse()?))
+            } else {
+                None
+            };
+
+            Ok(SynapseRecord {
+                synapse_type,
+                weight,
+                target_kind,
+                target_loc,
+                delay,
+                weak_checks,
+                reflex,
+            })
+        };
+
+        let mut plastic_synapses = Vec::new();
+        for _ in 0..num_plastic {
+            plastic_synapses.push(read_synapse(structural, false)?);
+        }
+        let mut static_synapses = Vec::new();
+        for _ in 0..num_static {
+            static_synapses.push(read_synapse(false, true)?);
+        }
+
+        Ok((plastic_synapses, static_synapses))
+    }
+}
+
+/// Restores the neurons, synapses and interfaces of a saved encephalon into `encephalon`,
+/// which must have been created with the saved geometry and no neurons.
+pub(crate) fn read_neurons(
+    reader: &mut Reader,
+    encephalon: &mut Encephalon,
+    sensors: Vec<Arc<dyn Sensor>>,
+    actuators: Vec<Arc<dyn Actuator>>,
+    learning_rule: Arc<dyn LearningRule>,
+) -> io::Result<()> {
+    reader.expect("cycle")?;
+    encephalon.cycle = reader.cycle()?;
+    encephalon.neurons.cycle_number = reader.parse()?;
+    encephalon.neurons.stdp = reader.stdp()?;
+    let regulation = reader.regulation()?;
+    encephalon.neurons.set_weight_regulation(regulation);
+    let adaptive_threshold = reader.adaptive_threshold()?;
+    encephalon
+        .neurons
+        .set_adaptive_threshold(adaptive_threshold);
+    let refractory = reader.refractory()?;
+    encephalon.neurons.set_refractory(refractory);
+    let leaky_integration = reader.leaky_integration()?;
+    encephalon.neurons.set_leaky_integration(leaky_integration);
+    encephalon.synaptic_delay = reader.synaptic_delay()?;
+    if let Some((structural, changes)) = reader.structural()? {
+        encephalon.structural_plasticity = Some(structural);
+        encephalon.structural_changes = changes;
+    }
+    encephalon.template = reader.template(&learning_rule)?;
+    let structural = encephalon.structural_plasticity.is_some();
+    let stdp = encephalon.neurons.stdp().is_some();
+    let adaptive = encephalon.neurons.adaptive_threshold().is_some();
+    let homeostasis = encephalon.neurons.weight_regulation().homeostasis.is_some();
+
+    // Counts are checked before anything is read, so a corrupted count can't make
+    // the reader allocate or loop for neurons that aren't there
+    let num_plastic = reader.count(
+        "plastic",
+        encephalon.ecp_geometry.get_num_plastic() as usize,
+    )?;
+    let mut plastic_records = Vec::new();
+    for _ in 0..num_plastic {
+        reader.expect("neuron")?;
+        let loc = reader.loc()?;
+        let fire_threshold = reader.f32()?;
+        let fire = reader.fire_record(stdp, structural)?;
+        let firing_rate = if homeostasis {
+            reader.expect("rate")?;
+            Some(reader.f32()?)
+        } else {
+            None
+        };
+        let threshold_rise = reader.threshold_rise(adaptive)?;
+        let charge = reader.charge()?;
+        let (plastic_synapses, static_synapses) = reader.synapses(structural)?;
+
+        plastic_records.push(PlasticRecord {
+            loc,
+            fire_threshold,
+            fire,
+            firing_rate,
+            threshold_rise,
+            charge,
+            plastic_synapses,
+            static_synapses,
+        });
+    }
+
+    let num_actuator = reader.count("actuator", actuators.len())?;
+    let mut actuator_records = Vec::new();
+    for _ in 0..num_actuator {
+        reader.expect("neuron")?;
+
+        actuator_records.push(ActuatorRecord {
+            loc: reader.loc()?,
+            name: reader.name()?,
+            fire_threshold: reader.f32()?,
+            measure: reader.f32()?,
+            fire: reader.fire_record(stdp, structural)?,
+            threshold_rise: reader.threshold_rise(adaptive)?,
+            charge: reader.charge()?,
+        });
+    }
+
+    let num_sensory = reader.count("sensory", sensors.len())?;
+    let mut sensory_records = Vec::new();
+    for _ in 0..num_sensory {
+        reader.expect("neuron")?;
+        let loc = reader.loc()?;
+        let name = reader.name()?;
+        let measure = reader.f32()?;
+        let fire = reader.fire_record(stdp, structural)?;
+        let (plastic_synapses, static_synapses) = reader.synapses(structural)?;
+
+        sensory_records.push(SensoryRecord {
+            loc,
+            name,
+            measure,
+            fire,
+            plastic_synapses,
+            static_synapses,
+        });
+    }
+
+    if reader.tokens.next().is_some() {
+        return Err(invalid_data("Unexpected trailing data in encephalon file"));
+    }
+
+    let mut sensors = sensors
+        .into_iter()
+        .map(|sensor| (sensor.get_name(), sensor))
+        .collect::<IndexMap<String, Arc<dyn Sensor>>>();
+    let mut actuators = actuators
+        .into_iter()
+        .map(|actuator| (actuator.get_name(), actuator))
+        .collect::<IndexMap<String, Arc<dyn Actuator>>>();
+
+    // First create every neuron that can receive synapses
+    for record in plastic_records.iter() {
+        if encephalon.plastic_neurons.contains_key(&record.loc) {
+            return Err(invalid_data(format!(
+                "Duplicate plastic neuron at {:?}",
+                record.loc
+            )));
+        }
+
+        let neuron = encephalon.neurons.add_plastic_neuron(
+            record.charge.bins,
+            Arc::clone(&learning_rule),
+            record.fire_threshold,
+        );
+        restore_fire_tracker(encephalon.neurons.fire_tracker_mut(neuron), &record.fire);
+        if let Some(rate) = record.firing_rate {
+            encephalon.neurons.restore_firing_rate(neuron, rate);
+        }
+        encephalon
+            .neurons
+            .restore_threshold_rise(neuron, record.threshold_rise);
+        restore_charge(&mut encephalon.neurons, neuron, &record.charge);
+
+        encephalon
+            .plastic_neurons
+            .insert(record.loc.clone(), neuron);
+    }
+
+    for record in actuator_records {
+        if encephalon.actuator_neurons.contains_key(&record.loc) {
+            return Err(invalid_data(format!(
+                "Duplicate actuator neuron at {:?}",
+                record.loc
+            )));
+        }
+
+        let neuron = encephalon
+            .neurons
+            .add_actuator_neuron(record.charge.bins, record.fire_threshold);
+        encephalon
+            .neurons
+            .restore_actuator_measure(neuron, record.measure);
+        encephalon
+            .neurons
+            .restore_threshold_rise(neuron, record.threshold_rise);
+        restore_fire_tracker(encephalon.neurons.fire_tracker_mut(neuron), &record.fire);
+        restore_charge(&mut encephalon.neurons, neuron, &record.charge);
+
+        encephalon
+            .actuator_neurons
+            .insert(record.loc.clone(), neuron);
+
+        let name = record.name;
+        let actuator = actuators
+            .swap_remove(&name)
+            .ok_or_else(|| invalid_data(format!("No actuator named `{}` was provided", name)))?;
+        encephalon
+            .actuator_interfaces
+            .insert(name, ActuatorInterface::new(actuator, neuron));
+    }
+
+    // Then wire up synapses
+    for record in plastic_records {
+        let neuron = *encephalon.plastic_neurons.get(&record.loc).unwrap();
+        let reflexes = reflex_records(
+            encephalon,
+            ReflexSource::Plastic(record.loc),
+            &record.static_synapses,
+        )?;
+        add_synapses(
+            encephalon,
+            neuron,
+            record.plastic_synapses,
+            record.static_synapses,
+        )?;
+        register_reflexes(encephalon, reflexes)?;
+    }
+
+    for record in sensory_records {
+        if encephalon.sensory_neurons.contains_key(&record.loc) {
+            return Err(invalid_data(format!(
+                "Duplicate sensory neuron at {:?}",
+                record.loc
+            )));
+        }
+
+        let neuron = encephalon
+            .neurons
+            .add_sensory_neuron(Arc::clone(&learning_rule));
+        encephalon.neurons.set_measure(neuron, record.measure);
+        restore_fire_tracker(encephalon.neurons.fire_tracker_mut(neuron), &record.fire);
+        let reflexes = reflex_records(
+            encephalon,
+            ReflexSource::Sensor(record.name.clone()),
+            &record.static_synapses,
+        )?;
+        add_synapses(
+            encephalon,
+            neuron,
+            record.plastic_synapses,
+            record.static_synapses,
+        )?;
+        register_reflexes(encephalon, reflexes)?;
+
+        encephalon
+            .sensory_neurons
+            .insert(record.loc.clone(), neuron);
+
+        let name = record.name;
+        let sensor = sensors
+            .swap_remove(&name)
+            .ok_or_else(|| invalid_data(format!("No sensor named `{}` was provided", name)))?;
+        encephalon
+            .sensory_interfaces
+            .insert(name, SensoryInterface::new(sensor, neuron));
+    }
+
+    // Ids grow in the order reflexes were added
+    encephalon.reflexes.sort_keys();
+
+    if let Some(name) = sensors.keys().next() {
+        return Err(invalid_data(format!(
+            "Sensor `{}` is not part of the saved encephalon",
+            name
+        )));
+    }
+
+    if let Some(name) = actuators.keys().next() {
+        return Err(invalid_data(format!(
+            "Actuator `{}` is not part of the saved encephalon",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+fn restore_fire_tracker(fire_tracker: &mut FireTracker, record: &FireRecord) {
+    fire_tracker.receipts = record.receipts;
+    fire_tracker.last_fire = record.last_fire;
+    fire_tracker.restore_history(&record.history);
+    fire_tracker.activity = record.activity;
+}
+
+fn restore_charge(neurons: &mut NeuronArena, neuron: NeuronId, record: &ChargeRecord) {
+    let cycle_number = neurons.cycle_number();
+    let internal_charge = neurons.internal_charge_mut(neuron);
+    internal_charge.set_aggregation(record.aggregation);
+    internal_charge.grow((record.cycles.len() - 1) as u8, cycle_number);
+
+    for (cycle_number, (charges, weights, shunt)) in (cycle_number..).zip(&record.cycles) {
+        let (restored_charges, restored_weights) = internal_charge.cycle_bins_mut(cycle_number);
+
+        restored_charges.copy_from_slice(charges);
+        restored_weights.copy_from_slice(weights);
+        *internal_charge.shunt_mut(cycle_number) = *shunt;
+    }
+}
+
+/// Reflexes behind the static synapses of `source`, whose actuator targets must
+/// already have their interfaces
+fn reflex_records(
+    encephalon: &Encephalon,
+    source: ReflexSource,
+    static_synapses: &[SynapseRecord],
+) -> io::Result<Vec<(ReflexId, Reflex)>> {
+    static_synapses
+        .iter()
+        .map(|synapse| {
+            let target = match synapse.target_kind {
+                RxKind::Plastic => ReflexTarget::Plastic(synapse.target_loc.clone()),
+                RxKind::Actuator => {
+                    let neuron = find_target(encephalon, synapse)?;
+                    let name = encephalon
+                        .actuator_interfaces
+                        .iter()
+                        .find(|(_, interface)| interface.actuator_neuron == neuron)
+                        .map(|(name, _)| name.clone())
+                        .ok_or_else(|| invalid_data("Reflex targets an unnamed actuator"))?;
+
+                    ReflexTarget::Actuator(name)
+                }
+            };
+
+            let mut reflex =
+                Reflex::between(source.clone(), target, synapse.synapse_type, synapse.weight);
+            reflex.delay = synapse.delay;
+
+            // Static synapses are always read along with their reflex id
+            Ok((synapse.reflex.unwrap(), reflex))
+        })
+        .collect()
+}
+
+fn register_reflexes(
+    encephalon: &mut Encephalon,
+    reflexes: Vec<(ReflexId, Reflex)>,
+) -> io::Result<()> {
+    for (id, reflex) in reflexes {
+        encephalon.next_reflex_id = encephalon.next_reflex_id.max(id.0 + 1);
+
+        if encephalon.reflexes.insert(id, reflex).is_some() {
+            return Err(invalid_data(format!("Duplicate reflex id {}", id.0)));
+        }
+    }
+
+    Ok(())
+}
+
+fn add_synapses(
+    encephalon: &mut Encephalon,
+    neuron: NeuronId,
+    plastic_synapses: Vec<SynapseRecord>,
+    static_synapses: Vec<SynapseRecord>,
+) -> io::Result<()> {
+    for synapse in plastic_synapses {
+        let target = find_target(encephalon, &synapse)?;
+        encephalon.neurons.add_plastic_synapse_with_delay(
+            neuron,
+            synapse.weight,
+            synapse.synapse_type,
+            target,
+            synapse.delay,
+        );
+
+        let plastic_synapses = encephalon.neurons.synapses_mut(neuron).0;
+        *plastic_synapses.weak_checks.last_mut().unwrap() = synapse.weak_checks;
+    }
+
+    for synapse in static_synapses {
+        let target = find_target(encephalon, &synapse)?;
+        encephalon.neurons.add_static_synapse_with_delay(
+            neuron,
+            synapse.weight,
+            synapse.synapse_type,
+            target,
+            synapse.delay,
+        );
+    }
+
+    Ok(())
+}
+
+fn find_target(encephalon: &Encephalon, synapse: &SynapseRecord) -> io::Result<NeuronId> {
+    let target = match synapse.target_kind {
+        RxKind::Plastic => encephalon.plastic_neurons.get(&synapse.target_loc),
+        RxKind::Actuator => encephalon.actuator_neurons.get(&synapse.target_loc),
+    };
+
+    target.copied().ok_or_else(|| {
+        invalid_data(format!(
+            "Synapse targets missing neuron at {:?}",
+            synapse.target_loc
+        ))
+    })
+}
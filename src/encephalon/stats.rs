@@ -0,0 +1,102 @@
+//! Per-cycle statistics. The weights of every synapse are copied before the neurons
+//! run, which tells both how much each weight changed and the weight of the impulses
+//! sent, since synapses fire before they learn.
+
+use crate::cycle_stats::CycleStats;
+use crate::encephalon::Encephalon;
+use crate::neuron::{NeuronId, SynapseType};
+
+/// Weights and types of the plastic and then static synapses of a neuron
+pub(crate) struct SourceWeights {
+    pub(crate) neuron: NeuronId,
+    pub(crate) weights: Vec<f32>,
+    pub(crate) types: Vec<SynapseType>,
+}
+
+impl Encephalon {
+    /// In the order of the sensory and then plastic neurons
+    pub(crate) fn weights_before_cycle(&self) -> Vec<SourceWeights> {
+        self.sensory_neurons
+            .values()
+            .chain(self.plastic_neurons.values())
+            .map(|&neuron| {
+                let (plastic_synapses, static_synapses) = self.neurons.synapses(neuron);
+
+                SourceWeights {
+                    neuron,
+                    weights: plastic_synapses
+                        .weights
+                        .iter()
+                        .chain(&static_synapses.weights)
+                        .copied()
+                        .collect(),
+                    types: plastic_synapses
+                        .types
+                        .iter()
+                        .chain(&static_synapses.types)
+                        .copied()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Stats of the cycle just run, before any restructuring
+    pub(crate) fn cycle_stats(&self, before: &[SourceWeights]) -> CycleStats {
+        let mut stats = CycleStats {
+            cycle: self.neurons.cycle_number() - 1,
+            ..CycleStats::default()
+        };
+
+        let mut measures = Vec::new();
+        let classes = [
+            (&self.sensory_neurons, &mut stats.sensory_fired),
+            (&self.plastic_neurons, &mut stats.plastic_fired),
+            (&self.actuator_neurons, &mut stats.actuator_fired),
+        ];
+        for (neurons, fired) in classes {
+            for &neuron in neurons.values() {
+                if let Some(measure) = self.neurons.last_cycle_fire(neuron, self.cycle) {
+                    *fired += 1;
+                    measures.push(measure);
+                }
+            }
+        }
+
+        if !measures.is_empty() {
+            stats.mean_measure = measures.iter().sum::<f32>() / measures.len() as f32;
+            stats.max_measure = measures.iter().copied().fold(f32::MIN, f32::max);
+        }
+
+        for source in before {
+            let (plastic_synapses, static_synapses) = self.neurons.synapses(source.neuron);
+            let weights_after = plastic_synapses
+                .weights
+                .iter()
+                .chain(&static_synapses.weights);
+
+            for (&before, &after) in source.weights.iter().zip(weights_after) {
+                stats.total_weight += after;
+                if after != before {
+                    stats.changed_synapses += 1;
+                    stats.weight_change += (after - before).abs();
+                }
+            }
+
+            if self
+                .neurons
+                .last_cycle_fire(source.neuron, self.cycle)
+                .is_some()
+            {
+                for (&weight, &synapse_type) in source.weights.iter().zip(&source.types) {
+                    match synapse_type {
+                        SynapseType::Excitatory => stats.excitatory_impulse += weight,
+                        SynapseType::Inhibitory => stats.inhibitory_impulse += weight,
+                    }
+                }
+            }
+        }
+
+        stats
+    }
+}
@@ -0,0 +1,118 @@
+//! Structural plasticity: pruning plastic synapses that stay weak and growing new ones
+//! between nearby neurons that fire in step.
+//!
+//! Co-activity is read from the last 64 cycles of activity each fire tracker keeps. A
+//! source and a nearby target are co-active in a cycle when the target fired in it
+//! and the source fired as many cycles before as a synapse between them would delay
+//! its impulses.
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::encephalon::Encephalon;
+use crate::neuron::{NeuronId, SynapseType};
+use crate::structural_plasticity::StructuralPlasticity;
+
+impl Encephalon {
+    /// Restructures at the end of a learning cycle, when the interval is up
+    pub(crate) fn restructure_if_due(&mut self) {
+        if let Some(structural) = self.structural_plasticity {
+            if self.neurons.cycle_number() % structural.interval as u64 == 0 {
+                self.restructure(structural);
+            }
+        }
+    }
+
+    fn restructure(&mut self, structural: StructuralPlasticity) {
+        let pruned = self
+            .neurons
+            .prune_synapses(structural.prune_threshold, structural.prune_after);
+        self.structural_changes.pruned += pruned as u64;
+
+        // Only neurons placed by the geometry have nearby neurons to grow synapses to
+        let mut sources = Vec::new();
+
+        let mut plastic_loc_option = Some(self.ecp_geometry.first_plastic_loc());
+        while let Some(loc) = plastic_loc_option {
+            plastic_loc_option = self.ecp_geometry.next_plastic_loc(&loc);
+            if let Some(&neuron) = self.plastic_neurons.get(&loc) {
+                sources.push((loc, neuron));
+            }
+        }
+
+        let mut laid_out = HashSet::new();
+        let mut sensory_loc_option = Some(self.ecp_geometry.first_sensory_loc());
+        while let Some(loc) = sensory_loc_option {
+            sensory_loc_option = self.ecp_geometry.next_sensory_loc(&loc);
+            if let Some(&neuron) = self.sensory_neurons.get(&loc) {
+                laid_out.insert(loc.clone());
+                sources.push((loc, neuron));
+            }
+        }
+
+        // Sensors added once the laid out locations were taken
+        for (loc, &neuron) in &self.sensory_neurons {
+            if !laid_out.contains(loc) {
+                sources.push((loc.clone(), neuron));
+            }
+        }
+
+        for (loc, neuron) in sources {
+            let source_activity = self.neurons.fire_tracker(neuron).activity;
+            if source_activity == 0 {
+                continue;
+            }
+
+            let (plastic_synapses, static_synapses) = self.neurons.synapses(neuron);
+            let connected = plastic_synapses
+                .targets
+                .iter()
+                .chain(&static_synapses.targets)
+                .copied()
+                .collect::<HashSet<u32>>();
+
+            let (plastic_locs, actuator_locs) = self.ecp_geometry.get_nearby_rx_neurons(&loc);
+            let targets = plastic_locs
+                .iter()
+                .filter_map(|target_loc| {
+                    self.plastic_neurons
+                        .get(target_loc)
+                        .map(|&target| (target_loc, target))
+                })
+                .chain(actuator_locs.iter().filter_map(|target_loc| {
+                    self.actuator_neurons
+                        .get(target_loc)
+                        .map(|&target| (target_loc, target))
+                }))
+                .collect::<Vec<(&Vec<i32>, NeuronId)>>();
+
+            for (target_loc, target) in targets {
+                if connected.contains(&self.neurons.rx_index(target)) {
+                    continue;
+                }
+
+                let delay = self
+                    .synaptic_delay
+                    .delay(self.ecp_geometry.distance(&loc, target_loc));
+                let target_activity = self.neurons.fire_tracker(target).activity;
+                let coactivity = (source_activity.checked_shr(delay as u32).unwrap_or(0)
+                    & target_activity)
+                    .count_ones();
+
+                if coactivity >= structural.growth_coactivity
+                    && self.rng.gen_range(0., 1.) < structural.growth_probability
+                {
+                    self.neurons.add_plastic_synapse_with_delay(
+                        neuron,
+                        structural.growth_weight,
+                        SynapseType::Excitatory,
+                        target,
+                        delay,
+                    );
+                    self.structural_changes.grown += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::reflex::ReflexId;
+
+/// Error type shared by everything in the crate that can be misconfigured
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncephalonError {
+    /// The geometry has a different number of sensory neuron positions than sensors were given
+    SensorCountMismatch { expected: u32, found: usize },
+    /// The geometry has a different number of actuator neuron positions than actuators were given
+    ActuatorCountMismatch { expected: u32, found: usize },
+    /// Two sensors share the same name
+    DuplicateSensorName(String),
+    /// Two actuators share the same name
+    DuplicateActuatorName(String),
+    /// A sensory neuron already exists at this location
+    DuplicateSensorLocation(Vec<i32>),
+    /// A reflex refers to a sensor that isn't part of the encephalon
+    UnknownSensor(String),
+    /// A reflex refers to an actuator that isn't part of the encephalon
+    UnknownActuator(String),
+    /// A reflex refers to a location without a plastic neuron
+    UnknownPlasticNeuron(Vec<i32>),
+    /// No reflex in the encephalon has this id
+    UnknownReflex(ReflexId),
+    /// The geometry has no room left for another `sensory` or `actuator` neuron
+    NoVacantLocation(&'static str),
+    /// A neuron or reflex parameter is out of its valid range
+    InvalidParameter {
+        parameter: &'static str,
+        reason: String,
+    },
+    /// A geometry parameter can't be laid out
+    InvalidGeometry {
+        parameter: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for EncephalonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncephalonError::SensorCountMismatch { expected, found } => write!(
+                f,
+                "The number of sensors passed to the encephalon ({}) doesn't match the number \
+                 of sensor neuron positions within the specified ecp_geometry ({})",
+                found, expected
+            ),
+            EncephalonError::ActuatorCountMismatch { expected, found } => write!(
+                f,
+                "The number of actuators passed to the encephalon ({}) doesn't match the number \
+                 of actuator neuron positions within the specified ecp_geometry ({})",
+                found, expected
+            ),
+            EncephalonError::DuplicateSensorName(name) => {
+                write!(f, "More than one sensor is named `{}`", name)
+            }
+            EncephalonError::DuplicateActuatorName(name) => {
+                write!(f, "More than one actuator is named `{}`", name)
+            }
+            EncephalonError::DuplicateSensorLocation(loc) => {
+                write!(f, "Sensor with this {:?} id already exists", loc)
+            }
+            EncephalonError::UnknownSensor(name) => {
+                write!(f, "No sensor named `{}` exists in the encephalon", name)
+            }
+            EncephalonError::UnknownActuator(name) => {
+                write!(f, "No actuator named `{}` exists in the encephalon", name)
+            }
+            EncephalonError::UnknownPlasticNeuron(loc) => {
+                write!(f, "No plastic neuron exists at {:?}", loc)
+            }
+            EncephalonError::UnknownReflex(id) => {
+                write!(f, "No reflex with id {} exists in the encephalon", id.0)
+            }
+            EncephalonError::NoVacantLocation(kind) => {
+                write!(
+                    f,
+                    "The geometry has no room left for another {} neuron",
+                    kind
+                )
+            }
+            EncephalonError::InvalidParameter { parameter, reason } => {
+                write!(f, "Invalid `{}`: {}", parameter, reason)
+            }
+            EncephalonError::InvalidGeometry { parameter, reason } => {
+                write!(f, "Invalid geometry `{}`: {}", parameter, reason)
+            }
+        }
+    }
+}
+
+impl Error for EncephalonError {}
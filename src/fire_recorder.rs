@@ -0,0 +1,313 @@
+//! Opt-in recording of which neurons fired on which cycle, see
+//! `Encephalon::set_fire_recorder`.
+//!
+//! Events are either kept in memory, the oldest dropped once the buffer is full, or
+//! written out as they happen. Both CSV and a compact binary format can be written;
+//! the binary format is a magic header followed by one record per event:
+//!
+//! ```text
+//! cycle: u64, kind: u8, dimensions: u8, location: [i32; dimensions], measure: f32
+//! ```
+//!
+//! with every number little endian, and kinds numbered as `NeuronKind::code`.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+const BINARY_MAGIC: &[u8; 8] = b"aesirfr1";
+const CSV_HEADER: &str = "cycle,kind,location,measure\n";
+
+/// Kind of neuron that fired
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NeuronKind {
+    Sensory,
+    Plastic,
+    Actuator,
+}
+
+impl NeuronKind {
+    /// Code of the kind in the binary format
+    pub fn code(self) -> u8 {
+        match self {
+            NeuronKind::Sensory => 0,
+            NeuronKind::Plastic => 1,
+            NeuronKind::Actuator => 2,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<NeuronKind> {
+        match code {
+            0 => Some(NeuronKind::Sensory),
+            1 => Some(NeuronKind::Plastic),
+            2 => Some(NeuronKind::Actuator),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            NeuronKind::Sensory => "sensory",
+            NeuronKind::Plastic => "plastic",
+            NeuronKind::Actuator => "actuator",
+        }
+    }
+}
+
+/// A neuron firing, with the cycle number it fired in and the measure it fired with
+#[derive(Clone, Debug, PartialEq)]
+pub struct FireEvent {
+    pub cycle: u64,
+    pub loc: Vec<i32>,
+    pub kind: NeuronKind,
+    pub measure: f32,
+}
+
+/// Format events are written in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FireFormat {
+    Csv,
+    Binary,
+}
+
+enum Sink {
+    Buffer {
+        events: VecDeque<FireEvent>,
+        capacity: usize,
+    },
+    Stream {
+        writer: Box<dyn Write + Send + Sync>,
+        format: FireFormat,
+        // First error writing hit, after which nothing more is written
+        error: Option<io::Error>,
+    },
+}
+
+/// Collects the fire events of an encephalon as it runs cycles
+pub struct FireRecorder {
+    sink: Sink,
+    recorded: u64,
+    dropped: u64,
+}
+
+impl FireRecorder {
+    /// Keeps the latest `capacity` events in memory. Panics if `capacity` is 0
+    pub fn buffer(capacity: usize) -> FireRecorder {
+        assert!(capacity > 0, "Fire recorders must hold at least one event");
+
+        FireRecorder {
+            sink: Sink::Buffer {
+                events: VecDeque::new(),
+                capacity,
+            },
+            recorded: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Writes every event to `writer` as it's recorded, starting with the header of
+    /// `format`. Errors writing events are kept until `finish`
+    pub fn stream(
+        mut writer: Box<dyn Write + Send + Sync>,
+        format: FireFormat,
+    ) -> io::Result<FireRecorder> {
+        write_header(&mut writer, format)?;
+
+        Ok(FireRecorder {
+            sink: Sink::Stream {
+                writer,
+                format,
+                error: None,
+            },
+            recorded: 0,
+            dropped: 0,
+        })
+    }
+
+    pub(crate) fn record(&mut self, event: FireEvent) {
+        self.recorded += 1;
+
+        match &mut self.sink {
+            Sink::Buffer { events, capacity } => {
+                if events.len() == *capacity {
+                    events.pop_front();
+                    self.dropped += 1;
+                }
+                events.push_back(event);
+            }
+            Sink::Stream {
+                writer,
+                format,
+                error,
+            } => {
+                if error.is_some() {
+                    self.dropped += 1;
+                } else if let Err(err) = write_event(writer, *format, &event) {
+                    *error = Some(err);
+                    self.dropped += 1;
+                }
+            }
+        }
+    }
+
+    /// Number of events recorded so far, dropped ones included
+    pub fn recorded(&self) -> u64 {
+        self.recorded
+    }
+
+    /// Number of events pushed out of a full buffer, or lost to a failed write
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Buffered events, oldest first. Always empty when streaming
+    pub fn events(&self) -> impl Iterator<Item = &FireEvent> {
+        let events = match &self.sink {
+            Sink::Buffer { events, .. } => Some(events.iter()),
+            Sink::Stream { .. } => None,
+        };
+
+        events.into_iter().flatten()
+    }
+
+    /// Takes the buffered events out, oldest first
+    pub fn drain(&mut self) -> Vec<FireEvent> {
+        match &mut self.sink {
+            Sink::Buffer { events, .. } => events.drain(..).collect(),
+            Sink::Stream { .. } => Vec::new(),
+        }
+    }
+
+    /// Flushes a stream, returning the first error hit writing to it
+    pub fn finish(self) -> io::Result<()> {
+        match self.sink {
+            Sink::Buffer { .. } => Ok(()),
+            Sink::Stream {
+                mut writer, error, ..
+            } => match error {
+                Some(err) => Err(err),
+                None => writer.flush(),
+            },
+        }
+    }
+}
+
+fn write_header<W: Write + ?Sized>(writer: &mut W, format: FireFormat) -> io::Result<()> {
+    match format {
+        FireFormat::Csv => writer.write_all(CSV_HEADER.as_bytes()),
+        FireFormat::Binary => writer.write_all(BINARY_MAGIC),
+    }
+}
+
+fn write_event<W: Write + ?Sized>(
+    writer: &mut W,
+    format: FireFormat,
+    event: &FireEvent,
+) -> io::Result<()> {
+    match format {
+        FireFormat::Csv => {
+            let loc = event
+                .loc
+                .iter()
+                .map(|coordinate| coordinate.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                event.cycle,
+                event.kind.name(),
+                loc,
+                event.measure
+            )
+        }
+        FireFormat::Binary => {
+            if event.loc.len() > u8::MAX as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Locations can have at most 255 dimensions",
+                ));
+            }
+
+            writer.write_all(&event.cycle.to_le_bytes())?;
+            writer.write_all(&[event.kind.code(), event.loc.len() as u8])?;
+            for coordinate in &event.loc {
+                writer.write_all(&coordinate.to_le_bytes())?;
+            }
+            writer.write_all(&event.measure.to_le_bytes())
+        }
+    }
+}
+
+/// Writes `events` as CSV with a `cycle,kind,location,measure` header, where the
+/// coordinates of each location are separated by spaces
+pub fn write_csv<'a, W: Write>(
+    writer: &mut W,
+    events: impl IntoIterator<Item = &'a FireEvent>,
+) -> io::Result<()> {
+    write_events(writer, FireFormat::Csv, events)
+}
+
+/// Writes `events` in the binary format, see the module docs
+pub fn write_binary<'a, W: Write>(
+    writer: &mut W,
+    events: impl IntoIterator<Item = &'a FireEvent>,
+) -> io::Result<()> {
+    write_events(writer, FireFormat::Binary, events)
+}
+
+fn write_events<'a, W: Write>(
+    writer: &mut W,
+    format: FireFormat,
+    events: impl IntoIterator<Item = &'a FireEvent>,
+) -> io::Result<()> {
+    write_header(writer, format)?;
+    for event in events {
+        write_event(writer, format, event)?;
+    }
+
+    Ok(())
+}
+
+/// Reads back events written in the binary format
+pub fn read_binary<R: Read>(reader: &mut R) -> io::Result<Vec<FireEvent>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if !bytes.starts_with(BINARY_MAGIC) {
+        return Err(invalid_data("Not a binary fire recording"));
+    }
+
+    let mut rest = &bytes[BINARY_MAGIC.len()..];
+    let mut events = Vec::new();
+    while !rest.is_empty() {
+        let cycle = u64::from_le_bytes(read_array(&mut rest)?);
+        let [code, dimensions] = read_array(&mut rest)?;
+        let kind = NeuronKind::from_code(code)
+            .ok_or_else(|| invalid_data("Invalid neuron kind in fire recording"))?;
+        let loc = (0..dimensions)
+            .map(|_| Ok(i32::from_le_bytes(read_array(&mut rest)?)))
+            .collect::<io::Result<Vec<i32>>>()?;
+        let measure = f32::from_le_bytes(read_array(&mut rest)?);
+
+        events.push(FireEvent {
+            cycle,
+            loc,
+            kind,
+            measure,
+        });
+    }
+
+    Ok(events)
+}
+
+fn read_array<const N: usize>(reader: &mut &[u8]) -> io::Result<[u8; N]> {
+    let mut array = [0; N];
+    reader.read_exact(&mut array)?;
+
+    Ok(array)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
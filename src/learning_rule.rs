@@ -0,0 +1,148 @@
+use crate::neuron::{FireReceipt, SynapseType};
+
+/// Everything a learning rule knows about a synapse whose target fired right after
+/// its source did
+#[derive(Copy, Clone, Debug)]
+pub struct LearningInput {
+    /// Current weight of the synapse
+    pub weight: f32,
+    pub synapse_type: SynapseType,
+    /// Receipt of the source neuron firing
+    pub pre_fire_receipt: FireReceipt,
+    /// Receipt of the target neuron firing, one cycle after the source
+    pub post_fire_receipt: FireReceipt,
+    /// Number of the cycle being run, counted from 0
+    pub cycle: u64,
+}
+
+/// Decides how the weight of a synapse changes when its source and target fire back
+/// to back. Learning rules are shared by every neuron they're given to, possibly
+/// across threads.
+///
+/// Closures taking a `&LearningInput` are learning rules, and `WeightModifier` turns
+/// a plain `fn(target_measure, synapse_measure)` into one.
+pub trait LearningRule: Send + Sync {
+    /// Change in weight. The resulting weight is clamped to be non-negative
+    fn weight_change(&self, input: &LearningInput) -> f32;
+}
+
+impl<F> LearningRule for F
+where
+    F: Fn(&LearningInput) -> f32 + Send + Sync,
+{
+    fn weight_change(&self, input: &LearningInput) -> f32 {
+        self(input)
+    }
+}
+
+/// Learning rule that only looks at the measures the two neurons fired with
+#[derive(Copy, Clone)]
+pub struct WeightModifier(pub fn(target_measure: f32, synapse_measure: f32) -> f32);
+
+impl LearningRule for WeightModifier {
+    fn weight_change(&self, input: &LearningInput) -> f32 {
+        (self.0)(
+            input.post_fire_receipt.measure(),
+            input.pre_fire_receipt.measure(),
+        )
+    }
+}
+
+/// `basic_weight_modifier` with parameters: synapses between neurons that fire with
+/// close measures are strengthened, the others weakened.
+///
+/// The change decays exponentially with the distance between the two measures, by a
+/// factor of e every `width`, and turns negative past 1.5 widths. It's scaled so that
+/// identical measures change the weight by `learning_rate`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BasicLearningRule {
+    pub learning_rate: f32,
+    pub width: f32,
+}
+
+impl BasicLearningRule {
+    pub fn new(learning_rate: f32, width: f32) -> BasicLearningRule {
+        BasicLearningRule {
+            learning_rate,
+            width,
+        }
+    }
+}
+
+impl Default for BasicLearningRule {
+    /// Same curve as `basic_weight_modifier`
+    fn default() -> BasicLearningRule {
+        BasicLearningRule::new(1., 1. / 15.)
+    }
+}
+
+impl LearningRule for BasicLearningRule {
+    fn weight_change(&self, input: &LearningInput) -> f32 {
+        let x = (input.post_fire_receipt.measure() - input.pre_fire_receipt.measure()).abs();
+        let floor = (-1.5_f32).exp();
+
+        self.learning_rate * ((-x / self.width).exp() - floor) / (1. - floor)
+    }
+}
+
+/// Spike-timing-dependent plasticity, used in place of the learning rules of every
+/// neuron when an encephalon is built with it.
+///
+/// Neurons remember the cycles they fired in over the last `window` cycles. A synapse
+/// is potentiated for every time its source fired before its target and depressed for
+/// every time its target fired before its source, by an amount that decays
+/// exponentially with the number of cycles between the two fires. Fires further than
+/// `window` cycles apart, or in the same cycle, are ignored.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stdp {
+    /// Largest weight increase, for a source firing right before its target
+    pub potentiation: f32,
+    /// Largest weight decrease, for a target firing right before its source
+    pub depression: f32,
+    /// Number of cycles over which potentiation decays by a factor of e
+    pub potentiation_time_constant: f32,
+    /// Number of cycles over which depression decays by a factor of e
+    pub depression_time_constant: f32,
+    /// Number of cycles of fire history kept by each neuron
+    pub window: u32,
+}
+
+impl Stdp {
+    pub fn new(
+        potentiation: f32,
+        depression: f32,
+        potentiation_time_constant: f32,
+        depression_time_constant: f32,
+        window: u32,
+    ) -> Stdp {
+        Stdp {
+            potentiation,
+            depression,
+            potentiation_time_constant,
+            depression_time_constant,
+            window,
+        }
+    }
+
+    /// Weight change for a pair of fires, where `delay` is the cycle the target fired in
+    /// minus the cycle the source fired in
+    pub fn weight_change(&self, delay: i64) -> f32 {
+        if delay == 0 || delay.unsigned_abs() > self.window as u64 {
+            return 0.;
+        }
+
+        if delay > 0 {
+            self.potentiation * (-(delay as f32) / self.potentiation_time_constant).exp()
+        } else {
+            -self.depression * (delay as f32 / self.depression_time_constant).exp()
+        }
+    }
+}
+
+impl Default for Stdp {
+    /// Slightly stronger depression than potentiation, so that synapses between
+    /// unrelated neurons weaken over time
+    fn default() -> Stdp {
+        Stdp::new(0.5, 0.55, 4., 4., 20)
+    }
+}
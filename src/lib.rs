@@ -1,7 +1,15 @@
 pub mod actuator;
+pub mod cycle_stats;
 pub mod ecp_geometry;
 pub mod encephalon;
+pub mod error;
+pub mod fire_recorder;
+pub mod learning_rule;
 pub mod neuron;
 pub mod neuron_interfaces;
+pub mod observer;
 pub mod reflex;
 pub mod sensor;
+pub mod structural_plasticity;
+pub mod synapse_snapshot;
+pub mod weight_regulation;
@@ -1,126 +1,1056 @@
-use std::borrow::BorrowMut;
-use std::cell::{Ref, RefCell, RefMut};
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::collections::VecDeque;
+use std::sync::Arc;
 
-/// For better documentation of everything, see the eywa library
-/// Most of the names here are equivalent
+use crate::learning_rule::{LearningInput, LearningRule, Stdp};
+use crate::weight_regulation::{NormalizationDirection, WeightRegulation};
 
-pub trait Neuronic {
-    fn run_cycle(&self, cycle: ChargeCycle);
+// For better documentation of everything, see the eywa library
+// Most of the names here are equivalent
 
+/// Identifies a neuron in a `NeuronArena`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NeuronId {
+    Sensory(usize),
+    Plastic(usize),
+    Actuator(usize),
+}
 
-    fn run_static_cycle(&self, cycle: ChargeCycle);
-
-    fn clear(&self);
+/// Owns every neuron of a network in contiguous arrays.
+///
+/// Plastic and actuator neurons share the arrays holding what they receive (their
+/// charge, fire receipts and fire threshold), and synapses refer to their targets by
+/// their index into those arrays, so delivering an impulse is a plain array access.
+#[derive(Default)]
+pub struct NeuronArena {
+    pub(crate) rx: RxNeurons,
+    pub(crate) sensory_neurons: Vec<SensoryNeuron>,
+    pub(crate) plastic_neurons: Vec<PlasticNeuron>,
+    pub(crate) actuator_neurons: Vec<ActuatorNeuron>,
+
+    // Whether each plastic neuron fires, worked out at the start of every cycle
+    pub(crate) evaluations: Vec<Evaluation>,
+
+    // Number of cycles run so far
+    pub(crate) cycle_number: u64,
+
+    // Replaces the learning rules of every neuron when set
+    pub(crate) stdp: Option<Stdp>,
+
+    pub(crate) regulation: WeightRegulation,
+    // Running average of how often each plastic neuron fires, kept with homeostasis
+    pub(crate) firing_rates: Vec<f32>,
 }
 
-pub trait TxNeuronic {
-    fn update_synapses(
-        &self,
+impl NeuronArena {
+    pub fn new() -> NeuronArena {
+        NeuronArena::default()
+    }
+
+    /// Arena whose synapses learn with spike-timing-dependent plasticity instead of the
+    /// learning rules of their neurons
+    pub fn with_stdp(stdp: Stdp) -> NeuronArena {
+        NeuronArena {
+            stdp: Some(stdp),
+            ..NeuronArena::default()
+        }
+    }
+
+    pub fn stdp(&self) -> Option<&Stdp> {
+        self.stdp.as_ref()
+    }
+
+    pub fn weight_regulation(&self) -> &WeightRegulation {
+        &self.regulation
+    }
+
+    /// Bounds, normalizes or scales plastic weights from now on. Firing rates start
+    /// out at the homeostasis target
+    pub fn set_weight_regulation(&mut self, regulation: WeightRegulation) {
+        self.regulation = regulation;
+
+        let initial_rate = self.initial_firing_rate();
+        for rate in self.firing_rates.iter_mut() {
+            *rate = initial_rate;
+        }
+    }
+
+    fn initial_firing_rate(&self) -> f32 {
+        self.regulation
+            .homeostasis
+            .map_or(0., |homeostasis| homeostasis.target_rate)
+    }
+
+    pub fn adaptive_threshold(&self) -> Option<&AdaptiveThreshold> {
+        self.rx.adaptive_threshold.as_ref()
+    }
+
+    /// Makes fire thresholds rise when their neuron fires from now on, or stops
+    /// them from adapting. Thresholds start back at their fire threshold
+    pub fn set_adaptive_threshold(&mut self, adaptive_threshold: Option<AdaptiveThreshold>) {
+        self.rx.set_adaptive_threshold(adaptive_threshold);
+    }
+
+    pub fn leaky_integration(&self) -> Option<&LeakyIntegration> {
+        self.rx.leaky_integration.as_ref()
+    }
+
+    pub fn set_leaky_integration(&mut self, leaky_integration: Option<LeakyIntegration>) {
+        self.rx.leaky_integration = leaky_integration;
+    }
+
+    pub fn refractory(&self) -> Option<&Refractory> {
+        self.rx.refractory.as_ref()
+    }
+
+    /// Gives plastic and actuator neurons a refractory period from now on, or removes it
+    pub fn set_refractory(&mut self, refractory: Option<Refractory>) {
+        self.rx.refractory = refractory;
+    }
+
+    /// Running average of how often a plastic neuron fires, if homeostasis is on
+    pub fn firing_rate(&self, neuron: NeuronId) -> Option<f32> {
+        match neuron {
+            NeuronId::Plastic(i) if self.regulation.homeostasis.is_some() => {
+                Some(self.firing_rates[i])
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of cycles of fire history each neuron keeps
+    fn history_window(&self) -> u32 {
+        self.stdp.map_or(0, |stdp| stdp.window)
+    }
+
+    pub fn add_sensory_neuron(&mut self, learning_rule: Arc<dyn LearningRule>) -> NeuronId {
+        let window = self.history_window();
+        self.sensory_neurons
+            .push(SensoryNeuron::new(learning_rule, window));
+
+        NeuronId::Sensory(self.sensory_neurons.len() - 1)
+    }
+
+    pub fn add_plastic_neuron(
+        &mut self,
+        charge_bins: u8,
+        learning_rule: Arc<dyn LearningRule>,
+        fire_threshold: f32,
+    ) -> NeuronId {
+        let rx = self
+            .rx
+            .push(charge_bins, fire_threshold, self.history_window());
+        self.plastic_neurons
+            .push(PlasticNeuron::new(rx, learning_rule));
+        self.firing_rates.push(self.initial_firing_rate());
+
+        NeuronId::Plastic(self.plastic_neurons.len() - 1)
+    }
+
+    pub fn add_actuator_neuron(&mut self, charge_bins: u8, fire_threshold: f32) -> NeuronId {
+        let rx = self
+            .rx
+            .push(charge_bins, fire_threshold, self.history_window());
+        self.actuator_neurons.push(ActuatorNeuron::new(rx));
+
+        NeuronId::Actuator(self.actuator_neurons.len() - 1)
+    }
+
+    /// Panics if `source` is an actuator neuron or `target` is a sensory neuron
+    pub fn add_plastic_synapse(
+        &mut self,
+        source: NeuronId,
+        weight: f32,
+        synapse_type: SynapseType,
+        target: NeuronId,
+    ) {
+        self.add_plastic_synapse_with_delay(source, weight, synapse_type, target, 1);
+    }
+
+    /// Panics if `source` is an actuator neuron or `target` is a sensory neuron
+    pub fn add_static_synapse(
+        &mut self,
+        source: NeuronId,
+        weight: f32,
+        synapse_type: SynapseType,
+        target: NeuronId,
+    ) {
+        self.add_static_synapse_with_delay(source, weight, synapse_type, target, 1);
+    }
+
+    /// Plastic synapse whose impulses reach `target` `delay` cycles after `source`
+    /// fires. Panics if `delay` is 0, if `source` is an actuator neuron or `target`
+    /// is a sensory neuron
+    pub fn add_plastic_synapse_with_delay(
+        &mut self,
+        source: NeuronId,
+        weight: f32,
+        synapse_type: SynapseType,
+        target: NeuronId,
+        delay: u8,
+    ) {
+        let target = self.delayed_target(target, delay);
+
+        self.synapses_mut(source)
+            .0
+            .push(weight, synapse_type, target, delay);
+    }
+
+    /// Static synapse whose impulses reach `target` `delay` cycles after `source`
+    /// fires. Panics if `delay` is 0, if `source` is an actuator neuron or `target`
+    /// is a sensory neuron
+    pub fn add_static_synapse_with_delay(
+        &mut self,
+        source: NeuronId,
+        weight: f32,
+        synapse_type: SynapseType,
+        target: NeuronId,
+        delay: u8,
+    ) {
+        let target = self.delayed_target(target, delay);
+
+        self.synapses_mut(source)
+            .1
+            .push(weight, synapse_type, target, delay);
+    }
+
+    /// Rx index of `target`, whose charge is grown to take in impulses with `delay`
+    fn delayed_target(&mut self, target: NeuronId, delay: u8) -> u32 {
+        assert!(delay > 0, "Synaptic delays must be at least one cycle");
+
+        let cycle_number = self.cycle_number;
+        let target = self.rx_index(target);
+        self.rx.internal_charges[target as usize].grow(delay, cycle_number);
+
+        target
+    }
+
+    /// Removes a sensory neuron along with its synapses. The sensory neurons after it
+    /// move down one place, keeping their order, so the index of each of their ids
+    /// drops by one. Panics if `neuron` isn't a sensory neuron
+    pub fn remove_sensory_neuron(&mut self, neuron: NeuronId) {
+        let i = match neuron {
+            NeuronId::Sensory(i) => i,
+            _ => panic!("Only sensory neurons can be removed as such"),
+        };
+
+        self.sensory_neurons.remove(i);
+    }
+
+    /// Removes an actuator neuron along with every synapse targeting it. The actuator
+    /// neurons after it move down one place, keeping their order, so the index of each
+    /// of their ids drops by one. Panics if `neuron` isn't an actuator neuron
+    pub fn remove_actuator_neuron(&mut self, neuron: NeuronId) {
+        let i = match neuron {
+            NeuronId::Actuator(i) => i,
+            _ => panic!("Only actuator neurons can be removed as such"),
+        };
+
+        // Every rx slot after the one freed up moves down one place
+        let rx = self.actuator_neurons[i].rx;
+        let shift = |target: &mut u32| {
+            if *target > rx {
+                *target -= 1;
+            }
+        };
+
+        for neuron in self.sensory_neurons.iter_mut() {
+            neuron.plastic_synapses.detach(rx);
+            neuron.static_synapses.detach(rx);
+        }
+        for neuron in self.plastic_neurons.iter_mut() {
+            neuron.plastic_synapses.detach(rx);
+            neuron.static_synapses.detach(rx);
+            shift(&mut neuron.rx);
+        }
+        for neuron in self.actuator_neurons.iter_mut() {
+            shift(&mut neuron.rx);
+        }
+        self.rx.remove(rx as usize);
+
+        self.actuator_neurons.remove(i);
+    }
+
+    /// Panics if `neuron` isn't a sensory neuron
+    pub fn set_measure(&mut self, neuron: NeuronId, measure: f32) {
+        match neuron {
+            NeuronId::Sensory(i) => self.sensory_neurons[i].measure = measure,
+            _ => panic!("Only sensory neurons can be given a measure"),
+        }
+    }
+
+    /// Panics if `neuron` isn't an actuator neuron
+    pub fn read_measure(&self, neuron: NeuronId) -> f32 {
+        match neuron {
+            NeuronId::Actuator(i) => self.actuator_neurons[i].measure,
+            _ => panic!("Only actuator neurons have a measure to read"),
+        }
+    }
+
+    /// Panics if `neuron` isn't a sensory neuron
+    pub(crate) fn sensory_measure(&self, neuron: NeuronId) -> f32 {
+        match neuron {
+            NeuronId::Sensory(i) => self.sensory_neurons[i].measure,
+            _ => panic!("Only sensory neurons are given a measure"),
+        }
+    }
+
+    /// Panics if `neuron` isn't an actuator neuron
+    pub(crate) fn restore_actuator_measure(&mut self, neuron: NeuronId, measure: f32) {
+        match neuron {
+            NeuronId::Actuator(i) => self.actuator_neurons[i].measure = measure,
+            _ => panic!("Only actuator neurons have a measure to restore"),
+        }
+    }
+
+    /// Number of cycles run so far, static cycles included
+    pub fn cycle_number(&self) -> u64 {
+        self.cycle_number
+    }
+
+    pub(crate) fn restore_firing_rate(&mut self, neuron: NeuronId, rate: f32) {
+        if let NeuronId::Plastic(i) = neuron {
+            self.firing_rates[i] = rate;
+        }
+    }
+
+    pub fn run_cycle(&mut self, cycle: ChargeCycle) {
+        self.run_neurons(cycle, false, &SerialExecutor);
+    }
+
+    pub fn run_static_cycle(&mut self, cycle: ChargeCycle) {
+        self.run_neurons(cycle, true, &SerialExecutor);
+    }
+
+    /// Runs the phases of a cycle in order, with `executor` running the synapses of
+    /// the sensory and plastic phases
+    pub(crate) fn run_neurons<E: PhaseExecutor>(
+        &mut self,
         cycle: ChargeCycle,
-        fire: bool,
-        measure: f32,
-        prev_prev_fire_receipt: FireReceipt,
+        static_cycle: bool,
+        executor: &E,
     ) {
-        //If neither of the following are true, then nothing occurs in the loop, and we're just burning cycles
-        if fire || prev_prev_fire_receipt.fired {
-            // First do plastic synapses
-            for synapse in self.get_plastic_synapses().borrow_mut().iter_mut() {
-                // Fire synapse if it's supposed to be fired
-                if fire {
-                    synapse.target.intake_synaptic_impulse(
-                        cycle,
-                        Impulse::new(synapse.weight, measure),
-                        synapse.synapse_type,
-                    );
-                }
+        let cycle_number = self.cycle_number;
+        let stdp = self.stdp;
+        let plasticity =
+            Plasticity::new(static_cycle, stdp.as_ref(), self.regulation.weight_bound());
+
+        executor.run_phase(
+            &mut self.sensory_neurons,
+            cycle_number,
+            &self.rx.fire_trackers,
+            &mut self.rx.internal_charges,
+            |_, neuron, fire_trackers, sink| {
+                neuron.run_cycle(cycle, cycle_number, plasticity, fire_trackers, sink);
+            },
+        );
+
+        self.evaluate_plastic_neurons(cycle, executor);
+
+        let evaluations = &self.evaluations;
+        executor.run_phase(
+            &mut self.plastic_neurons,
+            cycle_number,
+            &self.rx.fire_trackers,
+            &mut self.rx.internal_charges,
+            |index, neuron, fire_trackers, sink| {
+                neuron.update_synapses(
+                    cycle,
+                    cycle_number,
+                    &evaluations[index],
+                    plasticity,
+                    fire_trackers,
+                    sink,
+                );
+            },
+        );
+
+        self.record_plastic_fires(cycle);
+        self.run_actuator_neurons(cycle);
+        self.regulate_weights(static_cycle);
+        self.cycle_number += 1;
+    }
 
-                let target_receipt = synapse.target.get_fire_receipt(cycle);
+    /// Works out whether each plastic neuron fires this cycle and resets its charge,
+    /// with `executor` running the evaluations. Receipts are only written by
+    /// `record_plastic_fires`, once every synapse has been updated
+    pub(crate) fn evaluate_plastic_neurons<E: PhaseExecutor>(
+        &mut self,
+        cycle: ChargeCycle,
+        executor: &E,
+    ) {
+        let cycle_number = self.cycle_number;
+        let rules = self.rx.rules();
+        let RxNeurons {
+            internal_charges,
+            fire_trackers,
+            fire_thresholds,
+            threshold_rises,
+            ..
+        } = &mut self.rx;
+
+        // The charge and threshold rise of every plastic neuron are borrowed apart, so
+        // that each neuron can be evaluated on any thread
+        let mut charges = internal_charges.iter_mut().map(Some).collect::<Vec<_>>();
+        let mut rises = threshold_rises.iter_mut().map(Some).collect::<Vec<_>>();
+
+        self.evaluations
+            .resize(self.plastic_neurons.len(), Evaluation::default());
+        let mut evaluations = self
+            .plastic_neurons
+            .iter()
+            .zip(self.evaluations.iter_mut())
+            .map(|(neuron, evaluation)| {
+                let index = neuron.rx as usize;
+                let state = RxState {
+                    internal_charge: charges[index].take().unwrap(),
+                    threshold_rise: rises[index].take().unwrap(),
+                    fire_threshold: fire_thresholds[index],
+                    fire_tracker: &fire_trackers[index],
+                };
+
+                (state, evaluation)
+            })
+            .collect::<Vec<_>>();
+
+        executor.for_each(&mut evaluations, |_, (state, evaluation)| {
+            let prev_prev_fire_receipt = state.fire_tracker.check_receipt(cycle);
+            let (fire, measure) = rules.evaluate(state, cycle_number);
+
+            **evaluation = Evaluation {
+                fire,
+                measure,
+                prev_prev_fire_receipt,
+            };
+        });
+    }
 
-                // Modify weight if the receipts indicate a back to back firing occurred
-                if target_receipt.fired && prev_prev_fire_receipt.fired {
-                    let new_weight = synapse.weight
-                        + (self.get_weight_modifier())(
-                            target_receipt.measure,
-                            prev_prev_fire_receipt.measure,
-                        );
+    pub(crate) fn record_plastic_fires(&mut self, cycle: ChargeCycle) {
+        for (neuron, evaluation) in self.plastic_neurons.iter().zip(&self.evaluations) {
+            self.rx.fire_trackers[neuron.rx as usize].create_receipt(
+                cycle,
+                self.cycle_number,
+                evaluation.fire,
+                evaluation.measure,
+            );
+        }
 
-                    synapse.weight = if new_weight > 0.0 { new_weight } else { 0.0 };
-                }
+        if let Some(homeostasis) = self.regulation.homeostasis {
+            for (rate, evaluation) in self.firing_rates.iter_mut().zip(&self.evaluations) {
+                *rate = homeostasis.update_rate(*rate, evaluation.fire);
             }
+        }
+    }
 
-            // Then static synapses
-            for synapse in self.get_static_synapses().borrow_mut().iter_mut() {
-                // Fire synapse if it's supposed to be fired
-                if fire {
-                    synapse.target.intake_synaptic_impulse(
-                        cycle,
-                        Impulse::new(synapse.weight, measure),
-                        synapse.synapse_type,
-                    );
-                }
+    pub(crate) fn run_actuator_neurons(&mut self, cycle: ChargeCycle) {
+        for neuron in self.actuator_neurons.iter_mut() {
+            let index = neuron.rx as usize;
+            let (fire, measure) = self.rx.evaluate(index, self.cycle_number);
 
-                let target_receipt = synapse.target.get_fire_receipt(cycle);
+            if fire {
+                neuron.measure = measure;
+            }
 
-                // Modify weight if the receipts indicate a back to back firing occurred
-                if target_receipt.fired && prev_prev_fire_receipt.fired {
-                    let new_weight = synapse.weight
-                        + (self.get_weight_modifier())(
-                            target_receipt.measure,
-                            prev_prev_fire_receipt.measure,
-                        );
+            self.rx.fire_trackers[index].create_receipt(cycle, self.cycle_number, fire, measure);
+        }
+    }
 
-                    synapse.weight = if new_weight > 0.0 { new_weight } else { 0.0 };
+    /// Normalizes and scales plastic weights at the end of a learning cycle, when
+    /// their interval is up
+    pub(crate) fn regulate_weights(&mut self, static_cycle: bool) {
+        if static_cycle {
+            return;
+        }
+
+        let cycles_run = self.cycle_number + 1;
+        let due = |interval: u32| cycles_run % interval as u64 == 0;
+        let max_weight = self.regulation.weight_bound();
+
+        if let Some(normalization) = self.regulation.normalization.filter(|n| due(n.interval)) {
+            match normalization.direction {
+                NormalizationDirection::Outgoing => {
+                    for synapses in self.plastic_synapses_mut() {
+                        let total = synapses.weights.iter().sum::<f32>();
+
+                        if total > 0. {
+                            let scale = normalization.total_weight / total;
+                            for weight in synapses.weights.iter_mut() {
+                                *weight = (*weight * scale).min(max_weight);
+                            }
+                        }
+                    }
                 }
+                NormalizationDirection::Incoming => {
+                    let mut totals = vec![0.; self.rx.fire_thresholds.len()];
+                    for synapses in self.plastic_synapses_mut() {
+                        for (&target, weight) in synapses.targets.iter().zip(&synapses.weights) {
+                            totals[target as usize] += weight;
+                        }
+                    }
+
+                    let scales = totals
+                        .into_iter()
+                        .map(|total| {
+                            if total > 0. {
+                                normalization.total_weight / total
+                            } else {
+                                1.
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    self.scale_incoming_weights(&scales);
+                }
+            }
+        }
+
+        if let Some(homeostasis) = self.regulation.homeostasis.filter(|h| due(h.interval)) {
+            // Only plastic neurons are scaled, actuators are left to their reflexes
+            let mut scales = vec![1.; self.rx.fire_thresholds.len()];
+            for (neuron, &rate) in self.plastic_neurons.iter().zip(&self.firing_rates) {
+                scales[neuron.rx as usize] = homeostasis.scale(rate);
             }
+
+            self.scale_incoming_weights(&scales);
         }
     }
 
-    fn get_plastic_synapses(&self) -> RefMut<Vec<Synapse>>;
-    fn get_static_synapses(&self) -> RefMut<Vec<Synapse>>;
-    fn get_weight_modifier(&self) -> fn(target_measure: f32, synapse_measure: f32) -> f32;
+    /// Multiplies the weight of every plastic synapse by the scale of its target
+    fn scale_incoming_weights(&mut self, scales: &[f32]) {
+        let max_weight = self.regulation.weight_bound();
 
-    fn add_plastic_synapse(
-        &self,
-        weight: f32,
-        synapse_type: SynapseType,
-        target: Rc<dyn RxNeuronic>,
-    );
+        for synapses in self.plastic_synapses_mut() {
+            for (&target, weight) in synapses.targets.iter().zip(synapses.weights.iter_mut()) {
+                *weight = (*weight * scales[target as usize]).min(max_weight);
+            }
+        }
+    }
+
+    /// Removes the plastic synapses that have weighed less than `threshold` for `checks`
+    /// calls in a row, returning how many were removed
+    pub(crate) fn prune_synapses(&mut self, threshold: f32, checks: u32) -> usize {
+        self.plastic_synapses_mut()
+            .map(|synapses| synapses.prune(threshold, checks))
+            .sum()
+    }
+
+    fn plastic_synapses_mut(&mut self) -> impl Iterator<Item = &mut Synapses> {
+        self.sensory_neurons
+            .iter_mut()
+            .map(|neuron| &mut neuron.plastic_synapses)
+            .chain(
+                self.plastic_neurons
+                    .iter_mut()
+                    .map(|neuron| &mut neuron.plastic_synapses),
+            )
+    }
+
+    pub fn clear(&mut self) {
+        for internal_charge in self.rx.internal_charges.iter_mut() {
+            internal_charge.clear();
+        }
+
+        for fire_tracker in self.rx.fire_trackers.iter_mut() {
+            fire_tracker.clear_receipts();
+        }
+
+        for rise in self.rx.threshold_rises.iter_mut() {
+            *rise = 0.;
+        }
+
+        for neuron in self.sensory_neurons.iter_mut() {
+            neuron.fire_tracker.clear_receipts();
+        }
+    }
+
+    /// Index of a plastic or actuator neuron into the receiving arrays
+    pub(crate) fn rx_index(&self, neuron: NeuronId) -> u32 {
+        match neuron {
+            NeuronId::Plastic(i) => self.plastic_neurons[i].rx,
+            NeuronId::Actuator(i) => self.actuator_neurons[i].rx,
+            NeuronId::Sensory(_) => panic!("Sensory neurons can't receive synapses"),
+        }
+    }
+
+    /// Plastic and static synapses of a sensory or plastic neuron
+    pub(crate) fn synapses(&self, neuron: NeuronId) -> (&Synapses, &Synapses) {
+        match neuron {
+            NeuronId::Sensory(i) => {
+                let neuron = &self.sensory_neurons[i];
+                (&neuron.plastic_synapses, &neuron.static_synapses)
+            }
+            NeuronId::Plastic(i) => {
+                let neuron = &self.plastic_neurons[i];
+                (&neuron.plastic_synapses, &neuron.static_synapses)
+            }
+            NeuronId::Actuator(_) => panic!("Actuator neurons don't have synapses"),
+        }
+    }
+
+    pub(crate) fn synapses_mut(&mut self, neuron: NeuronId) -> (&mut Synapses, &mut Synapses) {
+        match neuron {
+            NeuronId::Sensory(i) => {
+                let neuron = &mut self.sensory_neurons[i];
+                (&mut neuron.plastic_synapses, &mut neuron.static_synapses)
+            }
+            NeuronId::Plastic(i) => {
+                let neuron = &mut self.plastic_neurons[i];
+                (&mut neuron.plastic_synapses, &mut neuron.static_synapses)
+            }
+            NeuronId::Actuator(_) => panic!("Actuator neurons don't have synapses"),
+        }
+    }
+
+    pub(crate) fn fire_tracker(&self, neuron: NeuronId) -> &FireTracker {
+        match neuron {
+            NeuronId::Sensory(i) => &self.sensory_neurons[i].fire_tracker,
+            _ => &self.rx.fire_trackers[self.rx_index(neuron) as usize],
+        }
+    }
+
+    /// Measure the neuron fired with in the cycle just run, which was `cycle`, if it fired
+    pub(crate) fn last_cycle_fire(&self, neuron: NeuronId, cycle: ChargeCycle) -> Option<f32> {
+        let fire_tracker = self.fire_tracker(neuron);
+
+        if self.cycle_number > 0 && fire_tracker.last_fire() == Some(self.cycle_number - 1) {
+            Some(fire_tracker.check_receipt(cycle).measure())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn fire_tracker_mut(&mut self, neuron: NeuronId) -> &mut FireTracker {
+        match neuron {
+            NeuronId::Sensory(i) => &mut self.sensory_neurons[i].fire_tracker,
+            _ => {
+                let index = self.rx_index(neuron) as usize;
+                &mut self.rx.fire_trackers[index]
+            }
+        }
+    }
+
+    pub(crate) fn internal_charge(&self, neuron: NeuronId) -> &InternalCharge {
+        &self.rx.internal_charges[self.rx_index(neuron) as usize]
+    }
+
+    pub(crate) fn internal_charge_mut(&mut self, neuron: NeuronId) -> &mut InternalCharge {
+        let index = self.rx_index(neuron) as usize;
+        &mut self.rx.internal_charges[index]
+    }
+
+    /// Panics if `neuron` is a sensory neuron
+    pub fn aggregation(&self, neuron: NeuronId) -> Aggregation {
+        self.internal_charge(neuron).aggregation()
+    }
+
+    /// Panics if `neuron` is a sensory neuron
+    pub fn set_aggregation(&mut self, neuron: NeuronId, aggregation: Aggregation) {
+        self.internal_charge_mut(neuron)
+            .set_aggregation(aggregation);
+    }
+
+    pub(crate) fn fire_threshold(&self, neuron: NeuronId) -> f32 {
+        self.rx.fire_thresholds[self.rx_index(neuron) as usize]
+    }
+
+    /// Fire threshold of a plastic or actuator neuron, including how far it has risen
+    /// with adaptive thresholds
+    pub fn current_fire_threshold(&self, neuron: NeuronId) -> f32 {
+        self.rx
+            .current_fire_threshold(self.rx_index(neuron) as usize)
+    }
+
+    /// How far the threshold of a plastic or actuator neuron has risen
+    pub(crate) fn threshold_rise(&self, neuron: NeuronId) -> f32 {
+        self.rx.threshold_rises[self.rx_index(neuron) as usize]
+    }
+
+    pub(crate) fn restore_threshold_rise(&mut self, neuron: NeuronId, rise: f32) {
+        let index = self.rx_index(neuron) as usize;
+        self.rx.threshold_rises[index] = rise;
+    }
+}
+
+/// Intrinsic plasticity: the fire threshold of a plastic or actuator neuron rises by
+/// `increment` every time it fires, and the rise decays back exponentially, so that
+/// neurons that fire all the time get harder to fire. Thresholds adapt in static
+/// cycles too
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AdaptiveThreshold {
+    pub increment: f32,
+    /// Number of cycles over which the rise decays by a factor of e
+    pub time_constant: f32,
+}
+
+impl AdaptiveThreshold {
+    pub fn new(increment: f32, time_constant: f32) -> AdaptiveThreshold {
+        AdaptiveThreshold {
+            increment,
+            time_constant,
+        }
+    }
+}
+
+/// Keeps plastic and actuator neurons from firing right after they fired. For
+/// `absolute` cycles after a fire a neuron can't fire at all, and for the `relative`
+/// cycles after that its fire threshold is raised by `relative_threshold_rise`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Refractory {
+    pub absolute: u32,
+    pub relative: u32,
+    pub relative_threshold_rise: f32,
+}
+
+impl Refractory {
+    pub fn new(absolute: u32, relative: u32, relative_threshold_rise: f32) -> Refractory {
+        Refractory {
+            absolute,
+            relative,
+            relative_threshold_rise,
+        }
+    }
+}
+
+/// Leaky integration: when a plastic or actuator neuron doesn't fire, `carry_over` of
+/// its charge and weight is carried over into the next cycle instead of being
+/// discarded, so input below the threshold adds up over several cycles and leaks away
+/// when it stops
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LeakyIntegration {
+    /// Fraction of the charge kept, between 0 and 1
+    pub carry_over: f32,
+}
+
+impl LeakyIntegration {
+    pub fn new(carry_over: f32) -> LeakyIntegration {
+        LeakyIntegration { carry_over }
+    }
+}
+
+/// What plastic and actuator neurons receive, indexed by their rx index
+#[derive(Default)]
+pub(crate) struct RxNeurons {
+    pub(crate) internal_charges: Vec<InternalCharge>,
+    pub(crate) fire_trackers: Vec<FireTracker>,
+    pub(crate) fire_thresholds: Vec<f32>,
+
+    // How far each threshold has risen above its fire threshold, kept with adaptive
+    // thresholds
+    pub(crate) adaptive_threshold: Option<AdaptiveThreshold>,
+    pub(crate) threshold_rises: Vec<f32>,
+    // Fraction of the rise left after a cycle
+    threshold_decay: f32,
+
+    pub(crate) refractory: Option<Refractory>,
+    pub(crate) leaky_integration: Option<LeakyIntegration>,
+}
+
+impl RxNeurons {
+    fn push(&mut self, charge_bins: u8, fire_threshold: f32, history_window: u32) -> u32 {
+        self.internal_charges.push(InternalCharge::new(charge_bins));
+        self.fire_trackers.push(FireTracker::new(history_window));
+        self.fire_thresholds.push(fire_threshold);
+        self.threshold_rises.push(0.);
+
+        (self.fire_thresholds.len() - 1) as u32
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.internal_charges.remove(index);
+        self.fire_trackers.remove(index);
+        self.fire_thresholds.remove(index);
+        self.threshold_rises.remove(index);
+    }
+
+    fn set_adaptive_threshold(&mut self, adaptive_threshold: Option<AdaptiveThreshold>) {
+        self.adaptive_threshold = adaptive_threshold;
+        self.threshold_decay =
+            adaptive_threshold.map_or(0., |adaptive| (-1. / adaptive.time_constant).exp());
+
+        for rise in self.threshold_rises.iter_mut() {
+            *rise = 0.;
+        }
+    }
+
+    fn current_fire_threshold(&self, index: usize) -> f32 {
+        self.fire_thresholds[index] + self.threshold_rises[index]
+    }
+
+    fn rules(&self) -> RxRules {
+        RxRules {
+            adaptive_threshold: self.adaptive_threshold,
+            threshold_decay: self.threshold_decay,
+            refractory: self.refractory,
+            leaky_integration: self.leaky_integration,
+        }
+    }
 
-    fn add_static_synapse(
+    fn evaluate(&mut self, index: usize, cycle_number: u64) -> (bool, f32) {
+        let rules = self.rules();
+        let mut state = RxState {
+            internal_charge: &mut self.internal_charges[index],
+            threshold_rise: &mut self.threshold_rises[index],
+            fire_threshold: self.fire_thresholds[index],
+            fire_tracker: &self.fire_trackers[index],
+        };
+
+        rules.evaluate(&mut state, cycle_number)
+    }
+}
+
+/// The settings of `RxNeurons` that every neuron is evaluated with
+#[derive(Copy, Clone)]
+struct RxRules {
+    adaptive_threshold: Option<AdaptiveThreshold>,
+    threshold_decay: f32,
+    refractory: Option<Refractory>,
+    leaky_integration: Option<LeakyIntegration>,
+}
+
+/// What a single neuron of `RxNeurons` is evaluated on, borrowed apart from the others
+struct RxState<'a> {
+    internal_charge: &'a mut InternalCharge,
+    threshold_rise: &'a mut f32,
+    fire_threshold: f32,
+    fire_tracker: &'a FireTracker,
+}
+
+impl RxRules {
+    /// Threshold the neuron has to reach in cycle `cycle_number`, infinite while
+    /// it's absolutely refractory
+    fn refractory_fire_threshold(&self, state: &RxState, cycle_number: u64) -> f32 {
+        let fire_threshold = state.fire_threshold + *state.threshold_rise;
+
+        let (refractory, last_fire) = match (self.refractory, state.fire_tracker.last_fire) {
+            (Some(refractory), Some(last_fire)) => (refractory, last_fire),
+            _ => return fire_threshold,
+        };
+
+        let cycles_since_fire = cycle_number - last_fire;
+        if cycles_since_fire <= refractory.absolute as u64 {
+            f32::INFINITY
+        } else if cycles_since_fire <= refractory.absolute as u64 + refractory.relative as u64 {
+            fire_threshold + refractory.relative_threshold_rise
+        } else {
+            fire_threshold
+        }
+    }
+
+    /// Whether the neuron fires on the charge it received for cycle `cycle_number`, and
+    /// with what measure. The charge is reset so its slot can be reused, after carrying
+    /// part of it over with leaky integration if the neuron didn't fire
+    fn evaluate(&self, state: &mut RxState, cycle_number: u64) -> (bool, f32) {
+        let fire_threshold = self.refractory_fire_threshold(state, cycle_number);
+        let internal_charge = &mut *state.internal_charge;
+
+        let weights = internal_charge.get_weights(cycle_number);
+        let result = if weights > fire_threshold {
+            (true, internal_charge.get_measure(cycle_number))
+        } else {
+            (false, 0.0)
+        };
+
+        if let (false, Some(leaky_integration)) = (result.0, self.leaky_integration) {
+            internal_charge.carry_over(cycle_number, leaky_integration.carry_over);
+        }
+
+        internal_charge.reset_charge(cycle_number);
+
+        if let Some(adaptive_threshold) = self.adaptive_threshold {
+            let rise = &mut *state.threshold_rise;
+            *rise *= self.threshold_decay;
+
+            if result.0 {
+                *rise += adaptive_threshold.increment;
+            }
+        }
+
+        result
+    }
+}
+
+/// How synapses learn during a cycle
+#[derive(Copy, Clone)]
+pub(crate) struct Plasticity<'a> {
+    mode: PlasticityMode<'a>,
+    // Upper bound on learned weights
+    max_weight: f32,
+}
+
+#[derive(Copy, Clone)]
+enum PlasticityMode<'a> {
+    /// Static cycle, weights don't change
+    Static,
+    /// Each neuron applies its learning rule
+    Rule,
+    Stdp(&'a Stdp),
+}
+
+impl<'a> Plasticity<'a> {
+    pub(crate) fn new(
+        static_cycle: bool,
+        stdp: Option<&'a Stdp>,
+        max_weight: f32,
+    ) -> Plasticity<'a> {
+        let mode = if static_cycle {
+            PlasticityMode::Static
+        } else if let Some(stdp) = stdp {
+            PlasticityMode::Stdp(stdp)
+        } else {
+            PlasticityMode::Rule
+        };
+
+        Plasticity { mode, max_weight }
+    }
+
+    /// How the synapses of a neuron learn, given its learning rule, its fire tracker and
+    /// the receipt of its fire two cycles ago
+    fn learning(
+        self,
+        learning_rule: &'a dyn LearningRule,
+        fire_tracker: &'a FireTracker,
+        prev_prev_fire_receipt: FireReceipt,
+    ) -> Learning<'a> {
+        match self.mode {
+            PlasticityMode::Static => Learning::Off,
+            PlasticityMode::Rule => Learning::Rule(learning_rule, prev_prev_fire_receipt),
+            PlasticityMode::Stdp(stdp) => Learning::Stdp(stdp, fire_tracker),
+        }
+    }
+}
+
+/// How the synapses of a single neuron learn during a cycle
+#[derive(Copy, Clone)]
+enum Learning<'a> {
+    Off,
+    /// The learning rule, and the receipt of the neuron's fire two cycles ago
+    Rule(&'a dyn LearningRule, FireReceipt),
+    /// The STDP parameters, and the neuron's own fire history
+    Stdp(&'a Stdp, &'a FireTracker),
+}
+
+/// Whether a plastic neuron fires this cycle
+#[derive(Copy, Clone)]
+pub(crate) struct Evaluation {
+    fire: bool,
+    measure: f32,
+    prev_prev_fire_receipt: FireReceipt,
+}
+
+impl Default for Evaluation {
+    fn default() -> Evaluation {
+        Evaluation {
+            fire: false,
+            measure: 0.,
+            prev_prev_fire_receipt: FireReceipt::new(false, 0.),
+        }
+    }
+}
+
+/// Runs the neurons of one phase of a cycle, calling `run` on every neuron along with
+/// its index. The impulses they fire must reach `internal_charges` in neuron order
+pub(crate) trait PhaseExecutor {
+    fn run_phase<N, F>(
         &self,
-        weight: f32,
-        synapse_type: SynapseType,
-        target: Rc<dyn RxNeuronic>,
-    );
+        neurons: &mut [N],
+        cycle_number: u64,
+        fire_trackers: &[FireTracker],
+        internal_charges: &mut [InternalCharge],
+        run: F,
+    ) where
+        N: Send,
+        F: Fn(usize, &mut N, &[FireTracker], &mut ImpulseSink) + Sync;
+
+    /// Calls `run` on every item along with its index, for work that fires no impulses
+    fn for_each<T, F>(&self, items: &mut [T], run: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut T) + Sync;
 }
 
-pub trait RxNeuronic {
-    fn intake_synaptic_impulse(
+/// Runs every phase on the calling thread, delivering impulses as they are fired
+pub(crate) struct SerialExecutor;
+
+impl PhaseExecutor for SerialExecutor {
+    fn run_phase<N, F>(
         &self,
-        cycle: ChargeCycle,
+        neurons: &mut [N],
+        _cycle_number: u64,
+        fire_trackers: &[FireTracker],
+        internal_charges: &mut [InternalCharge],
+        run: F,
+    ) where
+        N: Send,
+        F: Fn(usize, &mut N, &[FireTracker], &mut ImpulseSink) + Sync,
+    {
+        let mut sink = ImpulseSink::Deliver(internal_charges);
+
+        for (index, neuron) in neurons.iter_mut().enumerate() {
+            run(index, neuron, fire_trackers, &mut sink);
+        }
+    }
+
+    fn for_each<T, F>(&self, items: &mut [T], run: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut T) + Sync,
+    {
+        for (index, item) in items.iter_mut().enumerate() {
+            run(index, item);
+        }
+    }
+}
+
+/// Where the impulses of firing synapses go. Impulses are either delivered to their
+/// target straight away, or buffered so that a cycle can be evaluated on several
+/// threads and the impulses delivered afterwards in a deterministic order
+pub(crate) enum ImpulseSink<'a> {
+    Deliver(&'a mut [InternalCharge]),
+    #[cfg(feature = "parallel")]
+    Buffer(Vec<PendingImpulse>),
+}
+
+impl ImpulseSink<'_> {
+    #[inline]
+    fn send(
+        &mut self,
+        cycle_number: u64,
+        target: u32,
+        delay: u8,
         impulse: Impulse,
         synapse_type: SynapseType,
     ) {
-        // println!("Yoge {} {}", impulse.measure, impulse.weight);
-        match synapse_type {
-            SynapseType::Excitatory => self
-                .get_internal_charge_mut()
-                .incr_next_charge(cycle, impulse),
-            SynapseType::Inhibitory => self
-                .get_internal_charge_mut()
-                .inhibit_next_charge(cycle, impulse),
+        match self {
+            ImpulseSink::Deliver(internal_charges) => internal_charges[target as usize]
+                .intake_synaptic_impulse(cycle_number, delay, impulse, synapse_type),
+            #[cfg(feature = "parallel")]
+            ImpulseSink::Buffer(pending) => pending.push(PendingImpulse {
+                target,
+                delay,
+                impulse,
+                synapse_type,
+            }),
         }
     }
+}
 
-    fn get_fire_receipt(&self, cycle: ChargeCycle) -> FireReceipt {
-        self.get_fire_tracker().check_receipt(cycle.next_cycle())
-    }
-
-    fn get_fire_tracker(&self) -> Ref<FireTracker>;
+/// An impulse that has been fired but not yet delivered
+#[cfg(feature = "parallel")]
+pub(crate) struct PendingImpulse {
+    target: u32,
+    delay: u8,
+    impulse: Impulse,
+    synapse_type: SynapseType,
+}
 
-    fn get_internal_charge_mut(&self) -> RefMut<InternalCharge>;
+#[cfg(feature = "parallel")]
+impl PendingImpulse {
+    pub(crate) fn deliver(self, cycle_number: u64, internal_charges: &mut [InternalCharge]) {
+        internal_charges[self.target as usize].intake_synaptic_impulse(
+            cycle_number,
+            self.delay,
+            self.impulse,
+            self.synapse_type,
+        );
+    }
 }
 
 /// Here, the impulse measure is always between 0 and 1
@@ -140,6 +1070,8 @@ impl Impulse {
     }
 }
 
+/// Parity of a cycle, which picks which of its two fire receipts a neuron writes.
+/// Charge is kept per cycle number instead, see `InternalCharge`
 #[derive(Copy, Clone)]
 pub enum ChargeCycle {
     Even,
@@ -155,177 +1087,385 @@ impl ChargeCycle {
     }
 }
 
+/// How a plastic or actuator neuron turns the charge it received into whether it fires
+/// and with what measure
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Aggregation {
+    pub measure: MeasureAggregation,
+    pub inhibition: Inhibition,
+}
+
+impl Aggregation {
+    pub fn new(measure: MeasureAggregation, inhibition: Inhibition) -> Aggregation {
+        Aggregation {
+            measure,
+            inhibition,
+        }
+    }
+}
+
+/// How the measure a neuron fires with is worked out from its charge bins. Bins whose
+/// net weight isn't positive are ignored
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum MeasureAggregation {
+    /// Average measure of every impulse, weighted by impulse weight
+    #[default]
+    WeightedAverage,
+    /// Measure of the bin holding the weighted median
+    WeightedMedian,
+    /// Measure of the bin with the most weight
+    Mode,
+    /// Average of the bin measures, weighted by the softmax of the bin weights. Low
+    /// temperatures approach the mode and high ones the plain average of the bins
+    Softmax { temperature: f32 },
+}
+
+/// How inhibitory impulses act on a neuron
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Inhibition {
+    /// Inhibitory impulses subtract from the bin of their measure
+    #[default]
+    Subtractive,
+    /// Inhibitory impulses divide the net weight by `1 + inhibitory weight` and leave
+    /// the measure alone
+    Shunting,
+}
+
+/// Charge received for each upcoming cycle, split into bins by impulse measure.
+///
+/// It's a ring buffer indexed by cycle number, with a slot for every cycle an impulse
+/// can still be on its way for, so there's one slot more than the longest delay of the
+/// synapses targeting the neuron. Bins are stored densely and reset in place, so a
+/// neuron only reallocates them when it gets a synapse with a longer delay.
 pub struct InternalCharge {
-    even: HashMap<u8, f32>,
-    even_weights: HashMap<u8, f32>,
-    odd: HashMap<u8, f32>,
-    odd_weights: HashMap<u8, f32>,
-    bins: u8, //Number of bins
+    // Weighted charges and weights of every slot, one slot after the other
+    pub(crate) charges: Box<[f32]>,
+    pub(crate) weights: Box<[f32]>,
+    // Inhibitory weight of every slot with shunting inhibition
+    pub(crate) shunts: Box<[f32]>,
+    pub(crate) bins: u8, //Number of bins
+    slots: usize,
+    aggregation: Aggregation,
 }
 
 impl InternalCharge {
-    fn new(bins: u8) -> InternalCharge {
+    /// Charge for synapses delivering into the next cycle
+    pub fn new(bins: u8) -> InternalCharge {
+        InternalCharge::with_max_delay(bins, 1)
+    }
+
+    /// Charge for synapses with delays of up to `max_delay` cycles. Panics if
+    /// `max_delay` is 0
+    pub fn with_max_delay(bins: u8, max_delay: u8) -> InternalCharge {
+        assert!(max_delay > 0, "Synaptic delays must be at least one cycle");
+
+        let slots = max_delay as usize + 1;
+        let empty = || vec![0.0; slots * bins as usize].into_boxed_slice();
+
         InternalCharge {
-            even: (0..bins).map(|i| (i, 0.0)).collect::<HashMap<u8, f32>>(),
-            even_weights: (0..bins).map(|i| (i, 0.0)).collect::<HashMap<u8, f32>>(),
-            odd: (0..bins).map(|i| (i, 0.0)).collect::<HashMap<u8, f32>>(),
-            odd_weights: (0..bins).map(|i| (i, 0.0)).collect::<HashMap<u8, f32>>(),
+            charges: empty(),
+            weights: empty(),
+            shunts: vec![0.0; slots].into_boxed_slice(),
             bins,
+            slots,
+            aggregation: Aggregation::default(),
         }
     }
 
-    fn get_charge_weighted_average(&self, cycle: ChargeCycle) -> f32 {
-        match cycle {
-            ChargeCycle::Even => {
-                let mut total_weighted_charge = 0.0;
-                let mut weights = 0.0;
+    pub fn aggregation(&self) -> Aggregation {
+        self.aggregation
+    }
 
-                for i in 0..self.bins {
-                    let weight = *self.even_weights.get(&i).unwrap();
-                    if weight >= 0.0 {
-                        weights += weight;
+    /// Best set before the neuron takes in any impulse, as charge received with
+    /// shunting inhibition is kept apart
+    pub fn set_aggregation(&mut self, aggregation: Aggregation) {
+        self.aggregation = aggregation;
+    }
 
-                        let weighted_charge = *self.even.get(&i).unwrap();
-                        if weighted_charge >= 0.0 {
-                            total_weighted_charge += weighted_charge;
-                        }
-                    }
-                }
+    /// Longest delay of the impulses this charge can take in
+    pub fn max_delay(&self) -> u8 {
+        (self.slots - 1) as u8
+    }
 
-                if weights == 0.0 {
-                    0.0
-                } else {
-                    total_weighted_charge / weights
-                }
-            }
-            ChargeCycle::Odd => {
-                let mut total_weighted_charge = 0.0;
-                let mut weights = 0.0;
+    /// Makes room for impulses with delays of up to `max_delay` cycles, keeping the
+    /// charge already received for every cycle from `cycle_number` on
+    pub(crate) fn grow(&mut self, max_delay: u8, cycle_number: u64) {
+        if max_delay <= self.max_delay() {
+            return;
+        }
 
-                for i in 0..self.bins {
-                    let weight = *self.odd_weights.get(&i).unwrap();
-                    if weight >= 0.0 {
-                        weights += weight;
+        let mut grown = InternalCharge::with_max_delay(self.bins, max_delay);
+        grown.aggregation = self.aggregation;
 
-                        let weighted_charge = *self.odd.get(&i).unwrap();
-                        if weighted_charge >= 0.0 {
-                            total_weighted_charge += weighted_charge;
-                        }
-                    }
-                }
+        for cycle_number in cycle_number..cycle_number + self.slots as u64 {
+            let (charges, weights) = self.cycle_bins(cycle_number);
+            let (grown_charges, grown_weights) = grown.cycle_bins_mut(cycle_number);
 
-                if weights == 0.0 {
-                    0.0
-                } else {
-                    total_weighted_charge / weights
-                }
-            }
+            grown_charges.copy_from_slice(charges);
+            grown_weights.copy_from_slice(weights);
+            *grown.shunt_mut(cycle_number) = self.shunt(cycle_number);
         }
+
+        *self = grown;
     }
 
-    fn get_weights(&self, cycle: ChargeCycle) -> f32 {
-        match cycle {
-            ChargeCycle::Even => {
-                let mut weights = 0.0;
-                for i in 0..self.bins {
-                    let weight = self.even_weights.get(&i).unwrap();
+    /// Position of the bins of `cycle_number` in the ring
+    fn slot_range(&self, cycle_number: u64) -> std::ops::Range<usize> {
+        let bins = self.bins as usize;
+        let start = (cycle_number % self.slots as u64) as usize * bins;
 
-                    if *weight > 0.0 {
-                        weights += *weight;
-                    }
-                }
+        start..start + bins
+    }
 
-                weights
-            }
-            ChargeCycle::Odd => {
-                let mut weights = 0.0;
-                for i in 0..self.bins {
-                    let weight = self.odd_weights.get(&i).unwrap();
+    /// Inhibitory weight of cycle `cycle_number` with shunting inhibition
+    pub(crate) fn shunt(&self, cycle_number: u64) -> f32 {
+        self.shunts[(cycle_number % self.slots as u64) as usize]
+    }
 
-                    if *weight > 0.0 {
-                        weights += *weight;
-                    }
-                }
+    pub(crate) fn shunt_mut(&mut self, cycle_number: u64) -> &mut f32 {
+        &mut self.shunts[(cycle_number % self.slots as u64) as usize]
+    }
+
+    /// Weighted charges and weights of cycle `cycle_number`
+    pub(crate) fn cycle_bins(&self, cycle_number: u64) -> (&[f32], &[f32]) {
+        let range = self.slot_range(cycle_number);
+
+        (&self.charges[range.clone()], &self.weights[range])
+    }
+
+    pub(crate) fn cycle_bins_mut(&mut self, cycle_number: u64) -> (&mut [f32], &mut [f32]) {
+        let range = self.slot_range(cycle_number);
+
+        (&mut self.charges[range.clone()], &mut self.weights[range])
+    }
+
+    pub fn get_charge_weighted_average(&self, cycle_number: u64) -> f32 {
+        let (charges, charge_weights) = self.cycle_bins(cycle_number);
 
-                weights
+        let mut total_weighted_charge = 0.0;
+        let mut weights = 0.0;
+
+        for (&weighted_charge, &weight) in charges.iter().zip(charge_weights.iter()) {
+            if weight >= 0.0 {
+                weights += weight;
+
+                if weighted_charge >= 0.0 {
+                    total_weighted_charge += weighted_charge;
+                }
             }
         }
+
+        if weights == 0.0 {
+            0.0
+        } else {
+            total_weighted_charge / weights
+        }
     }
 
-    fn reset_charge(&mut self, cycle: ChargeCycle) {
-        match cycle {
-            ChargeCycle::Even => {
-                self.even = (0..self.bins)
-                    .map(|i| (i, 0.0))
-                    .collect::<HashMap<u8, f32>>();
-                self.even_weights = (0..self.bins)
-                    .map(|i| (i, 0.0))
-                    .collect::<HashMap<u8, f32>>();
+    /// Measure the neuron fires with in cycle `cycle_number`, according to its
+    /// aggregation
+    pub fn get_measure(&self, cycle_number: u64) -> f32 {
+        match self.aggregation.measure {
+            MeasureAggregation::WeightedAverage => self.get_charge_weighted_average(cycle_number),
+            MeasureAggregation::WeightedMedian => self.get_weighted_median(cycle_number),
+            MeasureAggregation::Mode => self.get_mode(cycle_number),
+            MeasureAggregation::Softmax { temperature } => {
+                self.get_softmax_average(cycle_number, temperature)
             }
-            ChargeCycle::Odd => {
-                self.odd = (0..self.bins)
-                    .map(|i| (i, 0.0))
-                    .collect::<HashMap<u8, f32>>();
-                self.odd_weights = (0..self.bins)
-                    .map(|i| (i, 0.0))
-                    .collect::<HashMap<u8, f32>>();
+        }
+    }
+
+    /// Bins with a positive net weight, as their weight and the average measure of the
+    /// impulses in them
+    fn weighted_bins(&self, cycle_number: u64) -> impl Iterator<Item = (f32, f32)> + '_ {
+        let (charges, weights) = self.cycle_bins(cycle_number);
+
+        charges
+            .iter()
+            .zip(weights.iter())
+            .filter(|(_, &weight)| weight > 0.0)
+            .map(|(&charge, &weight)| (weight, (charge / weight).max(0.0)))
+    }
+
+    pub fn get_weighted_median(&self, cycle_number: u64) -> f32 {
+        let total_weight: f32 = self
+            .weighted_bins(cycle_number)
+            .map(|(weight, _)| weight)
+            .sum();
+        let mut weights = 0.0;
+
+        for (weight, measure) in self.weighted_bins(cycle_number) {
+            weights += weight;
+
+            if weights >= total_weight / 2.0 {
+                return measure;
             }
         }
+
+        0.0
     }
 
-    fn incr_next_charge(&mut self, cycle: ChargeCycle, incr_charge: Impulse) {
-        let bin = self.get_bin(incr_charge.measure);
+    pub fn get_mode(&self, cycle_number: u64) -> f32 {
+        let mut mode = (0.0, 0.0);
 
-        match cycle.next_cycle() {
-            ChargeCycle::Even => {
-                *self.even.get_mut(&bin).unwrap() += incr_charge.weighted_measure();
-                *self.even_weights.get_mut(&bin).unwrap() += incr_charge.weight;
+        for (weight, measure) in self.weighted_bins(cycle_number) {
+            if weight > mode.0 {
+                mode = (weight, measure);
             }
-            ChargeCycle::Odd => {
-                *self.odd.get_mut(&bin).unwrap() += incr_charge.weighted_measure();
-                *self.odd_weights.get_mut(&bin).unwrap() += incr_charge.weight;
+        }
+
+        mode.1
+    }
+
+    pub fn get_softmax_average(&self, cycle_number: u64, temperature: f32) -> f32 {
+        // Shifting by the largest weight keeps the exponentials from overflowing
+        let max_weight = self
+            .weighted_bins(cycle_number)
+            .map(|(weight, _)| weight)
+            .fold(0.0, f32::max);
+
+        let mut total_measure = 0.0;
+        let mut total_softmax = 0.0;
+
+        for (weight, measure) in self.weighted_bins(cycle_number) {
+            let softmax = ((weight - max_weight) / temperature).exp();
+
+            total_measure += softmax * measure;
+            total_softmax += softmax;
+        }
+
+        if total_softmax == 0.0 {
+            0.0
+        } else {
+            total_measure / total_softmax
+        }
+    }
+
+    /// Net weight received for cycle `cycle_number`, which is what's compared to the
+    /// fire threshold
+    pub fn get_weights(&self, cycle_number: u64) -> f32 {
+        let mut weights = 0.0;
+
+        for &weight in self.cycle_bins(cycle_number).1 {
+            if weight > 0.0 {
+                weights += weight;
             }
         }
+
+        weights / (1.0 + self.shunt(cycle_number))
+    }
+
+    pub fn reset_charge(&mut self, cycle_number: u64) {
+        let (charges, weights) = self.cycle_bins_mut(cycle_number);
+
+        charges.fill(0.0);
+        weights.fill(0.0);
+        *self.shunt_mut(cycle_number) = 0.0;
+    }
+
+    /// Adds `fraction` of the charge and weight of cycle `cycle_number` to the next
+    /// cycle
+    pub fn carry_over(&mut self, cycle_number: u64, fraction: f32) {
+        let current = self.slot_range(cycle_number);
+        let next = self.slot_range(cycle_number + 1);
+
+        for (current, next) in current.zip(next) {
+            self.charges[next] += fraction * self.charges[current];
+            self.weights[next] += fraction * self.weights[current];
+        }
+
+        let shunt = self.shunt(cycle_number);
+        *self.shunt_mut(cycle_number + 1) += fraction * shunt;
+    }
+
+    /// Resets the charge of every cycle
+    pub(crate) fn clear(&mut self) {
+        self.charges.fill(0.0);
+        self.weights.fill(0.0);
+        self.shunts.fill(0.0);
+    }
+
+    /// Adds to the charge of cycle `cycle_number`, which can be at most `max_delay`
+    /// cycles after the next cycle to be evaluated
+    pub fn incr_charge(&mut self, cycle_number: u64, incr_charge: Impulse) {
+        let bin = self.get_bin(incr_charge.measure);
+        let (charges, weights) = self.cycle_bins_mut(cycle_number);
+
+        charges[bin] += incr_charge.weighted_measure();
+        weights[bin] += incr_charge.weight;
     }
 
     /// Called when an inhibitory synapse fires
-    fn inhibit_next_charge(&mut self, cycle: ChargeCycle, inhibitory_impulse: Impulse) {
+    pub fn inhibit_charge(&mut self, cycle_number: u64, inhibitory_impulse: Impulse) {
+        if let Inhibition::Shunting = self.aggregation.inhibition {
+            *self.shunt_mut(cycle_number) += inhibitory_impulse.weight;
+            return;
+        }
+
         let bin = self.get_bin(inhibitory_impulse.measure);
+        let (charges, weights) = self.cycle_bins_mut(cycle_number);
 
-        match cycle.next_cycle() {
-            ChargeCycle::Even => {
-                *self.even.get_mut(&bin).unwrap() -= inhibitory_impulse.weighted_measure();
-                *self.even_weights.get_mut(&bin).unwrap() -= inhibitory_impulse.weight;
-            }
-            ChargeCycle::Odd => {
-                *self.odd.get_mut(&bin).unwrap() -= inhibitory_impulse.weighted_measure();
-                *self.odd_weights.get_mut(&bin).unwrap() -= inhibitory_impulse.weight;
-            }
+        charges[bin] -= inhibitory_impulse.weighted_measure();
+        weights[bin] -= inhibitory_impulse.weight;
+    }
+
+    pub fn incr_next_charge(&mut self, cycle_number: u64, incr_charge: Impulse) {
+        self.incr_charge(cycle_number + 1, incr_charge);
+    }
+
+    pub fn inhibit_next_charge(&mut self, cycle_number: u64, inhibitory_impulse: Impulse) {
+        self.inhibit_charge(cycle_number + 1, inhibitory_impulse);
+    }
+
+    /// Takes in an impulse fired in cycle `cycle_number` by a synapse with `delay`
+    fn intake_synaptic_impulse(
+        &mut self,
+        cycle_number: u64,
+        delay: u8,
+        impulse: Impulse,
+        synapse_type: SynapseType,
+    ) {
+        debug_assert!(delay > 0 && delay <= self.max_delay());
+
+        let arrival = cycle_number + delay as u64;
+
+        match synapse_type {
+            SynapseType::Excitatory => self.incr_charge(arrival, impulse),
+            SynapseType::Inhibitory => self.inhibit_charge(arrival, impulse),
         }
     }
 
-    fn get_bin(&self, measure: f32) -> u8 {
+    fn get_bin(&self, measure: f32) -> usize {
         let mut bin = (measure * (self.bins) as f32).floor() as u8;
 
         if bin >= self.bins {
             bin = self.bins - 1;
         };
 
-        bin
+        bin as usize
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct FireReceipt {
-    fired: bool,
-    measure: f32,
+    pub(crate) fired: bool,
+    pub(crate) measure: f32,
 }
 
 impl FireReceipt {
-    fn new(fired: bool, measure: f32) -> FireReceipt {
+    pub(crate) fn new(fired: bool, measure: f32) -> FireReceipt {
         FireReceipt { fired, measure }
     }
 
+    pub fn fired(&self) -> bool {
+        self.fired
+    }
+
+    /// Measure the neuron fired with, 0 if it didn't fire
+    pub fn measure(&self) -> f32 {
+        self.measure
+    }
+
     fn new_empty() -> FireReceipt {
         FireReceipt {
             fired: false,
@@ -335,24 +1475,75 @@ impl FireReceipt {
 }
 
 pub struct FireTracker {
-    receipts: (FireReceipt, FireReceipt),
+    pub(crate) receipts: (FireReceipt, FireReceipt),
+    pub(crate) last_fire: Option<u64>,
+
+    // Cycle numbers of the fires within the last `history_window` cycles, oldest first.
+    // Only kept for STDP
+    history: VecDeque<u64>,
+    history_window: u32,
+
+    // Whether the neuron fired in each of the last 64 cycles, the latest in the lowest bit
+    pub(crate) activity: u64,
 }
 
 impl FireTracker {
-    fn new() -> FireTracker {
+    fn new(history_window: u32) -> FireTracker {
         FireTracker {
             receipts: (FireReceipt::new_empty(), FireReceipt::new_empty()),
+            last_fire: None,
+            history: VecDeque::new(),
+            history_window,
+            activity: 0,
         }
     }
 
-    fn check_receipt(&self, cycle: ChargeCycle) -> FireReceipt {
+    /// Cycle numbers this neuron fired in, oldest first, going back as far as the STDP
+    /// window. Empty without STDP
+    pub fn fire_history(&self) -> impl Iterator<Item = u64> + '_ {
+        self.history.iter().copied()
+    }
+
+    pub(crate) fn history_window(&self) -> u32 {
+        self.history_window
+    }
+
+    /// Cycle number this neuron last fired in
+    pub fn last_fire(&self) -> Option<u64> {
+        self.last_fire
+    }
+
+    pub(crate) fn restore_history(&mut self, history: &[u64]) {
+        self.history.clear();
+        self.history.extend(history);
+    }
+
+    pub(crate) fn check_receipt(&self, cycle: ChargeCycle) -> FireReceipt {
         match cycle {
             ChargeCycle::Even => self.receipts.0,
             ChargeCycle::Odd => self.receipts.1,
         }
     }
 
-    fn create_receipt(&mut self, cycle: ChargeCycle, fired: bool, measure: f32) {
+    fn create_receipt(&mut self, cycle: ChargeCycle, cycle_number: u64, fired: bool, measure: f32) {
+        if fired {
+            self.last_fire = Some(cycle_number);
+        }
+
+        self.activity = (self.activity << 1) | fired as u64;
+
+        if fired && self.history_window > 0 {
+            while let Some(&first) = self.history.front() {
+                if cycle_number - first > self.history_window as u64 {
+                    self.history.pop_front();
+                } else {
+                    break;
+                }
+            }
+
+            self.history.push_back(cycle_number);
+        }
+
         match cycle {
             ChargeCycle::Even => {
                 self.receipts.0 = FireReceipt::new(fired, measure);
@@ -365,297 +1556,338 @@ impl FireTracker {
 
     fn clear_receipts(&mut self) {
         self.receipts = (FireReceipt::new_empty(), FireReceipt::new_empty());
+        self.last_fire = None;
+        self.history.clear();
+        self.activity = 0;
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SynapseType {
     Excitatory,
     Inhibitory,
 }
 
-pub struct Synapse {
-    synapse_type: SynapseType,
-    weight: f32,
-    target: Rc<dyn RxNeuronic>,
-}
-
-impl Synapse {
-    pub fn new(synapse_type: SynapseType, weight: f32, target: Rc<dyn RxNeuronic>) -> Synapse {
-        Synapse {
-            synapse_type,
-            weight,
-            target,
-        }
-    }
-}
-
-pub struct SensoryNeuron {
-    measure: RefCell<f32>,
-    plastic_synapses: RefCell<Vec<Synapse>>,
-    static_synapses: RefCell<Vec<Synapse>>,
-    weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
-    fire_tracker: RefCell<FireTracker>,
+/// Outgoing synapses of a neuron, stored as a struct of arrays. Targets are rx indices,
+/// delays the number of cycles an impulse takes to reach the target, and weak checks
+/// the number of restructurings in a row a synapse has been too weak at
+#[derive(Default)]
+pub(crate) struct Synapses {
+    pub(crate) targets: Vec<u32>,
+    pub(crate) weights: Vec<f32>,
+    pub(crate) types: Vec<SynapseType>,
+    pub(crate) delays: Vec<u8>,
+    pub(crate) weak_checks: Vec<u32>,
 }
 
-impl SensoryNeuron {
-    pub fn new(
-        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
-    ) -> SensoryNeuron {
-        SensoryNeuron {
-            measure: RefCell::new(0.0),
-            plastic_synapses: RefCell::new(Vec::new()),
-            static_synapses: RefCell::new(Vec::new()),
-            weight_modifier,
-            fire_tracker: RefCell::new(FireTracker::new()),
-        }
+impl Synapses {
+    pub(crate) fn len(&self) -> usize {
+        self.targets.len()
     }
 
-    pub fn set_measure(&self, measure: f32) {
-        *self.measure.borrow_mut() = measure;
+    fn push(&mut self, weight: f32, synapse_type: SynapseType, target: u32, delay: u8) {
+        self.targets.push(target);
+        self.weights.push(weight);
+        self.types.push(synapse_type);
+        self.delays.push(delay);
+        self.weak_checks.push(0);
     }
-}
 
-impl Neuronic for SensoryNeuron {
-    fn run_cycle(&self, cycle: ChargeCycle) {
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
-        let measure = self.measure.borrow();
+    /// Counts another check for every synapse weighing less than `threshold`, and
+    /// removes those that have now been weak for `checks` checks in a row. Returns the
+    /// number of synapses removed
+    fn prune(&mut self, threshold: f32, checks: u32) -> usize {
+        for (weight, weak_checks) in self.weights.iter().zip(self.weak_checks.iter_mut()) {
+            if *weight < threshold {
+                *weak_checks += 1;
+            } else {
+                *weak_checks = 0;
+            }
+        }
 
-        self.update_synapses(cycle, true, *measure, fire_tracker.check_receipt(cycle));
+        let keep = self
+            .weak_checks
+            .iter()
+            .map(|&weak_checks| weak_checks < checks)
+            .collect::<Vec<bool>>();
 
-        fire_tracker.create_receipt(cycle, true, *measure);
+        self.retain(&keep)
     }
 
-    fn run_static_cycle(&self, cycle: ChargeCycle) {
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
-        let measure = self.measure.borrow();
-
-        self.update_synapses(cycle, true, *measure, FireReceipt::new_empty());
-
-        fire_tracker.create_receipt(cycle, true, *measure);
+    pub(crate) fn remove(&mut self, index: usize) {
+        let keep = (0..self.len()).map(|i| i != index).collect::<Vec<bool>>();
+        self.retain(&keep);
     }
 
-    fn clear(&self) {
-        self.fire_tracker.borrow_mut().clear_receipts();
+    /// Removes every synapse targeting `target`, and moves the synapses targeting a
+    /// later rx slot down one place along with it
+    fn detach(&mut self, target: u32) {
+        let keep = self
+            .targets
+            .iter()
+            .map(|&t| t != target)
+            .collect::<Vec<bool>>();
+        self.retain(&keep);
+
+        for t in self.targets.iter_mut() {
+            if *t > target {
+                *t -= 1;
+            }
+        }
     }
-}
 
-impl TxNeuronic for SensoryNeuron {
-    fn get_plastic_synapses(&self) -> RefMut<Vec<Synapse>> {
-        self.plastic_synapses.borrow_mut()
-    }
+    /// Keeps the synapses `keep` is true for, in order, and returns the number of
+    /// synapses removed
+    fn retain(&mut self, keep: &[bool]) -> usize {
+        let mut kept = 0;
+
+        for (i, &keep) in keep.iter().enumerate() {
+            if keep {
+                self.targets.swap(kept, i);
+                self.weights.swap(kept, i);
+                self.types.swap(kept, i);
+                self.delays.swap(kept, i);
+                self.weak_checks.swap(kept, i);
+                kept += 1;
+            }
+        }
 
-    fn get_static_synapses(&self) -> RefMut<Vec<Synapse>> {
-        self.static_synapses.borrow_mut()
-    }
+        let removed = self.len() - kept;
 
-    fn get_weight_modifier(&self) -> fn(f32, f32) -> f32 {
-        self.weight_modifier
-    }
+        self.targets.truncate(kept);
+        self.weights.truncate(kept);
+        self.types.truncate(kept);
+        self.delays.truncate(kept);
+        self.weak_checks.truncate(kept);
 
-    fn add_plastic_synapse(
-        &self,
-        weight: f32,
-        synapse_type: SynapseType,
-        target: Rc<dyn RxNeuronic>,
-    ) {
-        self.plastic_synapses
-            .borrow_mut()
-            .push(Synapse::new(synapse_type, weight, target));
+        removed
     }
 
-    fn add_static_synapse(
-        &self,
-        weight: f32,
-        synapse_type: SynapseType,
-        target: Rc<dyn RxNeuronic>,
+    /// Fires every synapse if `fire`, and strengthens or weakens the synapses according
+    /// to `learning`. Learning rules only ever see back to back fires, whatever the
+    /// delay, while STDP times each pair of fires by when the impulse arrives
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        &mut self,
+        cycle: ChargeCycle,
+        cycle_number: u64,
+        fire: bool,
+        measure: f32,
+        learning: Learning,
+        max_weight: f32,
+        fire_trackers: &[FireTracker],
+        sink: &mut ImpulseSink,
     ) {
-        self.static_synapses
-            .borrow_mut()
-            .push(Synapse::new(synapse_type, weight, target));
-    }
-}
-
-pub struct ActuatorNeuron {
-    fire_tracker: RefCell<FireTracker>,
-    internal_charge: RefCell<InternalCharge>,
-    measure: RefCell<f32>,
-    fire_threshold: f32,
-}
-
-impl ActuatorNeuron {
-    pub fn new(charge_bins: u8, fire_threshold: f32) -> ActuatorNeuron {
-        ActuatorNeuron {
-            fire_tracker: RefCell::new(FireTracker::new()),
-            internal_charge: RefCell::new(InternalCharge::new(charge_bins)),
-            measure: RefCell::new(0.0),
-            fire_threshold,
-        }
-    }
-
-    pub fn read_measure(&self) -> f32 {
-        self.measure.borrow().clone()
-    }
-}
-
-impl Neuronic for ActuatorNeuron {
-    fn run_cycle(&self, cycle: ChargeCycle) {
-        let mut internal_charge = self.internal_charge.borrow_mut();
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
-
-        let weights = internal_charge.get_weights(cycle);
+        let learn = match learning {
+            Learning::Off => false,
+            Learning::Rule(_, prev_prev_fire_receipt) => prev_prev_fire_receipt.fired,
+            Learning::Stdp(_, fire_tracker) => fire_tracker.last_fire().is_some(),
+        };
 
-        if weights > self.fire_threshold {
-            let measure = internal_charge.get_charge_weighted_average(cycle);
-            *self.measure.borrow_mut() = measure;
-            fire_tracker.create_receipt(cycle, true, measure);
-        } else {
-            fire_tracker.create_receipt(cycle, false, 0.0);
+        //If neither of the following are true, then nothing occurs in the loop, and we're just burning cycles
+        if !fire && !learn {
+            return;
         }
 
-        internal_charge.reset_charge(cycle);
-    }
+        for (((&target, weight), &synapse_type), &delay) in self
+            .targets
+            .iter()
+            .zip(self.weights.iter_mut())
+            .zip(self.types.iter())
+            .zip(self.delays.iter())
+        {
+            // Fire synapse if it's supposed to be fired
+            if fire {
+                sink.send(
+                    cycle_number,
+                    target,
+                    delay,
+                    Impulse::new(*weight, measure),
+                    synapse_type,
+                );
+            }
 
-    fn run_static_cycle(&self, cycle: ChargeCycle) {
-        self.run_cycle(cycle);
-    }
+            let target_tracker = &fire_trackers[target as usize];
 
-    fn clear(&self) {
-        let mut internal_charge = self.internal_charge.borrow_mut();
+            let weight_change = match learning {
+                Learning::Off => continue,
+                Learning::Rule(learning_rule, prev_prev_fire_receipt) => {
+                    let target_receipt = target_tracker.check_receipt(cycle.next_cycle());
 
-        internal_charge.reset_charge(ChargeCycle::Even);
-        internal_charge.reset_charge(ChargeCycle::Odd);
+                    // Modify weight if the receipts indicate a back to back firing occurred
+                    if !target_receipt.fired || !prev_prev_fire_receipt.fired {
+                        continue;
+                    }
 
-        self.fire_tracker.borrow_mut().clear_receipts();
+                    learning_rule.weight_change(&LearningInput {
+                        weight: *weight,
+                        synapse_type,
+                        pre_fire_receipt: prev_prev_fire_receipt,
+                        post_fire_receipt: target_receipt,
+                        cycle: cycle_number,
+                    })
+                }
+                Learning::Stdp(stdp, fire_tracker) => stdp_weight_change(
+                    stdp,
+                    cycle_number,
+                    fire,
+                    delay,
+                    fire_tracker,
+                    target_tracker,
+                ),
+            };
+
+            let new_weight = (*weight + weight_change).min(max_weight);
+            *weight = if new_weight > 0.0 { new_weight } else { 0.0 };
+        }
     }
 }
 
-impl RxNeuronic for ActuatorNeuron {
-    fn get_fire_tracker(&self) -> Ref<FireTracker> {
-        self.fire_tracker.borrow()
+/// STDP weight change of a synapse at the start of cycle `cycle_number`, where `fire` is
+/// whether its source fires this cycle. A fire of the source counts from when its
+/// impulse arrives, so with the usual delay of one cycle a target firing right after
+/// its source is one cycle late.
+///
+/// The histories go up to the previous cycle, so every pair of fires is counted exactly
+/// once: when the target fired last cycle, it's paired with the earlier fires of the
+/// source, and when the source fires this cycle, it's paired with the earlier fires of
+/// the target.
+fn stdp_weight_change(
+    stdp: &Stdp,
+    cycle_number: u64,
+    fire: bool,
+    delay: u8,
+    source: &FireTracker,
+    target: &FireTracker,
+) -> f32 {
+    // Time from the fire of the source to the fire of the target, net of the delay
+    let timing = |source_fire: u64, target_fire: u64| {
+        target_fire as i64 - source_fire as i64 - (delay as i64 - 1)
+    };
+    let mut weight_change = 0.;
+
+    if let Some(target_fire) = target.last_fire().filter(|&fire| fire + 1 == cycle_number) {
+        for source_fire in source.fire_history() {
+            weight_change += stdp.weight_change(timing(source_fire, target_fire));
+        }
     }
 
-    fn get_internal_charge_mut(&self) -> RefMut<InternalCharge> {
-        self.internal_charge.borrow_mut()
+    if fire {
+        for target_fire in target.fire_history() {
+            weight_change += stdp.weight_change(timing(cycle_number, target_fire));
+        }
     }
-}
 
-pub struct PlasticNeuron {
-    fire_tracker: RefCell<FireTracker>,
-    internal_charge: RefCell<InternalCharge>,
-    plastic_synapses: RefCell<Vec<Synapse>>,
-    static_synapses: RefCell<Vec<Synapse>>,
-    weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
-    fire_threshold: f32,
+    weight_change
 }
 
-impl PlasticNeuron {
-    pub fn new(
-        charge_bins: u8,
-        weight_modifier: fn(target_measure: f32, synapse_measure: f32) -> f32,
-        fire_threshold: f32,
-    ) -> PlasticNeuron {
-        PlasticNeuron {
-            fire_tracker: RefCell::new(FireTracker::new()),
-            internal_charge: RefCell::new(InternalCharge::new(charge_bins)),
-            plastic_synapses: RefCell::new(Vec::new()),
-            static_synapses: RefCell::new(Vec::new()),
-            weight_modifier,
-            fire_threshold,
-        }
-    }
+pub(crate) struct SensoryNeuron {
+    pub(crate) measure: f32,
+    pub(crate) plastic_synapses: Synapses,
+    pub(crate) static_synapses: Synapses,
+    learning_rule: Arc<dyn LearningRule>,
+    pub(crate) fire_tracker: FireTracker,
 }
 
-impl Neuronic for PlasticNeuron {
-    fn run_cycle(&self, cycle: ChargeCycle) {
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
-        let mut internal_charge = self.internal_charge.borrow_mut();
-
-        let weights = internal_charge.get_weights(cycle);
-
-        if weights > self.fire_threshold {
-            let measure = internal_charge.get_charge_weighted_average(cycle);
-            self.update_synapses(cycle, true, measure, fire_tracker.check_receipt(cycle));
-            fire_tracker.create_receipt(cycle, true, measure);
-        } else {
-            self.update_synapses(cycle, false, 0.0, fire_tracker.check_receipt(cycle));
-            fire_tracker.create_receipt(cycle, false, 0.0);
+impl SensoryNeuron {
+    fn new(learning_rule: Arc<dyn LearningRule>, history_window: u32) -> SensoryNeuron {
+        SensoryNeuron {
+            measure: 0.0,
+            plastic_synapses: Synapses::default(),
+            static_synapses: Synapses::default(),
+            learning_rule,
+            fire_tracker: FireTracker::new(history_window),
         }
-
-        internal_charge.reset_charge(cycle);
     }
 
-    fn run_static_cycle(&self, cycle: ChargeCycle) {
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
-        let mut internal_charge = self.internal_charge.borrow_mut();
-
-        let weights = internal_charge.get_weights(cycle);
-
-        if weights > self.fire_threshold {
-            let measure = internal_charge.get_charge_weighted_average(cycle);
-            self.update_synapses(cycle, true, measure, FireReceipt::new_empty());
-            fire_tracker.create_receipt(cycle, true, measure);
-        } else {
-            self.update_synapses(cycle, false, 0.0, FireReceipt::new_empty());
-            fire_tracker.create_receipt(cycle, false, 0.0);
+    /// Sensory neurons fire every cycle
+    pub(crate) fn run_cycle(
+        &mut self,
+        cycle: ChargeCycle,
+        cycle_number: u64,
+        plasticity: Plasticity,
+        fire_trackers: &[FireTracker],
+        sink: &mut ImpulseSink,
+    ) {
+        let learning = plasticity.learning(
+            &*self.learning_rule,
+            &self.fire_tracker,
+            self.fire_tracker.check_receipt(cycle),
+        );
+
+        for synapses in [&mut self.plastic_synapses, &mut self.static_synapses] {
+            synapses.update(
+                cycle,
+                cycle_number,
+                true,
+                self.measure,
+                learning,
+                plasticity.max_weight,
+                fire_trackers,
+                sink,
+            );
         }
 
-        internal_charge.reset_charge(cycle);
-    }
-
-    fn clear(&self) {
-        let mut internal_charge = self.internal_charge.borrow_mut();
-
-        internal_charge.reset_charge(ChargeCycle::Even);
-        internal_charge.reset_charge(ChargeCycle::Odd);
-
-        self.fire_tracker.borrow_mut().clear_receipts();
+        self.fire_tracker
+            .create_receipt(cycle, cycle_number, true, self.measure);
     }
 }
 
-impl TxNeuronic for PlasticNeuron {
-    fn get_plastic_synapses(&self) -> RefMut<Vec<Synapse>> {
-        self.plastic_synapses.borrow_mut()
-    }
-
-    fn get_static_synapses(&self) -> RefMut<Vec<Synapse>> {
-        self.static_synapses.borrow_mut()
-    }
-
-    fn get_weight_modifier(&self) -> fn(f32, f32) -> f32 {
-        self.weight_modifier
-    }
+pub(crate) struct PlasticNeuron {
+    pub(crate) rx: u32,
+    pub(crate) plastic_synapses: Synapses,
+    pub(crate) static_synapses: Synapses,
+    learning_rule: Arc<dyn LearningRule>,
+}
 
-    fn add_plastic_synapse(
-        &self,
-        weight: f32,
-        synapse_type: SynapseType,
-        target: Rc<dyn RxNeuronic>,
-    ) {
-        self.plastic_synapses
-            .borrow_mut()
-            .push(Synapse::new(synapse_type, weight, target));
+impl PlasticNeuron {
+    fn new(rx: u32, learning_rule: Arc<dyn LearningRule>) -> PlasticNeuron {
+        PlasticNeuron {
+            rx,
+            plastic_synapses: Synapses::default(),
+            static_synapses: Synapses::default(),
+            learning_rule,
+        }
     }
 
-    fn add_static_synapse(
-        &self,
-        weight: f32,
-        synapse_type: SynapseType,
-        target: Rc<dyn RxNeuronic>,
+    pub(crate) fn update_synapses(
+        &mut self,
+        cycle: ChargeCycle,
+        cycle_number: u64,
+        evaluation: &Evaluation,
+        plasticity: Plasticity,
+        fire_trackers: &[FireTracker],
+        sink: &mut ImpulseSink,
     ) {
-        self.static_synapses
-            .borrow_mut()
-            .push(Synapse::new(synapse_type, weight, target));
+        let learning = plasticity.learning(
+            &*self.learning_rule,
+            &fire_trackers[self.rx as usize],
+            evaluation.prev_prev_fire_receipt,
+        );
+
+        for synapses in [&mut self.plastic_synapses, &mut self.static_synapses] {
+            synapses.update(
+                cycle,
+                cycle_number,
+                evaluation.fire,
+                evaluation.measure,
+                learning,
+                plasticity.max_weight,
+                fire_trackers,
+                sink,
+            );
+        }
     }
 }
 
-impl RxNeuronic for PlasticNeuron {
-    fn get_fire_tracker(&self) -> Ref<FireTracker> {
-        self.fire_tracker.borrow()
-    }
+pub(crate) struct ActuatorNeuron {
+    pub(crate) rx: u32,
+    pub(crate) measure: f32,
+}
 
-    fn get_internal_charge_mut(&self) -> RefMut<InternalCharge> {
-        self.internal_charge.borrow_mut()
+impl ActuatorNeuron {
+    fn new(rx: u32) -> ActuatorNeuron {
+        ActuatorNeuron { rx, measure: 0.0 }
     }
 }
 
@@ -666,7 +1898,7 @@ pub fn basic_weight_modifier(target_measure: f32, weight_measure: f32) -> f32 {
 
     let numerator = (-15. * x).exp() - (-1.5_f32).exp();
 
-    return numerator / denominator;
+    numerator / denominator
 }
 
 #[cfg(test)]
@@ -1,8 +1,13 @@
+use crate::learning_rule::{BasicLearningRule, LearningInput, LearningRule, Stdp, WeightModifier};
 use crate::neuron::{
-    ActuatorNeuron, ChargeCycle, FireTracker, Impulse, InternalCharge, Neuronic, PlasticNeuron,
-    RxNeuronic, SensoryNeuron, SynapseType, TxNeuronic,
+    basic_weight_modifier, AdaptiveThreshold, Aggregation, ChargeCycle, FireReceipt, FireTracker,
+    Impulse, Inhibition, InternalCharge, LeakyIntegration, MeasureAggregation, NeuronArena,
+    Refractory, SynapseType,
 };
-use std::rc::Rc;
+use crate::weight_regulation::{
+    Homeostasis, Normalization, NormalizationDirection, WeightRegulation,
+};
+use std::sync::{Arc, Mutex};
 
 /// The following two tests are for Internal Charge
 #[test]
@@ -13,34 +18,34 @@ fn test_incr_next_charge() {
     let weight = 15.;
     let measure = 0.2;
 
-    internal_charge.incr_next_charge(ChargeCycle::Even, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(0, Impulse::new(weight, measure));
 
-    assert_eq!(*internal_charge.odd_weights.get(&1).unwrap(), 15.);
-    assert_eq!(*internal_charge.odd.get(&1).unwrap(), 3.);
+    assert_eq!(internal_charge.cycle_bins(1).1[1], 15.);
+    assert_eq!(internal_charge.cycle_bins(1).0[1], 3.);
 
     let weight = 14.;
     let measure = 0.15;
 
-    internal_charge.incr_next_charge(ChargeCycle::Even, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(0, Impulse::new(weight, measure));
 
-    assert_eq!(*internal_charge.odd_weights.get(&1).unwrap(), 29.);
-    assert_eq!((*internal_charge.odd.get(&1).unwrap() * 10.) as i32, 51);
+    assert_eq!(internal_charge.cycle_bins(1).1[1], 29.);
+    assert_eq!((internal_charge.cycle_bins(1).0[1] * 10.) as i32, 51);
 
     let weight = 15.;
     let measure = 0.2;
 
-    internal_charge.incr_next_charge(ChargeCycle::Odd, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(1, Impulse::new(weight, measure));
 
-    assert_eq!(*internal_charge.even_weights.get(&1).unwrap(), 15.);
-    assert_eq!(*internal_charge.even.get(&1).unwrap(), 3.);
+    assert_eq!(internal_charge.cycle_bins(0).1[1], 15.);
+    assert_eq!(internal_charge.cycle_bins(0).0[1], 3.);
 
     let weight = 14.;
     let measure = 0.15;
 
-    internal_charge.incr_next_charge(ChargeCycle::Odd, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(1, Impulse::new(weight, measure));
 
-    assert_eq!(*internal_charge.even_weights.get(&1).unwrap(), 29.);
-    assert_eq!((*internal_charge.even.get(&1).unwrap() * 10.) as i32, 51);
+    assert_eq!(internal_charge.cycle_bins(0).1[1], 29.);
+    assert_eq!((internal_charge.cycle_bins(0).0[1] * 10.) as i32, 51);
 }
 
 #[test]
@@ -49,75 +54,69 @@ fn test_get_and_reset_charge() {
     let mut internal_charge = InternalCharge::new(bins);
     let weight = 15.;
     let measure = 0.2;
-    internal_charge.incr_next_charge(ChargeCycle::Even, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(0, Impulse::new(weight, measure));
     let weight = 14.;
     let measure = 0.15;
-    internal_charge.incr_next_charge(ChargeCycle::Even, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(0, Impulse::new(weight, measure));
 
     assert_eq!(
-        (internal_charge.get_charge_weighted_average(ChargeCycle::Odd) * 1000.).round() as i32,
+        (internal_charge.get_charge_weighted_average(1) * 1000.).round() as i32,
         176
     );
 
-    compare_f32(
-        internal_charge.get_charge_weighted_average(ChargeCycle::Odd),
-        0.175,
-    );
+    compare_f32(internal_charge.get_charge_weighted_average(1), 0.175);
 
-    compare_f32(internal_charge.get_weights(ChargeCycle::Odd), 29.);
+    compare_f32(internal_charge.get_weights(1), 29.);
 
     let weight = 15.;
     let measure = 0.2;
-    internal_charge.incr_next_charge(ChargeCycle::Odd, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(1, Impulse::new(weight, measure));
     let weight = 14.;
     let measure = 0.15;
-    internal_charge.incr_next_charge(ChargeCycle::Odd, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(1, Impulse::new(weight, measure));
 
     let weight = 10.;
     let measure = 0.5;
-    internal_charge.incr_next_charge(ChargeCycle::Odd, Impulse::new(weight, measure));
+    internal_charge.incr_next_charge(1, Impulse::new(weight, measure));
 
-    compare_f32(
-        internal_charge.get_charge_weighted_average(ChargeCycle::Even),
-        0.258,
-    );
+    compare_f32(internal_charge.get_charge_weighted_average(0), 0.258);
 
-    compare_f32(internal_charge.get_weights(ChargeCycle::Even), 39.);
+    compare_f32(internal_charge.get_weights(0), 39.);
 
-    internal_charge.reset_charge(ChargeCycle::Odd);
+    internal_charge.reset_charge(1);
 
-    assert_eq!(*internal_charge.odd_weights.get(&1).unwrap(), 0.);
-    assert_eq!(*internal_charge.odd.get(&1).unwrap(), 0.);
+    assert_eq!(internal_charge.cycle_bins(1).1[1], 0.);
+    assert_eq!(internal_charge.cycle_bins(1).0[1], 0.);
 
-    internal_charge.reset_charge(ChargeCycle::Even);
+    internal_charge.reset_charge(0);
 
-    assert_eq!(*internal_charge.even_weights.get(&1).unwrap(), 0.);
-    assert_eq!(*internal_charge.even.get(&1).unwrap(), 0.);
+    assert_eq!(internal_charge.cycle_bins(0).1[1], 0.);
+    assert_eq!(internal_charge.cycle_bins(0).0[1], 0.);
 }
 
 /// This tests FireTracker
 #[test]
 fn test_fire_tracker() {
-    let mut tracker = FireTracker::new();
-    tracker.create_receipt(ChargeCycle::Even, true, 0.3);
+    let mut tracker = FireTracker::new(0);
+    tracker.create_receipt(ChargeCycle::Even, 0, true, 0.3);
 
     let even_receipt = tracker.check_receipt(ChargeCycle::Even);
     let odd_receipt = tracker.check_receipt(ChargeCycle::Odd);
 
     assert_eq!(even_receipt.measure, 0.3);
-    assert_eq!(even_receipt.fired, true);
+    assert!(even_receipt.fired);
     assert_eq!(odd_receipt.measure, 0.0);
-    assert_eq!(odd_receipt.fired, false);
+    assert!(!odd_receipt.fired);
 
-    tracker.create_receipt(ChargeCycle::Odd, true, 0.6);
+    tracker.create_receipt(ChargeCycle::Odd, 1, true, 0.6);
 
     let even_receipt = tracker.check_receipt(ChargeCycle::Even);
     let odd_receipt = tracker.check_receipt(ChargeCycle::Odd);
 
     assert_eq!(even_receipt.measure, 0.3);
-    assert_eq!(even_receipt.fired, true);
+    assert!(even_receipt.fired);
     assert_eq!(odd_receipt.measure, 0.6);
-    assert_eq!(odd_receipt.fired, true);
+    assert!(odd_receipt.fired);
 }
 
 fn weight_modifier(target_measure: f32, weight_measure: f32) -> f32 {
@@ -126,7 +125,11 @@ fn weight_modifier(target_measure: f32, weight_measure: f32) -> f32 {
 
     let numerator = (-15. * x).exp() - (-1.5_f32).exp();
 
-    return numerator / denominator;
+    numerator / denominator
+}
+
+fn learning_rule() -> Arc<dyn LearningRule> {
+    Arc::new(WeightModifier(weight_modifier))
 }
 
 /// Compares two floats to three decimal places
@@ -139,128 +142,93 @@ fn compare_f32(float1: f32, float2: f32) {
 fn test_sensor_plastic_fire() {
     let bins = 8;
 
+    let mut neurons = NeuronArena::new();
+
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
     let s1_measure = 0.8;
     let s2_measure = 0.4;
 
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
 
     // Create plastic
     let fire_threshold = 10.;
 
-    let plastic = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
+    let plastic = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make synapses
     let s1_synapse_weight = 6.;
     let s2_synapse_weight = 5.;
 
-    s1.add_plastic_synapse(
-        s1_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        s2_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, s1_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_plastic_synapse(s2, s2_synapse_weight, SynapseType::Excitatory, plastic);
 
     // Run cycles
     let cycle = ChargeCycle::Even;
 
-    s1.run_cycle(cycle);
-    s2.run_cycle(cycle);
-    plastic.run_cycle(cycle);
+    neurons.run_cycle(cycle);
 
     // Check odd internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Odd),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(1),
         0.618,
     );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Odd),
-        11.,
-    );
+    compare_f32(neurons.internal_charge(plastic).get_weights(1), 11.);
 
     // Check even internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Even),
-        0.0,
-    );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Even),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(0),
         0.0,
     );
+    compare_f32(neurons.internal_charge(plastic).get_weights(0), 0.0);
 }
 
 #[test]
 fn test_sensor_plastic_fire_with_inhibition() {
     let bins = 8;
 
+    let mut neurons = NeuronArena::new();
+
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s3 = Rc::new(SensoryNeuron::new(weight_modifier));
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
+    let s3 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
     let s1_measure = 0.8;
     let s2_measure = 0.4;
     let s3_measure = 0.45;
 
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
-    s3.set_measure(s3_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
+    neurons.set_measure(s3, s3_measure);
 
     // Create plastic
     let fire_threshold = 10.;
 
-    let plastic = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
+    let plastic = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make synapses
     let s1_synapse_weight = 9.;
     let s2_synapse_weight = 5.;
     let s3_synapse_weight = 3.;
 
-    s1.add_plastic_synapse(
-        s1_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        s2_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s3.add_plastic_synapse(
-        s3_synapse_weight,
-        SynapseType::Inhibitory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, s1_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_plastic_synapse(s2, s2_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_plastic_synapse(s3, s3_synapse_weight, SynapseType::Inhibitory, plastic);
 
     // Run cycles
     let cycle = ChargeCycle::Even;
 
-    s1.run_cycle(cycle);
-    s2.run_cycle(cycle);
-    s3.run_cycle(cycle);
-    plastic.run_cycle(cycle);
+    neurons.run_cycle(cycle);
 
     let weighted_measure = (s1_measure * s1_synapse_weight) + (s2_measure * s2_synapse_weight)
         - (s3_measure * s3_synapse_weight);
@@ -269,365 +237,248 @@ fn test_sensor_plastic_fire_with_inhibition() {
 
     // Check odd internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Odd),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(1),
         measure,
     );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Odd),
-        11.,
-    );
+    compare_f32(neurons.internal_charge(plastic).get_weights(1), 11.);
 
     // Check even internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Even),
-        0.0,
-    );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Even),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(0),
         0.0,
     );
+    compare_f32(neurons.internal_charge(plastic).get_weights(0), 0.0);
 
     //Do it all again, but this time the inhibitory synapse blocks everything
     //I'm not going to write the equivalent of this test for plastic synapses,
     //just please for the love of God, don't fuck up.  Just implement update_synapses
     //for inhibitory neurons in the same way
 
+    let mut neurons = NeuronArena::new();
+
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s3 = Rc::new(SensoryNeuron::new(weight_modifier));
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
+    let s3 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
     let s1_measure = 0.8;
     let s2_measure = 0.4;
     let s3_measure = 0.45;
 
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
-    s3.set_measure(s3_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
+    neurons.set_measure(s3, s3_measure);
 
     // Create plastic
     let fire_threshold = 10.;
 
-    let plastic = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
+    let plastic = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make synapses
     let s1_synapse_weight = 11.;
     let s2_synapse_weight = 5.;
     let s3_synapse_weight = 7.;
 
-    s1.add_plastic_synapse(
-        s1_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        s2_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s3.add_plastic_synapse(
-        s3_synapse_weight,
-        SynapseType::Inhibitory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, s1_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_plastic_synapse(s2, s2_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_plastic_synapse(s3, s3_synapse_weight, SynapseType::Inhibitory, plastic);
 
     // Run cycles
     let cycle = ChargeCycle::Even;
 
-    s1.run_cycle(cycle);
-    s2.run_cycle(cycle);
-    s3.run_cycle(cycle);
-    plastic.run_cycle(cycle);
+    neurons.run_cycle(cycle);
 
     // Check odd internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Odd),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(1),
         0.8,
     );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Odd),
-        11.,
-    );
+    compare_f32(neurons.internal_charge(plastic).get_weights(1), 11.);
 
     // Check even internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Even),
-        0.0,
-    );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Even),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(0),
         0.0,
     );
+    compare_f32(neurons.internal_charge(plastic).get_weights(0), 0.0);
 }
 
 #[test]
 fn test_sensor_static_fire() {
     let bins = 8;
 
+    let mut neurons = NeuronArena::new();
+
     // Create sensors
-    let s1 = SensoryNeuron::new(weight_modifier);
-    let s2 = SensoryNeuron::new(weight_modifier);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
     let s1_measure = 0.8;
     let s2_measure = 0.4;
 
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
 
     // Create plastic
     let fire_threshold = 10.;
 
-    let plastic = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
+    let plastic = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make synapses
     let s1_synapse_weight = 6.;
     let s2_synapse_weight = 5.;
 
-    s1.add_static_synapse(
-        s1_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_static_synapse(
-        s2_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s1, s1_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_static_synapse(s2, s2_synapse_weight, SynapseType::Excitatory, plastic);
 
     // Run cycles
     let cycle = ChargeCycle::Even;
 
-    s1.run_cycle(cycle);
-    s2.run_cycle(cycle);
-    plastic.run_cycle(cycle);
+    neurons.run_cycle(cycle);
 
     // Check odd internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Odd),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(1),
         0.618,
     );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Odd),
-        11.,
-    );
+    compare_f32(neurons.internal_charge(plastic).get_weights(1), 11.);
 
     // Check even internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Even),
-        0.0,
-    );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Even),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(0),
         0.0,
     );
+    compare_f32(neurons.internal_charge(plastic).get_weights(0), 0.0);
 }
 
 #[test]
 fn test_sensor_static_fire_with_inhibition() {
     let bins = 8;
 
+    let mut neurons = NeuronArena::new();
+
     // Create sensors
-    let s1 = SensoryNeuron::new(weight_modifier);
-    let s2 = SensoryNeuron::new(weight_modifier);
-    let s3 = SensoryNeuron::new(weight_modifier);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
+    let s3 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
     let s1_measure = 0.8;
     let s2_measure = 0.4;
     let s3_measure = 0.45;
 
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
-    s3.set_measure(s3_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
+    neurons.set_measure(s3, s3_measure);
 
     // Create plastic
     let fire_threshold = 10.;
 
-    let plastic = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
+    let plastic = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make synapses
     let s1_synapse_weight = 9.;
     let s2_synapse_weight = 5.;
     let s3_synapse_weight = 3.;
 
-    s1.add_static_synapse(
-        s1_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_static_synapse(
-        s2_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s3.add_static_synapse(
-        s3_synapse_weight,
-        SynapseType::Inhibitory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s1, s1_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_static_synapse(s2, s2_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_static_synapse(s3, s3_synapse_weight, SynapseType::Inhibitory, plastic);
 
     // Run cycles
     let cycle = ChargeCycle::Even;
 
-    s1.run_cycle(cycle);
-    s2.run_cycle(cycle);
-    s3.run_cycle(cycle);
-    plastic.run_cycle(cycle);
+    neurons.run_cycle(cycle);
 
     // Check odd internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Odd),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(1),
         0.713,
     );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Odd),
-        11.,
-    );
+    compare_f32(neurons.internal_charge(plastic).get_weights(1), 11.);
 
     // Check even internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Even),
-        0.0,
-    );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Even),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(0),
         0.0,
     );
+    compare_f32(neurons.internal_charge(plastic).get_weights(0), 0.0);
 
     //Do it all again, but this time the inhibitory synapse blocks everything
     //I'm not going to write the equivalent of this test for plastic synapses,
     //just please for the love of God, don't fuck up.  Just implement update_synapses
     //for inhibitory neurons in the same way
 
+    let mut neurons = NeuronArena::new();
+
     // Create sensors
-    let s1 = SensoryNeuron::new(weight_modifier);
-    let s2 = SensoryNeuron::new(weight_modifier);
-    let s3 = SensoryNeuron::new(weight_modifier);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
+    let s3 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
     let s1_measure = 0.8;
     let s2_measure = 0.4;
     let s3_measure = 0.45;
 
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
-    s3.set_measure(s3_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
+    neurons.set_measure(s3, s3_measure);
 
     // Create plastic
     let fire_threshold = 10.;
 
-    let plastic = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
+    let plastic = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make synapses
     let s1_synapse_weight = 11.;
     let s2_synapse_weight = 5.;
     let s3_synapse_weight = 7.;
 
-    s1.add_static_synapse(
-        s1_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_static_synapse(
-        s2_synapse_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
-    s3.add_static_synapse(
-        s3_synapse_weight,
-        SynapseType::Inhibitory,
-        Rc::clone(&plastic) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s1, s1_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_static_synapse(s2, s2_synapse_weight, SynapseType::Excitatory, plastic);
+    neurons.add_static_synapse(s3, s3_synapse_weight, SynapseType::Inhibitory, plastic);
 
     // Run cycles
     let cycle = ChargeCycle::Even;
 
-    s1.run_cycle(cycle);
-    s2.run_cycle(cycle);
-    s3.run_cycle(cycle);
-    plastic.run_cycle(cycle);
+    neurons.run_cycle(cycle);
 
     // Check odd internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Odd),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(1),
         0.8,
     );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Odd),
-        11.,
-    );
+    compare_f32(neurons.internal_charge(plastic).get_weights(1), 11.);
 
     // Check even internal charge
     compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_charge_weighted_average(ChargeCycle::Even),
-        0.0,
-    );
-    compare_f32(
-        plastic
-            .internal_charge
-            .borrow()
-            .get_weights(ChargeCycle::Even),
+        neurons
+            .internal_charge(plastic)
+            .get_charge_weighted_average(0),
         0.0,
     );
+    compare_f32(neurons.internal_charge(plastic).get_weights(0), 0.0);
 }
 
 #[test]
@@ -635,83 +486,45 @@ fn test_plastic_plastic_fire_to_actuator() {
     let bins = 8;
     let fire_threshold = 10.;
 
-    let mut neurons = Vec::<Rc<dyn Neuronic>>::new();
+    let mut neurons = NeuronArena::new();
 
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-
-    neurons.push(Rc::clone(&s1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&s2) as Rc<dyn Neuronic>);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
-    s1.set_measure(0.3);
-    s2.set_measure(0.4);
+    neurons.set_measure(s1, 0.3);
+    neurons.set_measure(s2, 0.4);
 
     // Create plastic
-    let p1 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p2 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-
-    neurons.push(Rc::clone(&p1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p2) as Rc<dyn Neuronic>);
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make sp synapses
-    s1.add_plastic_synapse(
-        7.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_plastic_synapse(
-        5.,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, 7., SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s1, 5., SynapseType::Excitatory, p2);
 
-    s2.add_plastic_synapse(
-        6.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        6.5,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s2, 6., SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s2, 6.5, SynapseType::Excitatory, p2);
 
     // Create actuator
-    let act = Rc::new(ActuatorNeuron::new(bins, fire_threshold));
-
-    neurons.push(Rc::clone(&act) as Rc<dyn Neuronic>);
+    let act = neurons.add_actuator_neuron(bins, fire_threshold);
 
     // Make pa synapses
-    p1.add_plastic_synapse(
-        5.5,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
-    p2.add_plastic_synapse(
-        6.,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(p1, 5.5, SynapseType::Excitatory, act);
+    neurons.add_plastic_synapse(p2, 6., SynapseType::Excitatory, act);
 
     // Run three cycles
     let cycle = ChargeCycle::Even;
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
-    compare_f32(act.read_measure(), 0.351);
+    compare_f32(neurons.read_measure(act), 0.351);
 }
 
 #[test]
@@ -719,100 +532,49 @@ fn test_plastic_plastic_fire_to_actuator_with_inhibition() {
     let bins = 8;
     let fire_threshold = 10.;
 
-    let mut neurons = Vec::<Rc<dyn Neuronic>>::new();
+    let mut neurons = NeuronArena::new();
 
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-
-    neurons.push(Rc::clone(&s1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&s2) as Rc<dyn Neuronic>);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
-    s1.set_measure(0.3);
-    s2.set_measure(0.4);
+    neurons.set_measure(s1, 0.3);
+    neurons.set_measure(s2, 0.4);
 
     // Create plastic
-    let p1 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p2 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p3 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-
-    neurons.push(Rc::clone(&p1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p2) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p3) as Rc<dyn Neuronic>);
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p3 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make sp synapses
-    s1.add_plastic_synapse(
-        7.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_plastic_synapse(
-        5.,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_plastic_synapse(
-        8.,
-        SynapseType::Excitatory,
-        Rc::clone(&p3) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, 7., SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s1, 5., SynapseType::Excitatory, p2);
+    neurons.add_plastic_synapse(s1, 8., SynapseType::Excitatory, p3);
 
-    s2.add_plastic_synapse(
-        6.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        6.5,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        3.,
-        SynapseType::Excitatory,
-        Rc::clone(&p3) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s2, 6., SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s2, 6.5, SynapseType::Excitatory, p2);
+    neurons.add_plastic_synapse(s2, 3., SynapseType::Excitatory, p3);
 
     // Create actuator
-    let act = Rc::new(ActuatorNeuron::new(bins, fire_threshold));
-
-    neurons.push(Rc::clone(&act) as Rc<dyn Neuronic>);
+    let act = neurons.add_actuator_neuron(bins, fire_threshold);
 
     // Make pa synapses
-    p1.add_plastic_synapse(
-        5.5,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
-    p2.add_plastic_synapse(
-        8.,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
-    p3.add_plastic_synapse(
-        2.,
-        SynapseType::Inhibitory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(p1, 5.5, SynapseType::Excitatory, act);
+    neurons.add_plastic_synapse(p2, 8., SynapseType::Excitatory, act);
+    neurons.add_plastic_synapse(p3, 2., SynapseType::Inhibitory, act);
 
     // Run three cycles
     let cycle = ChargeCycle::Even;
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
-    compare_f32(act.read_measure(), 0.356);
+    compare_f32(neurons.read_measure(act), 0.356);
 }
 
 #[test]
@@ -820,83 +582,45 @@ fn test_plastic_static_fire_to_actuator() {
     let bins = 8;
     let fire_threshold = 10.;
 
-    let mut neurons = Vec::<Rc<dyn Neuronic>>::new();
+    let mut neurons = NeuronArena::new();
 
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-
-    neurons.push(Rc::clone(&s1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&s2) as Rc<dyn Neuronic>);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
-    s1.set_measure(0.3);
-    s2.set_measure(0.4);
+    neurons.set_measure(s1, 0.3);
+    neurons.set_measure(s2, 0.4);
 
     // Create plastic
-    let p1 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p2 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-
-    neurons.push(Rc::clone(&p1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p2) as Rc<dyn Neuronic>);
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make sp synapses
-    s1.add_static_synapse(
-        7.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_static_synapse(
-        5.,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s1, 7., SynapseType::Excitatory, p1);
+    neurons.add_static_synapse(s1, 5., SynapseType::Excitatory, p2);
 
-    s2.add_static_synapse(
-        6.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_static_synapse(
-        6.5,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s2, 6., SynapseType::Excitatory, p1);
+    neurons.add_static_synapse(s2, 6.5, SynapseType::Excitatory, p2);
 
     // Create actuator
-    let act = Rc::new(ActuatorNeuron::new(bins, fire_threshold));
-
-    neurons.push(Rc::clone(&act) as Rc<dyn Neuronic>);
+    let act = neurons.add_actuator_neuron(bins, fire_threshold);
 
     // Make pa synapses
-    p1.add_static_synapse(
-        5.5,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
-    p2.add_static_synapse(
-        6.,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(p1, 5.5, SynapseType::Excitatory, act);
+    neurons.add_static_synapse(p2, 6., SynapseType::Excitatory, act);
 
     // Run three cycles
     let cycle = ChargeCycle::Even;
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
-    compare_f32(act.read_measure(), 0.351);
+    compare_f32(neurons.read_measure(act), 0.351);
 }
 
 #[test]
@@ -904,100 +628,49 @@ fn test_plastic_static_fire_to_actuator_with_inhibition() {
     let bins = 8;
     let fire_threshold = 10.;
 
-    let mut neurons = Vec::<Rc<dyn Neuronic>>::new();
+    let mut neurons = NeuronArena::new();
 
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-
-    neurons.push(Rc::clone(&s1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&s2) as Rc<dyn Neuronic>);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     // Set sensors
-    s1.set_measure(0.3);
-    s2.set_measure(0.4);
+    neurons.set_measure(s1, 0.3);
+    neurons.set_measure(s2, 0.4);
 
     // Create plastic
-    let p1 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p2 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p3 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-
-    neurons.push(Rc::clone(&p1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p2) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p3) as Rc<dyn Neuronic>);
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p3 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     // Make sp synapses
-    s1.add_static_synapse(
-        7.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_static_synapse(
-        5.,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_static_synapse(
-        8.,
-        SynapseType::Excitatory,
-        Rc::clone(&p3) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s1, 7., SynapseType::Excitatory, p1);
+    neurons.add_static_synapse(s1, 5., SynapseType::Excitatory, p2);
+    neurons.add_static_synapse(s1, 8., SynapseType::Excitatory, p3);
 
-    s2.add_static_synapse(
-        6.,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_static_synapse(
-        6.5,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_static_synapse(
-        3.,
-        SynapseType::Excitatory,
-        Rc::clone(&p3) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(s2, 6., SynapseType::Excitatory, p1);
+    neurons.add_static_synapse(s2, 6.5, SynapseType::Excitatory, p2);
+    neurons.add_static_synapse(s2, 3., SynapseType::Excitatory, p3);
 
     // Create actuator
-    let act = Rc::new(ActuatorNeuron::new(bins, fire_threshold));
-
-    neurons.push(Rc::clone(&act) as Rc<dyn Neuronic>);
+    let act = neurons.add_actuator_neuron(bins, fire_threshold);
 
     // Make pa synapses
-    p1.add_static_synapse(
-        5.5,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
-    p2.add_static_synapse(
-        8.,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
-    p3.add_static_synapse(
-        2.,
-        SynapseType::Inhibitory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_static_synapse(p1, 5.5, SynapseType::Excitatory, act);
+    neurons.add_static_synapse(p2, 8., SynapseType::Excitatory, act);
+    neurons.add_static_synapse(p3, 2., SynapseType::Inhibitory, act);
 
     // Run three cycles
     let cycle = ChargeCycle::Even;
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
-    compare_f32(act.read_measure(), 0.356);
+    compare_f32(neurons.read_measure(act), 0.356);
 }
 
 #[test]
@@ -1005,63 +678,44 @@ fn test_basic_weight_change() {
     let bins = 8;
     let fire_threshold = 10.;
 
-    let mut neurons = Vec::<Rc<dyn Neuronic>>::new();
+    let mut neurons = NeuronArena::new();
 
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-
-    neurons.push(Rc::clone(&s1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&s2) as Rc<dyn Neuronic>);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     let s1_measure = 0.5;
     let s2_measure = 0.7;
 
     // Set sensors
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
 
     // Create plastic
-    let p1 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-
-    neurons.push(Rc::clone(&p1) as Rc<dyn Neuronic>);
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     let s1_weight = 7.2;
     let s2_weight = 5.8;
 
     // Make sp synapses
-    s1.add_plastic_synapse(
-        s1_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        s2_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, s1_weight, SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s2, s2_weight, SynapseType::Excitatory, p1);
 
     let cycle = ChargeCycle::Even;
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let p_charge = ((s1_weight * s1_measure) + (s2_weight * s2_measure)) / (s1_weight + s2_weight);
     let new_s1_weight = s1_weight + weight_modifier(p_charge, s1_measure);
     let new_s2_weight = s2_weight + weight_modifier(p_charge, s2_measure);
 
-    let actual_s1_weight = s1.plastic_synapses.borrow().get(0).unwrap().weight;
-    let actual_s2_weight = s2.plastic_synapses.borrow().get(0).unwrap().weight;
+    let actual_s1_weight = neurons.synapses(s1).0.weights[0];
+    let actual_s2_weight = neurons.synapses(s2).0.weights[0];
 
     compare_f32(new_s1_weight, actual_s1_weight);
     compare_f32(new_s2_weight, actual_s2_weight);
@@ -1072,28 +726,22 @@ fn test_plastic_weight_change() {
     let bins = 8;
     let fire_threshold = 10.;
 
-    let mut neurons = Vec::<Rc<dyn Neuronic>>::new();
+    let mut neurons = NeuronArena::new();
 
     // Create sensors
-    let s1 = Rc::new(SensoryNeuron::new(weight_modifier));
-    let s2 = Rc::new(SensoryNeuron::new(weight_modifier));
-
-    neurons.push(Rc::clone(&s1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&s2) as Rc<dyn Neuronic>);
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let s2 = neurons.add_sensory_neuron(learning_rule());
 
     let s1_measure = 0.3;
     let s2_measure = 0.4;
 
     // Set sensors
-    s1.set_measure(s1_measure);
-    s2.set_measure(s2_measure);
+    neurons.set_measure(s1, s1_measure);
+    neurons.set_measure(s2, s2_measure);
 
     // Create plastic
-    let p1 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-    let p2 = Rc::new(PlasticNeuron::new(bins, weight_modifier, fire_threshold));
-
-    neurons.push(Rc::clone(&p1) as Rc<dyn Neuronic>);
-    neurons.push(Rc::clone(&p2) as Rc<dyn Neuronic>);
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
 
     let s1_p1_weight = 7.;
     let s1_p2_weight = 5.;
@@ -1102,68 +750,34 @@ fn test_plastic_weight_change() {
     let s2_p2_weight = 6.5;
 
     // Make sp synapses
-    s1.add_plastic_synapse(
-        s1_p1_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s1.add_plastic_synapse(
-        s1_p2_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s1, s1_p1_weight, SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s1, s1_p2_weight, SynapseType::Excitatory, p2);
 
-    s2.add_plastic_synapse(
-        s2_p1_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&p1) as Rc<dyn RxNeuronic>,
-    );
-    s2.add_plastic_synapse(
-        s2_p2_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&p2) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(s2, s2_p1_weight, SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s2, s2_p2_weight, SynapseType::Excitatory, p2);
 
     // Create actuator
-    let act = Rc::new(ActuatorNeuron::new(bins, fire_threshold));
-
-    neurons.push(Rc::clone(&act) as Rc<dyn Neuronic>);
+    let act = neurons.add_actuator_neuron(bins, fire_threshold);
 
     let p1_act_weight = 5.5;
     let p2_act_weight = 6.;
 
     // Make pa synapses
-    p1.add_plastic_synapse(
-        p1_act_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&(Rc::clone(&act) as Rc<dyn RxNeuronic>)),
-    );
-    p2.add_plastic_synapse(
-        p2_act_weight,
-        SynapseType::Excitatory,
-        Rc::clone(&act) as Rc<dyn RxNeuronic>,
-    );
+    neurons.add_plastic_synapse(p1, p1_act_weight, SynapseType::Excitatory, act);
+    neurons.add_plastic_synapse(p2, p2_act_weight, SynapseType::Excitatory, act);
 
     // Run three cycles
     let cycle = ChargeCycle::Even;
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let cycle = cycle.next_cycle();
-    for neuron in &neurons {
-        neuron.run_cycle(cycle);
-    }
+    neurons.run_cycle(cycle);
 
     let p1_charge =
         ((s1_measure * s1_p1_weight) + (s2_measure * s2_p1_weight)) / (s1_p1_weight + s2_p1_weight);
@@ -1176,9 +790,633 @@ fn test_plastic_weight_change() {
     let p1_new_weight = p1_act_weight + weight_modifier(act_charge, p1_charge);
     let p2_new_weight = p2_act_weight + weight_modifier(act_charge, p2_charge);
 
-    let p1_actual_weight = p1.plastic_synapses.borrow().get(0).unwrap().weight;
-    let p2_actual_weight = p2.plastic_synapses.borrow().get(0).unwrap().weight;
+    let p1_actual_weight = neurons.synapses(p1).0.weights[0];
+    let p2_actual_weight = neurons.synapses(p2).0.weights[0];
 
     compare_f32(p1_new_weight, p1_actual_weight);
     compare_f32(p2_new_weight, p2_actual_weight);
 }
+
+#[test]
+fn test_learning_rule_input() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let inputs = Arc::new(Mutex::new(Vec::<LearningInput>::new()));
+    let recorded_inputs = Arc::clone(&inputs);
+    let learning_rule: Arc<dyn LearningRule> = Arc::new(move |input: &LearningInput| {
+        recorded_inputs.lock().unwrap().push(*input);
+        0.25
+    });
+
+    let mut neurons = NeuronArena::new();
+
+    let s1 = neurons.add_sensory_neuron(Arc::clone(&learning_rule));
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule, fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_plastic_synapse(s1, 12., SynapseType::Excitatory, p1);
+
+    // s1 fires every cycle and p1 fires from the second cycle on, so the first back to
+    // back firing the learning rule sees is s1 in cycle 0 followed by p1 in cycle 1
+    let mut cycle = ChargeCycle::Even;
+    for _ in 0..3 {
+        neurons.run_cycle(cycle);
+        cycle = cycle.next_cycle();
+    }
+
+    assert_eq!(neurons.cycle_number(), 3);
+
+    let inputs = inputs.lock().unwrap();
+    assert_eq!(inputs.len(), 1);
+
+    let input = inputs[0];
+    assert_eq!(input.weight, 12.);
+    assert!(matches!(input.synapse_type, SynapseType::Excitatory));
+    assert!(input.pre_fire_receipt.fired());
+    compare_f32(input.pre_fire_receipt.measure(), 0.5);
+    assert!(input.post_fire_receipt.fired());
+    compare_f32(input.post_fire_receipt.measure(), 0.5);
+    assert_eq!(input.cycle, 2);
+
+    compare_f32(neurons.synapses(s1).0.weights[0], 12.25);
+}
+
+#[test]
+fn test_basic_learning_rule() {
+    let input = |pre_measure: f32, post_measure: f32| LearningInput {
+        weight: 5.,
+        synapse_type: SynapseType::Excitatory,
+        pre_fire_receipt: FireReceipt::new(true, pre_measure),
+        post_fire_receipt: FireReceipt::new(true, post_measure),
+        cycle: 0,
+    };
+
+    let rule = BasicLearningRule::default();
+    for &(pre_measure, post_measure) in &[(0.5, 0.5), (0.2, 0.25), (0.3, 0.4), (0.1, 0.9)] {
+        compare_f32(
+            rule.weight_change(&input(pre_measure, post_measure)),
+            basic_weight_modifier(post_measure, pre_measure),
+        );
+    }
+
+    // Identical measures change the weight by the learning rate
+    let rule = BasicLearningRule::new(0.1, 0.2);
+    compare_f32(rule.weight_change(&input(0.6, 0.6)), 0.1);
+
+    // Measures 1.5 widths apart leave the weight as is
+    compare_f32(rule.weight_change(&input(0.2, 0.5)), 0.);
+    assert!(rule.weight_change(&input(0.2, 0.6)) < 0.);
+}
+
+#[test]
+fn test_stdp_weight_change() {
+    let stdp = Stdp::new(1., 0.5, 2., 4., 5);
+
+    compare_f32(stdp.weight_change(1), (-0.5_f32).exp());
+    compare_f32(stdp.weight_change(4), (-2_f32).exp());
+    compare_f32(stdp.weight_change(-1), -0.5 * (-0.25_f32).exp());
+    compare_f32(stdp.weight_change(-5), -0.5 * (-1.25_f32).exp());
+
+    // Simultaneous fires and fires outside of the window don't count
+    assert_eq!(stdp.weight_change(0), 0.);
+    assert_eq!(stdp.weight_change(6), 0.);
+    assert_eq!(stdp.weight_change(-6), 0.);
+}
+
+/// Runs `n` more cycles, the first cycle being even
+fn run_cycles(neurons: &mut NeuronArena, n: usize) {
+    for _ in 0..n {
+        let cycle = if neurons.cycle_number() % 2 == 0 {
+            ChargeCycle::Even
+        } else {
+            ChargeCycle::Odd
+        };
+
+        neurons.run_cycle(cycle);
+    }
+}
+
+#[test]
+fn test_stdp_potentiation() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::with_stdp(Stdp::new(1., 0., 1., 1., 2));
+
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_plastic_synapse(s1, 12., SynapseType::Excitatory, p1);
+
+    // s1 fires every cycle and p1 fires every cycle from cycle 1 on. By the end of
+    // cycle 2, the only pair with s1 firing first is s1 in cycle 0 and p1 in cycle 1
+    run_cycles(&mut neurons, 3);
+    compare_f32(neurons.synapses(s1).0.weights[0], 12. + (-1_f32).exp());
+
+    assert_eq!(
+        neurons.fire_tracker(s1).fire_history().collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert_eq!(
+        neurons.fire_tracker(p1).fire_history().collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+
+    // Cycle 3 adds the pairs ending with p1 in cycle 2
+    run_cycles(&mut neurons, 1);
+    compare_f32(
+        neurons.synapses(s1).0.weights[0],
+        12. + 2. * (-1_f32).exp() + (-2_f32).exp(),
+    );
+
+    // History older than the window is dropped
+    run_cycles(&mut neurons, 2);
+    assert_eq!(
+        neurons.fire_tracker(s1).fire_history().collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+}
+
+#[test]
+fn test_stdp_depression() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::with_stdp(Stdp::new(0., 1., 1., 1., 4));
+
+    let s1 = neurons.add_sensory_neuron(learning_rule());
+    let p1 = neurons.add_plastic_neuron(bins, learning_rule(), fire_threshold);
+    let a1 = neurons.add_actuator_neuron(bins, fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+
+    // s1 makes p1 and a1 fire together every cycle from cycle 1 on
+    neurons.add_static_synapse(s1, 12., SynapseType::Excitatory, p1);
+    neurons.add_static_synapse(s1, 12., SynapseType::Excitatory, a1);
+    neurons.add_plastic_synapse(p1, 1., SynapseType::Excitatory, a1);
+
+    // When p1 fires in cycle 2, a1 already fired in cycle 1
+    run_cycles(&mut neurons, 3);
+    compare_f32(neurons.synapses(p1).0.weights[0], 1. - (-1_f32).exp());
+
+    // Static cycles don't learn
+    neurons.run_static_cycle(ChargeCycle::Odd);
+    compare_f32(neurons.synapses(p1).0.weights[0], 1. - (-1_f32).exp());
+}
+
+/// Learning rule that leaves weights as they are
+fn no_learning() -> Arc<dyn LearningRule> {
+    Arc::new(|_: &LearningInput| 0.)
+}
+
+#[test]
+fn test_max_weight() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let always_strengthen: Arc<dyn LearningRule> = Arc::new(|_: &LearningInput| 5.);
+
+    let mut neurons = NeuronArena::new();
+    neurons.set_weight_regulation(WeightRegulation {
+        max_weight: Some(14.),
+        ..WeightRegulation::default()
+    });
+
+    let s1 = neurons.add_sensory_neuron(always_strengthen);
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_plastic_synapse(s1, 12., SynapseType::Excitatory, p1);
+
+    // The first back to back firing is learned in cycle 2
+    run_cycles(&mut neurons, 3);
+    compare_f32(neurons.synapses(s1).0.weights[0], 14.);
+}
+
+#[test]
+fn test_outgoing_normalization() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+    neurons.set_weight_regulation(WeightRegulation {
+        normalization: Some(Normalization::new(NormalizationDirection::Outgoing, 5., 1)),
+        ..WeightRegulation::default()
+    });
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.add_plastic_synapse(s1, 4., SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s1, 6., SynapseType::Inhibitory, p2);
+    neurons.add_static_synapse(s1, 20., SynapseType::Excitatory, p1);
+
+    run_cycles(&mut neurons, 1);
+
+    let (plastic_synapses, static_synapses) = neurons.synapses(s1);
+    compare_f32(plastic_synapses.weights[0], 2.);
+    compare_f32(plastic_synapses.weights[1], 3.);
+    assert_eq!(static_synapses.weights[0], 20.);
+
+    // Static cycles leave weights alone
+    neurons.set_weight_regulation(WeightRegulation {
+        normalization: Some(Normalization::new(NormalizationDirection::Outgoing, 10., 1)),
+        ..WeightRegulation::default()
+    });
+    neurons.run_static_cycle(ChargeCycle::Odd);
+    compare_f32(neurons.synapses(s1).0.weights[0], 2.);
+}
+
+#[test]
+fn test_incoming_normalization() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+    neurons.set_weight_regulation(WeightRegulation {
+        normalization: Some(Normalization::new(NormalizationDirection::Incoming, 20., 2)),
+        ..WeightRegulation::default()
+    });
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let s2 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.add_plastic_synapse(s1, 4., SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s2, 6., SynapseType::Excitatory, p1);
+
+    // Only normalized every other cycle
+    run_cycles(&mut neurons, 1);
+    assert_eq!(neurons.synapses(s1).0.weights[0], 4.);
+
+    run_cycles(&mut neurons, 1);
+    compare_f32(neurons.synapses(s1).0.weights[0], 8.);
+    compare_f32(neurons.synapses(s2).0.weights[0], 12.);
+}
+
+#[test]
+fn test_homeostasis() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+    neurons.set_weight_regulation(WeightRegulation {
+        max_weight: Some(12.2),
+        homeostasis: Some(Homeostasis::new(0.1, 1., 0.5, 1)),
+        ..WeightRegulation::default()
+    });
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_plastic_synapse(s1, 12., SynapseType::Excitatory, p1);
+
+    assert_eq!(neurons.firing_rate(p1), Some(0.1));
+    assert_eq!(neurons.firing_rate(s1), None);
+
+    // p1 doesn't fire in cycle 0, so its input is strengthened, up to the bound
+    run_cycles(&mut neurons, 1);
+    assert_eq!(neurons.firing_rate(p1), Some(0.));
+    compare_f32(neurons.synapses(s1).0.weights[0], 12.2);
+
+    // p1 fires in cycle 1, far more than its target, so its input is weakened
+    run_cycles(&mut neurons, 1);
+    assert_eq!(neurons.firing_rate(p1), Some(1.));
+    compare_f32(neurons.synapses(s1).0.weights[0], 12.2 * 0.55);
+}
+
+#[test]
+fn test_adaptive_threshold() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+    neurons.set_adaptive_threshold(Some(AdaptiveThreshold::new(5., 2.)));
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_plastic_synapse(s1, 12., SynapseType::Excitatory, p1);
+
+    let fired = |neurons: &NeuronArena| {
+        let cycle = if neurons.cycle_number() % 2 == 0 {
+            ChargeCycle::Odd
+        } else {
+            ChargeCycle::Even
+        };
+
+        neurons.fire_tracker(p1).check_receipt(cycle).fired()
+    };
+
+    run_cycles(&mut neurons, 2);
+    assert!(fired(&neurons));
+    compare_f32(neurons.current_fire_threshold(p1), 15.);
+
+    // The raised threshold keeps p1 from firing until it has decayed below 12
+    run_cycles(&mut neurons, 1);
+    assert!(!fired(&neurons));
+    compare_f32(
+        neurons.current_fire_threshold(p1),
+        10. + 5. * (-0.5_f32).exp(),
+    );
+
+    run_cycles(&mut neurons, 1);
+    assert!(!fired(&neurons));
+    compare_f32(
+        neurons.current_fire_threshold(p1),
+        10. + 5. * (-1_f32).exp(),
+    );
+
+    run_cycles(&mut neurons, 1);
+    assert!(fired(&neurons));
+    compare_f32(
+        neurons.current_fire_threshold(p1),
+        15. + 5. * (-1.5_f32).exp(),
+    );
+
+    neurons.clear();
+    assert_eq!(neurons.current_fire_threshold(p1), fire_threshold);
+}
+
+#[test]
+fn test_refractory() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+    neurons.set_refractory(Some(Refractory::new(1, 2, 5.)));
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_plastic_synapse(s1, 12., SynapseType::Excitatory, p1);
+
+    let fired = |neurons: &NeuronArena| {
+        let cycle = if neurons.cycle_number() % 2 == 0 {
+            ChargeCycle::Odd
+        } else {
+            ChargeCycle::Even
+        };
+
+        neurons.fire_tracker(p1).check_receipt(cycle).fired()
+    };
+
+    run_cycles(&mut neurons, 2);
+    assert!(fired(&neurons));
+
+    // Absolute refractory period, p1 can't fire at all
+    run_cycles(&mut neurons, 1);
+    assert!(!fired(&neurons));
+
+    // Relative refractory period, 12 doesn't reach the raised threshold
+    for _ in 0..2 {
+        run_cycles(&mut neurons, 1);
+        assert!(!fired(&neurons));
+    }
+
+    run_cycles(&mut neurons, 1);
+    assert!(fired(&neurons));
+}
+
+#[test]
+fn test_grow_internal_charge() {
+    let mut internal_charge = InternalCharge::new(4);
+    assert_eq!(internal_charge.max_delay(), 1);
+
+    internal_charge.incr_next_charge(6, Impulse::new(3., 0.5));
+    internal_charge.grow(3, 6);
+    assert_eq!(internal_charge.max_delay(), 3);
+
+    // The pending charge survives, and the new slots don't overlap with it
+    internal_charge.incr_charge(9, Impulse::new(5., 0.5));
+    compare_f32(internal_charge.get_weights(7), 3.);
+    compare_f32(internal_charge.get_weights(9), 5.);
+    compare_f32(internal_charge.get_weights(8), 0.);
+}
+
+#[test]
+fn test_synaptic_delay() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+    let a1 = neurons.add_actuator_neuron(bins, fire_threshold);
+
+    neurons.set_measure(s1, 0.5);
+    neurons.add_static_synapse_with_delay(s1, 12., SynapseType::Excitatory, p1, 3);
+    neurons.add_static_synapse_with_delay(p1, 12., SynapseType::Excitatory, a1, 2);
+    assert_eq!(neurons.internal_charge(p1).max_delay(), 3);
+
+    run_cycles(&mut neurons, 1);
+    compare_f32(neurons.internal_charge(p1).get_weights(1), 0.);
+    compare_f32(neurons.internal_charge(p1).get_weights(3), 12.);
+
+    // p1 first fires in cycle 3, and a1 two cycles after that
+    run_cycles(&mut neurons, 3);
+    assert_eq!(neurons.fire_tracker(p1).last_fire(), Some(3));
+    assert_eq!(neurons.fire_tracker(a1).last_fire(), None);
+
+    run_cycles(&mut neurons, 2);
+    assert_eq!(neurons.fire_tracker(a1).last_fire(), Some(5));
+    compare_f32(neurons.read_measure(a1), 0.5);
+}
+
+#[test]
+fn test_carry_over() {
+    let mut internal_charge = InternalCharge::new(4);
+    internal_charge.incr_charge(0, Impulse::new(8., 0.5));
+    internal_charge.inhibit_charge(0, Impulse::new(2., 0.5));
+
+    // The charge left after each cycle halves
+    for cycle_number in 0..3 {
+        internal_charge.carry_over(cycle_number, 0.5);
+        internal_charge.reset_charge(cycle_number);
+
+        let expected = 6. * 0.5_f32.powi(cycle_number as i32 + 1);
+        compare_f32(internal_charge.get_weights(cycle_number + 1), expected);
+        compare_f32(
+            internal_charge.get_charge_weighted_average(cycle_number + 1),
+            0.5,
+        );
+    }
+}
+
+#[test]
+fn test_leaky_integration() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let build = |leaky_integration: Option<LeakyIntegration>| {
+        let mut neurons = NeuronArena::new();
+        neurons.set_leaky_integration(leaky_integration);
+
+        let s1 = neurons.add_sensory_neuron(no_learning());
+        let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+        neurons.set_measure(s1, 0.5);
+        neurons.add_plastic_synapse(s1, 6., SynapseType::Excitatory, p1);
+
+        (neurons, p1)
+    };
+
+    // Without a leak, 6 never reaches the threshold
+    let (mut neurons, p1) = build(None);
+    run_cycles(&mut neurons, 10);
+    assert_eq!(neurons.fire_tracker(p1).last_fire(), None);
+
+    // With half carried over, p1 gets 6, then 9, then 10.5 and fires
+    let (mut neurons, p1) = build(Some(LeakyIntegration::new(0.5)));
+    run_cycles(&mut neurons, 3);
+    assert_eq!(neurons.fire_tracker(p1).last_fire(), None);
+    compare_f32(neurons.internal_charge(p1).get_weights(3), 10.5);
+
+    run_cycles(&mut neurons, 1);
+    assert_eq!(neurons.fire_tracker(p1).last_fire(), Some(3));
+
+    // Firing discards the charge, so it builds up again from scratch
+    compare_f32(neurons.internal_charge(p1).get_weights(4), 6.);
+}
+
+#[test]
+fn test_measure_aggregation() {
+    let mut internal_charge = InternalCharge::new(10);
+    internal_charge.incr_charge(0, Impulse::new(5., 0.15));
+    internal_charge.incr_charge(0, Impulse::new(3., 0.55));
+    internal_charge.incr_charge(0, Impulse::new(4., 0.85));
+
+    let measure = |internal_charge: &mut InternalCharge, measure| {
+        internal_charge.set_aggregation(Aggregation::new(measure, Inhibition::Subtractive));
+        internal_charge.get_measure(0)
+    };
+
+    compare_f32(
+        measure(&mut internal_charge, MeasureAggregation::WeightedAverage),
+        (5. * 0.15 + 3. * 0.55 + 4. * 0.85) / 12.,
+    );
+
+    // Half of the weight is reached in the second bin
+    compare_f32(
+        measure(&mut internal_charge, MeasureAggregation::WeightedMedian),
+        0.55,
+    );
+    compare_f32(
+        measure(&mut internal_charge, MeasureAggregation::Mode),
+        0.15,
+    );
+
+    let softmax = [0_f32, -2., -1.].map(f32::exp);
+    compare_f32(
+        measure(
+            &mut internal_charge,
+            MeasureAggregation::Softmax { temperature: 1. },
+        ),
+        (softmax[0] * 0.15 + softmax[1] * 0.55 + softmax[2] * 0.85) / softmax.iter().sum::<f32>(),
+    );
+
+    // A high temperature weighs every bin the same
+    compare_f32(
+        measure(
+            &mut internal_charge,
+            MeasureAggregation::Softmax { temperature: 1e6 },
+        ),
+        (0.15 + 0.55 + 0.85) / 3.,
+    );
+}
+
+#[test]
+fn test_shunting_inhibition() {
+    let mut internal_charge = InternalCharge::new(10);
+    internal_charge.set_aggregation(Aggregation::new(
+        MeasureAggregation::WeightedAverage,
+        Inhibition::Shunting,
+    ));
+
+    internal_charge.incr_charge(1, Impulse::new(12., 0.5));
+    internal_charge.inhibit_charge(1, Impulse::new(2., 0.9));
+
+    // The weight is divided by 1 + 2 and the measure is left alone
+    compare_f32(internal_charge.get_weights(1), 4.);
+    compare_f32(internal_charge.get_measure(1), 0.5);
+
+    internal_charge.carry_over(1, 0.5);
+    internal_charge.reset_charge(1);
+    compare_f32(internal_charge.get_weights(1), 0.);
+    compare_f32(internal_charge.get_weights(2), 6. / 2.);
+}
+
+#[test]
+fn test_prune_synapses() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+    let p2 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+
+    neurons.add_plastic_synapse(s1, 0.1, SynapseType::Excitatory, p1);
+    neurons.add_plastic_synapse(s1, 5., SynapseType::Excitatory, p2);
+    neurons.add_static_synapse(s1, 0.1, SynapseType::Excitatory, p2);
+
+    // Synapses have to be weak for two checks in a row, and static synapses stay
+    assert_eq!(neurons.prune_synapses(1., 2), 0);
+    assert_eq!(neurons.prune_synapses(1., 2), 1);
+
+    let (plastic_synapses, static_synapses) = neurons.synapses(s1);
+    assert_eq!(plastic_synapses.weights, vec![5.]);
+    assert_eq!(plastic_synapses.targets, vec![neurons.rx_index(p2)]);
+    assert_eq!(static_synapses.len(), 1);
+
+    // Fire trackers keep the last 64 cycles of activity
+    run_cycles(&mut neurons, 3);
+    assert_eq!(neurons.fire_tracker(s1).activity, 0b111);
+    assert_eq!(neurons.fire_tracker(p1).activity, 0);
+}
+
+#[test]
+fn test_remove_neurons() {
+    let bins = 8;
+    let fire_threshold = 10.;
+
+    let mut neurons = NeuronArena::new();
+
+    let s1 = neurons.add_sensory_neuron(no_learning());
+    let s2 = neurons.add_sensory_neuron(no_learning());
+    let a1 = neurons.add_actuator_neuron(bins, fire_threshold);
+    let p1 = neurons.add_plastic_neuron(bins, no_learning(), fire_threshold);
+    let a2 = neurons.add_actuator_neuron(bins, fire_threshold);
+
+    neurons.set_measure(s2, 0.5);
+    neurons.add_plastic_synapse(s2, 12., SynapseType::Excitatory, a1);
+    neurons.add_plastic_synapse(s2, 12., SynapseType::Excitatory, p1);
+    neurons.add_static_synapse(s2, 12., SynapseType::Excitatory, a2);
+    neurons.add_plastic_synapse(p1, 12., SynapseType::Excitatory, a1);
+
+    // The actuators after the one removed move down one place, keeping their synapses
+    neurons.remove_actuator_neuron(a1);
+    let a2 = a1;
+    assert_eq!(neurons.synapses(s2).0.len(), 1);
+    assert_eq!(neurons.synapses(s2).1.targets, vec![neurons.rx_index(a2)]);
+    assert_eq!(neurons.synapses(p1).0.len(), 0);
+
+    neurons.remove_sensory_neuron(s1);
+    let s2 = s1;
+    assert_eq!(neurons.synapses(s2).1.len(), 1);
+
+    run_cycles(&mut neurons, 2);
+    compare_f32(neurons.read_measure(a2), 0.5);
+    assert_eq!(neurons.fire_tracker(p1).last_fire(), Some(1));
+
+    neurons.remove_actuator_neuron(a2);
+    assert_eq!(neurons.synapses(s2).1.len(), 0);
+}
@@ -1,47 +1,43 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::actuator::Actuator;
-use crate::neuron::ActuatorNeuron;
-use crate::neuron::SensoryNeuron;
+use crate::neuron::{NeuronArena, NeuronId};
 use crate::sensor::Sensor;
 
 pub struct SensoryInterface {
-    sensor: Rc<dyn Sensor>,
-    pub sensory_neuron: Rc<SensoryNeuron>,
+    pub(crate) sensor: Arc<dyn Sensor>,
+    pub sensory_neuron: NeuronId,
 }
 
 impl SensoryInterface {
-    pub fn new(sensor: Rc<dyn Sensor>, sensory_neuron: Rc<SensoryNeuron>) -> SensoryInterface {
+    pub fn new(sensor: Arc<dyn Sensor>, sensory_neuron: NeuronId) -> SensoryInterface {
         SensoryInterface {
             sensor,
             sensory_neuron,
         }
     }
 
-    pub fn run_cycle(&self) {
-        self.sensory_neuron.set_measure(self.sensor.measure());
+    pub fn run_cycle(&self, neurons: &mut NeuronArena) {
+        neurons.set_measure(self.sensory_neuron, self.sensor.measure());
     }
 }
 
 pub struct ActuatorInterface {
-    actuator: Rc<dyn Actuator>,
-    pub actuator_neuron: Rc<ActuatorNeuron>,
+    pub(crate) actuator: Arc<dyn Actuator>,
+    pub actuator_neuron: NeuronId,
 }
 
 impl ActuatorInterface {
-    pub fn new(
-        actuator: Rc<dyn Actuator>,
-        actuator_neuron: Rc<ActuatorNeuron>,
-    ) -> ActuatorInterface {
+    pub fn new(actuator: Arc<dyn Actuator>, actuator_neuron: NeuronId) -> ActuatorInterface {
         ActuatorInterface {
             actuator,
             actuator_neuron,
         }
     }
 
-    pub fn run_cycle(&self) {
+    pub fn run_cycle(&self, neurons: &NeuronArena) {
         self.actuator
-            .set_control_value(self.actuator_neuron.read_measure());
+            .set_control_value(neurons.read_measure(self.actuator_neuron));
     }
 
     pub fn clear(&self) {
@@ -0,0 +1,23 @@
+use crate::fire_recorder::NeuronKind;
+
+/// Instrumentation called back by an encephalon as it runs cycles, see
+/// `Encephalon::add_observer`. Every callback does nothing by default. Within a cycle
+/// the callbacks come in the order they're declared in
+pub trait EncephalonObserver: Send + Sync {
+    /// Before anything else happens in cycle number `cycle`
+    fn on_cycle_start(&mut self, _cycle: u64) {}
+
+    /// An actuator was handed `value`, the measure its neuron fired with last
+    fn on_actuator_output(&mut self, _name: &str, _value: f32) {}
+
+    /// The weight of the synapse from `source` to `target` was changed by learning or
+    /// weight regulation. Synapses pruned or grown by structural plasticity aren't
+    /// reported
+    fn on_weight_update(&mut self, _source: &[i32], _target: &[i32], _old: f32, _new: f32) {}
+
+    /// A neuron fired with `measure`
+    fn on_neuron_fire(&mut self, _kind: NeuronKind, _loc: &[i32], _measure: f32) {}
+
+    /// After everything else happened in cycle number `cycle`
+    fn on_cycle_end(&mut self, _cycle: u64) {}
+}
@@ -1,24 +1,119 @@
+use crate::error::EncephalonError;
 use crate::neuron::SynapseType;
 
+/// Identifies a reflex within its encephalon, see `Encephalon::add_reflex`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReflexId(pub(crate) u64);
+
+/// Neuron a reflex starts at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReflexSource {
+    /// Sensory neuron of the sensor with this name
+    Sensor(String),
+    /// Plastic neuron at this location
+    Plastic(Vec<i32>),
+}
+
+/// Neuron a reflex ends at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReflexTarget {
+    /// Actuator neuron of the actuator with this name
+    Actuator(String),
+    /// Plastic neuron at this location
+    Plastic(Vec<i32>),
+}
+
+/// Static synapse from a sensor to an actuator, or between any two neurons when made
+/// with `between`
+#[derive(Clone, Debug, PartialEq)]
 pub struct Reflex {
-    pub sensor_name: String,
-    pub actuator_name: String,
+    source: ReflexSource,
+    target: ReflexTarget,
     pub synapse_type: SynapseType,
     pub weight: f32,
+    /// Cycles the impulse takes to reach the target, 1 unless set with `with_delay`
+    pub delay: u8,
 }
 
 impl Reflex {
+    /// Reflex from a sensor to an actuator. The weight isn't checked, see `try_new`
     pub fn new(
         sensor_name: String,
         actuator_name: String,
         synapse_type: SynapseType,
         weight: f32,
+    ) -> Reflex {
+        Reflex::between(
+            ReflexSource::Sensor(sensor_name),
+            ReflexTarget::Actuator(actuator_name),
+            synapse_type,
+            weight,
+        )
+    }
+
+    /// The weight must be finite and non-negative, inhibition is expressed
+    /// through the synapse type
+    pub fn try_new(
+        sensor_name: String,
+        actuator_name: String,
+        synapse_type: SynapseType,
+        weight: f32,
+    ) -> Result<Reflex, EncephalonError> {
+        Reflex::try_between(
+            ReflexSource::Sensor(sensor_name),
+            ReflexTarget::Actuator(actuator_name),
+            synapse_type,
+            weight,
+        )
+    }
+
+    /// Reflex between any two neurons, plastic neurons included. The weight isn't
+    /// checked, see `try_between`
+    pub fn between(
+        source: ReflexSource,
+        target: ReflexTarget,
+        synapse_type: SynapseType,
+        weight: f32,
     ) -> Reflex {
         Reflex {
-            sensor_name,
-            actuator_name,
+            source,
+            target,
             synapse_type,
             weight,
+            delay: 1,
+        }
+    }
+
+    /// Like `try_new`, but between any two neurons
+    pub fn try_between(
+        source: ReflexSource,
+        target: ReflexTarget,
+        synapse_type: SynapseType,
+        weight: f32,
+    ) -> Result<Reflex, EncephalonError> {
+        if !weight.is_finite() || weight < 0. {
+            return Err(EncephalonError::InvalidParameter {
+                parameter: "weight",
+                reason: "reflex weights must be finite and non-negative".into(),
+            });
         }
+
+        Ok(Reflex::between(source, target, synapse_type, weight))
+    }
+
+    /// Panics if `delay` is 0
+    pub fn with_delay(mut self, delay: u8) -> Reflex {
+        assert!(delay > 0, "Synaptic delays must be at least one cycle");
+
+        self.delay = delay;
+        self
+    }
+
+    pub fn source(&self) -> &ReflexSource {
+        &self.source
+    }
+
+    pub fn target(&self) -> &ReflexTarget {
+        &self.target
     }
 }
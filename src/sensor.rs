@@ -1,4 +1,5 @@
-pub trait Sensor {
+/// Sensors are shared with the encephalon, which may be running on another thread
+pub trait Sensor: Send + Sync {
     /// Must return a value between 0 and 1
     fn measure(&self) -> f32;
     fn get_name(&self) -> String;
@@ -6,7 +7,7 @@ pub trait Sensor {
 
 pub mod custom_sensors {
     use crate::sensor::Sensor;
-    use std::cell::RefCell;
+    use std::sync::Mutex;
 
     /// Sensor with a constant value
     pub struct ConstantSensor {
@@ -33,25 +34,25 @@ pub mod custom_sensors {
     /// Sensor who's measure is explicitly set
     pub struct BasicSensor {
         name: String,
-        measure: RefCell<f32>,
+        measure: Mutex<f32>,
     }
 
     impl BasicSensor {
         pub fn new(name: String) -> BasicSensor {
             BasicSensor {
                 name,
-                measure: RefCell::new(0.0),
+                measure: Mutex::new(0.0),
             }
         }
 
         pub fn set_measure(&self, measure: f32) {
-            *self.measure.borrow_mut() = measure
+            *self.measure.lock().unwrap() = measure
         }
     }
 
     impl Sensor for BasicSensor {
         fn measure(&self) -> f32 {
-            *self.measure.borrow()
+            *self.measure.lock().unwrap()
         }
 
         fn get_name(&self) -> String {
@@ -0,0 +1,47 @@
+/// Periodically rewires an encephalon: plastic synapses that stay weak are pruned, and
+/// neurons grow synapses to nearby neurons that have been firing in step with them.
+/// Reflexes and other static synapses are never pruned
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StructuralPlasticity {
+    /// Restructures at the end of every `interval`th cycle, unless it's a static cycle
+    pub interval: u32,
+    /// Plastic synapses whose weight is below `prune_threshold` at `prune_after`
+    /// restructurings in a row are removed
+    pub prune_threshold: f32,
+    pub prune_after: u32,
+    /// A neuron can grow a synapse to a nearby neuron it isn't connected to once that
+    /// neuron has fired right as the impulse would have arrived at least
+    /// `growth_coactivity` times within the last 64 cycles
+    pub growth_coactivity: u32,
+    /// Chance that each such pair grows a synapse when restructuring
+    pub growth_probability: f32,
+    /// Weight of grown synapses, which are always excitatory
+    pub growth_weight: f32,
+}
+
+impl StructuralPlasticity {
+    pub fn new(
+        interval: u32,
+        prune_threshold: f32,
+        prune_after: u32,
+        growth_coactivity: u32,
+        growth_probability: f32,
+        growth_weight: f32,
+    ) -> StructuralPlasticity {
+        StructuralPlasticity {
+            interval,
+            prune_threshold,
+            prune_after,
+            growth_coactivity,
+            growth_probability,
+            growth_weight,
+        }
+    }
+}
+
+/// Number of synapses structural plasticity has removed and added
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StructuralChanges {
+    pub pruned: u64,
+    pub grown: u64,
+}
@@ -0,0 +1,155 @@
+//! Read-only view of every synapse of an encephalon, see `Encephalon::synapse_snapshot`,
+//! and what changed between two such views.
+
+use std::collections::HashMap;
+
+use crate::fire_recorder::NeuronKind;
+use crate::neuron::SynapseType;
+
+/// Whether a synapse learns, or is a static reflex synapse
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SynapseKind {
+    Plastic,
+    Static,
+}
+
+/// A synapse as it was when the snapshot was taken
+#[derive(Clone, Debug, PartialEq)]
+pub struct SynapseState {
+    pub source_kind: NeuronKind,
+    pub source: Vec<i32>,
+    pub target_kind: NeuronKind,
+    pub target: Vec<i32>,
+    pub kind: SynapseKind,
+    pub synapse_type: SynapseType,
+    pub weight: f32,
+    pub delay: u8,
+}
+
+/// Every synapse of an encephalon after `cycle` cycles, grouped by source neuron
+#[derive(Clone, Debug, PartialEq)]
+pub struct SynapseSnapshot {
+    pub cycle: u64,
+    pub synapses: Vec<SynapseState>,
+}
+
+/// A synapse found in both snapshots with a different weight
+#[derive(Clone, Debug, PartialEq)]
+pub struct SynapseChange {
+    /// The synapse as it is in the later snapshot
+    pub synapse: SynapseState,
+    pub weight_before: f32,
+}
+
+impl SynapseChange {
+    pub fn weight_change(&self) -> f32 {
+        self.synapse.weight - self.weight_before
+    }
+}
+
+/// What changed from one snapshot to a later one
+#[derive(Clone, Debug, PartialEq)]
+pub struct SynapseDiff {
+    /// Synapses whose weight changed, the largest change in either direction first
+    pub changed: Vec<SynapseChange>,
+    pub added: Vec<SynapseState>,
+    pub removed: Vec<SynapseState>,
+    /// Number of synapses in both snapshots with the same weight
+    pub unchanged: usize,
+    /// Sum of the absolute weight changes
+    pub total_change: f32,
+}
+
+impl SynapseDiff {
+    /// The `count` synapses that changed most
+    pub fn largest_changes(&self, count: usize) -> &[SynapseChange] {
+        &self.changed[..count.min(self.changed.len())]
+    }
+}
+
+/// Synapses are matched by their endpoints, kind and type. Neurons can have more than
+/// one reflex of a type to the same target, which are matched in order
+type SynapseKey<'a> = (
+    NeuronKind,
+    &'a [i32],
+    NeuronKind,
+    &'a [i32],
+    SynapseKind,
+    SynapseType,
+    usize,
+);
+
+impl SynapseSnapshot {
+    /// Changes from `self` to `later`
+    pub fn diff(&self, later: &SynapseSnapshot) -> SynapseDiff {
+        let mut before = keyed(&self.synapses).collect::<HashMap<SynapseKey, &SynapseState>>();
+
+        let mut changed = Vec::new();
+        let mut added = Vec::new();
+        let mut unchanged = 0;
+
+        for (key, synapse) in keyed(&later.synapses) {
+            match before.remove(&key) {
+                Some(earlier) if earlier.weight == synapse.weight => unchanged += 1,
+                Some(earlier) => changed.push(SynapseChange {
+                    synapse: synapse.clone(),
+                    weight_before: earlier.weight,
+                }),
+                None => added.push(synapse.clone()),
+            }
+        }
+
+        // Keep the removed synapses in the order of the earlier snapshot
+        let removed = keyed(&self.synapses)
+            .filter(|(key, _)| before.contains_key(key))
+            .map(|(_, synapse)| synapse.clone())
+            .collect();
+
+        changed.sort_by(|a, b| {
+            b.weight_change()
+                .abs()
+                .partial_cmp(&a.weight_change().abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let total_change = changed
+            .iter()
+            .map(|change| change.weight_change().abs())
+            .sum();
+
+        SynapseDiff {
+            changed,
+            added,
+            removed,
+            unchanged,
+            total_change,
+        }
+    }
+}
+
+fn keyed(synapses: &[SynapseState]) -> impl Iterator<Item = (SynapseKey<'_>, &SynapseState)> {
+    let mut occurrences = HashMap::new();
+
+    synapses.iter().map(move |synapse| {
+        let endpoints = (
+            synapse.source_kind,
+            synapse.source.as_slice(),
+            synapse.target_kind,
+            synapse.target.as_slice(),
+            synapse.kind,
+            synapse.synapse_type,
+        );
+        let occurrence = occurrences.entry(endpoints).or_insert(0);
+        let key = (
+            endpoints.0,
+            endpoints.1,
+            endpoints.2,
+            endpoints.3,
+            endpoints.4,
+            endpoints.5,
+            *occurrence,
+        );
+        *occurrence += 1;
+
+        (key, synapse)
+    })
+}
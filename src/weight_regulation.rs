@@ -0,0 +1,98 @@
+/// Keeps plastic weights from growing until every neuron fires every cycle. Every
+/// mechanism is off by default
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WeightRegulation {
+    /// Upper bound on every learned weight
+    pub max_weight: Option<f32>,
+    pub normalization: Option<Normalization>,
+    pub homeostasis: Option<Homeostasis>,
+}
+
+impl WeightRegulation {
+    /// Upper bound on learned weights, infinite if there isn't any
+    pub(crate) fn weight_bound(&self) -> f32 {
+        self.max_weight.unwrap_or(f32::INFINITY)
+    }
+}
+
+/// Which plastic synapses of a neuron are normalized together
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NormalizationDirection {
+    /// Synapses targeting the neuron
+    Incoming,
+    /// Synapses of the neuron
+    Outgoing,
+}
+
+/// Periodically rescales the plastic synapses of each neuron so that their weights
+/// add up to `total_weight`. Neurons without any weight are left as is
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Normalization {
+    pub direction: NormalizationDirection,
+    pub total_weight: f32,
+    /// Normalizes at the end of every `interval`th cycle, unless it's a static cycle
+    pub interval: u32,
+}
+
+impl Normalization {
+    pub fn new(
+        direction: NormalizationDirection,
+        total_weight: f32,
+        interval: u32,
+    ) -> Normalization {
+        Normalization {
+            direction,
+            total_weight,
+            interval,
+        }
+    }
+}
+
+/// Homeostatic synaptic scaling: every plastic neuron keeps a running average of how
+/// often it fires, and its incoming plastic weights are periodically multiplied by
+/// `1 + scaling_rate * (target_rate - firing_rate)`, so neurons that fire too often
+/// get weaker inputs and neurons that rarely fire get stronger ones
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Homeostasis {
+    /// Fraction of cycles each plastic neuron should fire in
+    pub target_rate: f32,
+    /// Number of cycles the firing rate is averaged over
+    pub rate_time_constant: f32,
+    pub scaling_rate: f32,
+    /// Scales at the end of every `interval`th cycle, unless it's a static cycle
+    pub interval: u32,
+}
+
+impl Homeostasis {
+    pub fn new(
+        target_rate: f32,
+        rate_time_constant: f32,
+        scaling_rate: f32,
+        interval: u32,
+    ) -> Homeostasis {
+        Homeostasis {
+            target_rate,
+            rate_time_constant,
+            scaling_rate,
+            interval,
+        }
+    }
+
+    /// Firing rate after a cycle in which the neuron did or didn't fire
+    pub(crate) fn update_rate(&self, rate: f32, fired: bool) -> f32 {
+        let fired = if fired { 1. } else { 0. };
+
+        rate + (fired - rate) / self.rate_time_constant
+    }
+
+    /// Factor the incoming weights of a neuron firing at `rate` are scaled by
+    pub(crate) fn scale(&self, rate: f32) -> f32 {
+        let scale = 1. + self.scaling_rate * (self.target_rate - rate);
+
+        if scale > 0. {
+            scale
+        } else {
+            0.
+        }
+    }
+}